@@ -18,14 +18,16 @@ use wgpu_rust_renderer::{
 		node::Node,
 		scene::Scene,
 	},
-	utils::gltf_loader::GltfLoader,
+	utils::{
+		gltf_loader::GltfLoader,
+		orbit_controls::OrbitControls,
+	},
 };
 
 async fn create_scene(
 	window: &Window,
 	pools: &mut ResourcePools
-) -> (ResourceId<Scene>, ResourceId<PerspectiveCamera>, Vec<ResourceId<Node>>) {
-	let mut objects = Vec::new();
+) -> (ResourceId<Scene>, ResourceId<PerspectiveCamera>, OrbitControls) {
 	let scene_rid = pools.borrow_mut::<Scene>().add(Scene::new());
 
 	let nodes = GltfLoader::load_gltf(
@@ -43,11 +45,6 @@ async fn create_scene(
 			.borrow_mut(&scene_rid)
 			.unwrap()
 			.add_node(node);
-		objects.push(*node);
-		pools.borrow_mut::<Node>()
-			.borrow_mut(node)
-			.unwrap()
-			.borrow_rotation_mut()[0] = 90.0_f32.to_radians();
 	}
 
 	let window_size = window.inner_size();
@@ -74,7 +71,12 @@ async fn create_scene(
 		scene.assign(&node, &camera);
 	}
 
-	(scene_rid, camera, objects)
+	let mut controls = OrbitControls::new(window, &node);
+	controls
+		.set_enable_damping(true)
+		.set_distance_range(1.0, 10.0);
+
+	(scene_rid, camera, controls)
 }
 
 fn resize(
@@ -95,15 +97,9 @@ fn resize(
 fn update(
 	pools: &mut ResourcePools,
 	scene: &ResourceId<Scene>,
-	objects: &Vec<ResourceId<Node>>,
+	controls: &mut OrbitControls,
 ) {
-	{
-		let node = pools.borrow_mut::<Node>().borrow_mut(&objects[0]).unwrap();
-		Vector3::add(
-			node.borrow_rotation_mut(),
-			&[0.0, 0.0, -0.01],
-		);
-	}
+	controls.update(pools);
 
 	pools.borrow::<Scene>()
 		.borrow(scene)
@@ -117,7 +113,8 @@ fn render(
 	scene: &ResourceId<Scene>,
 	camera: &ResourceId<PerspectiveCamera>,
 ) {
-	renderer.render(pools, scene, camera);
+	// @TODO: Proper error handling
+	renderer.render(pools, scene, camera).unwrap();
 }
 
 #[tokio::main]
@@ -133,7 +130,7 @@ async fn main() {
 	renderer.set_pixel_ratio(pixel_ratio);
 
 	let mut pools = ResourcePools::new();
-	let (scene, camera, objects) = create_scene(&window, &mut pools).await;
+	let (scene, camera, mut controls) = create_scene(&window, &mut pools).await;
 
 	event_loop.run(move |event, _, control_flow| {
 		*control_flow = ControlFlow::Poll;
@@ -142,15 +139,16 @@ async fn main() {
 				event: WindowEvent::Resized(size),
 				..
 			} => {
+				controls.handle_event(&WindowEvent::Resized(size));
 				resize(&mut renderer, &mut pools, &camera, size.width, size.height);
-				update(&mut pools, &scene, &objects);
+				update(&mut pools, &scene, &mut controls);
 				render(&mut renderer, &mut pools, &scene, &camera);
 			},
 			Event::RedrawEventsCleared => {
 				window.request_redraw();
 			},
 			Event::RedrawRequested(_) => {
-				update(&mut pools, &scene, &objects);
+				update(&mut pools, &scene, &mut controls);
 				render(&mut renderer, &mut pools, &scene, &camera);
 			},
 			Event::WindowEvent {
@@ -159,6 +157,9 @@ async fn main() {
 			} => {
 				*control_flow = ControlFlow::Exit;
 			},
+			Event::WindowEvent { event, .. } => {
+				controls.handle_event(&event);
+			},
 			_ => {}
 		}
 	});
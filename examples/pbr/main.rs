@@ -118,7 +118,8 @@ fn render(
 	scene: &ResourceId<Scene>,
 	camera: &ResourceId<PerspectiveCamera>,
 ) {
-	renderer.render(pools, scene, camera);
+	// @TODO: Proper error handling
+	renderer.render(pools, scene, camera).unwrap();
 }
 
 #[tokio::main]
@@ -1,15 +1,49 @@
+pub enum AttributeData {
+	Float32(Vec<f32>),
+	// Normalized to [0.0, 1.0] in shaders
+	Unorm8(Vec<u8>),
+	Unorm16(Vec<u16>),
+}
+
 pub struct Attribute {
 	count: u32,
-	data: Vec<f32>,
-	_item_size: u32,
+	data: AttributeData,
+	item_size: u32,
 }
 
 impl Attribute {
 	pub fn new(data: Vec<f32>, item_size: u32) -> Self {
 		Attribute {
 			count: data.len() as u32 / item_size,
-			data: data,
-			_item_size: item_size,
+			data: AttributeData::Float32(data),
+			item_size,
+		}
+	}
+
+	// There are no three component normalized vertex formats so
+	// three component data is padded to four with the max value,
+	// e.g. RGB colors get 1.0 alpha.
+	pub fn new_unorm8(data: Vec<u8>, item_size: u32) -> Self {
+		let (data, item_size) = match item_size {
+			3 => (pad_to_four(&data, u8::MAX), 4),
+			_ => (data, item_size),
+		};
+		Attribute {
+			count: data.len() as u32 / item_size,
+			data: AttributeData::Unorm8(data),
+			item_size,
+		}
+	}
+
+	pub fn new_unorm16(data: Vec<u16>, item_size: u32) -> Self {
+		let (data, item_size) = match item_size {
+			3 => (pad_to_four(&data, u16::MAX), 4),
+			_ => (data, item_size),
+		};
+		Attribute {
+			count: data.len() as u32 / item_size,
+			data: AttributeData::Unorm16(data),
+			item_size,
 		}
 	}
 
@@ -17,7 +51,37 @@ impl Attribute {
 		self.count
 	}
 
+	pub fn get_item_size(&self) -> u32 {
+		self.item_size
+	}
+
+	// Float32 data only
+	// @TODO: Proper error handling
 	pub fn borrow_data(&self) -> &Vec<f32> {
+		match &self.data {
+			AttributeData::Float32(data) => data,
+			_ => panic!("Attribute data is not Float32"),
+		}
+	}
+
+	pub fn borrow_attribute_data(&self) -> &AttributeData {
 		&self.data
 	}
+
+	pub fn borrow_bytes(&self) -> &[u8] {
+		match &self.data {
+			AttributeData::Float32(data) => bytemuck::cast_slice(data),
+			AttributeData::Unorm8(data) => data,
+			AttributeData::Unorm16(data) => bytemuck::cast_slice(data),
+		}
+	}
+}
+
+fn pad_to_four<T: Copy>(data: &[T], value: T) -> Vec<T> {
+	let mut padded = Vec::with_capacity(data.len() / 3 * 4);
+	for item in data.chunks(3) {
+		padded.extend_from_slice(item);
+		padded.push(value);
+	}
+	padded
 }
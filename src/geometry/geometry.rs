@@ -8,17 +8,38 @@ use crate::{
 	resource::resource::ResourceId,
 };
 
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PrimitiveTopology {
+	LineList,
+	PointList,
+	#[default]
+	TriangleList,
+}
+
+// Range of the index, or of the vertices if the geometry isn't indexed,
+// drawn with the mesh material at material_index
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GeometryGroup {
+	pub count: u32,
+	pub material_index: usize,
+	pub start: u32,
+}
+
 // @TODO: Support shared attribute
 pub struct Geometry {
 	attributes: HashMap<&'static str, ResourceId<Attribute>>,
+	groups: Vec<GeometryGroup>,
 	index: Option<ResourceId<Index>>,
+	topology: PrimitiveTopology,
 }
 
 impl Geometry {
 	pub fn new() -> Self {
 		Geometry {
 			attributes: HashMap::new(),
+			groups: Vec::new(),
 			index: None,
+			topology: PrimitiveTopology::default(),
 		}
 	}
 
@@ -31,6 +52,10 @@ impl Geometry {
 		self.attributes.get(key)
 	}
 
+	pub fn borrow_attributes(&self) -> &HashMap<&'static str, ResourceId<Attribute>> {
+		&self.attributes
+	}
+
 	pub fn set_index(&mut self, index: ResourceId<Index>) -> &mut Self {
 		self.index = Some(index);
 		self
@@ -44,4 +69,32 @@ impl Geometry {
 	pub fn borrow_index(&self) -> Option<&ResourceId<Index>> {
 		self.index.as_ref()
 	}
+
+	// Without groups the whole geometry is drawn with the first material
+	pub fn add_group(&mut self, start: u32, count: u32, material_index: usize) -> &mut Self {
+		self.groups.push(GeometryGroup {
+			count,
+			material_index,
+			start,
+		});
+		self
+	}
+
+	pub fn clear_groups(&mut self) -> &mut Self {
+		self.groups.clear();
+		self
+	}
+
+	pub fn borrow_groups(&self) -> &Vec<GeometryGroup> {
+		&self.groups
+	}
+
+	pub fn set_topology(&mut self, topology: PrimitiveTopology) -> &mut Self {
+		self.topology = topology;
+		self
+	}
+
+	pub fn borrow_topology(&self) -> &PrimitiveTopology {
+		&self.topology
+	}
 }
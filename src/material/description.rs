@@ -0,0 +1,712 @@
+use std::collections::HashMap;
+use serde::{
+	Deserialize,
+	Serialize,
+};
+use crate::{
+	material::{
+		error::{
+			MaterialError,
+			MaterialErrorKind,
+		},
+		material::{
+			Material,
+			Side,
+		},
+		node::{
+			abs::AbsNode,
+			add::AddNode,
+			brdf::{
+				BRDFNode,
+				BRDFNodeDescriptor,
+			},
+			camera_position::CameraPositionNode,
+			clamp::ClampNode,
+			compose::ComposeNode,
+			const_float::ConstFloatNode,
+			const_vector3::ConstVector3Node,
+			cos::CosNode,
+			cross::CrossNode,
+			custom_code::{
+				CustomCodeInput,
+				CustomCodeNode,
+				CustomCodeNodeDescriptor,
+			},
+			delta_time::DeltaTimeNode,
+			dot::DotNode,
+			float::FloatNode,
+			fract::FractNode,
+			length::LengthNode,
+			linear_to_srgb::LinearToSRGBNode,
+			max::MaxNode,
+			min::MinNode,
+			mix::MixNode,
+			multiply::MultiplyNode,
+			node::{
+				MaterialNode,
+				ValueType,
+			},
+			normal::NormalNode,
+			normal_matrix::NormalMatrixNode,
+			normalize::NormalizeNode,
+			one_minus::OneMinusNode,
+			position::PositionNode,
+			pow::PowNode,
+			resolution::ResolutionNode,
+			saturate::SaturateNode,
+			screen_position::ScreenPositionNode,
+			sin::SinNode,
+			smoothstep::SmoothstepNode,
+			srgb_to_linear::SRGBToLinearNode,
+			step::StepNode,
+			sub::SubNode,
+			swizzle::SwizzleNode,
+			tangent_to_object_normal::TangentToObjectNormalNode,
+			texture::TextureNode,
+			time::TimeNode,
+			varying::VaryingNode,
+			vector3::Vector3Node,
+			vertex_color::VertexColorNode,
+			view_direction::ViewDirectionNode,
+			world_position::WorldPositionNode,
+			x::XNode,
+			xyz::XYZNode,
+			y::YNode,
+			z::ZNode,
+		},
+	},
+	resource::resource::{
+		ResourceId,
+		ResourcePool,
+	},
+	texture::{
+		sampler::Sampler,
+		texture::Texture,
+	},
+};
+
+// Node type and parameters. N refers to input nodes, T to textures
+// and S to samplers. In memory they are resource ids, in files they
+// are node indices and texture and sampler names.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(tag = "type")]
+pub enum NodeDescription<N, T, S> {
+	Abs {node: N},
+	Add {value1: N, value2: N},
+	BRDF {base_color: N, metallic: N, normal: N, roughness: N},
+	CameraPosition,
+	Clamp {value: N, min: N, max: N},
+	Compose {nodes: Vec<N>},
+	ConstFloat {value: f32},
+	ConstVector3 {value: [f32; 3]},
+	Cos {node: N},
+	Cross {value1: N, value2: N},
+	CustomCode {
+		body: String,
+		functions: Vec<(String, String)>,
+		inputs: Vec<(String, N, ValueType)>,
+		output_type: ValueType,
+	},
+	DeltaTime,
+	Dot {value1: N, value2: N},
+	Float {value: f32},
+	Fract {node: N},
+	Length {node: N},
+	LinearToSRGB {node: N},
+	Max {value1: N, value2: N},
+	Min {value1: N, value2: N},
+	Mix {value1: N, value2: N, factor: N},
+	Multiply {value1: N, value2: N},
+	Normal,
+	NormalMatrix,
+	Normalize {node: N},
+	OneMinus {node: N},
+	Position,
+	Pow {base: N, exponent: N},
+	Resolution,
+	Saturate {node: N},
+	ScreenPosition,
+	Sin {node: N},
+	Smoothstep {low: N, high: N, value: N},
+	SRGBToLinear {node: N},
+	Step {edge: N, value: N},
+	Sub {value1: N, value2: N},
+	Swizzle {node: N, components: String},
+	TangentToObjectNormal {node: N},
+	Texture {texture: T, sampler: S},
+	Time,
+	Varying {node: N},
+	Vector3 {value: [f32; 3]},
+	VertexColor,
+	ViewDirection,
+	WorldPosition,
+	XYZ {node: N},
+	X {node: N},
+	Y {node: N},
+	Z {node: N},
+}
+
+pub type ResourceNodeDescription = NodeDescription<
+	ResourceId<Box<dyn MaterialNode>>,
+	ResourceId<Texture>,
+	ResourceId<Sampler>,
+>;
+
+pub type FileNodeDescription = NodeDescription<usize, String, String>;
+
+impl<N, T, S> NodeDescription<N, T, S> {
+	// Converts the node, texture and sampler references
+	pub fn map<N2, T2, S2, E>(
+		self,
+		mut node: impl FnMut(N) -> Result<N2, E>,
+		texture: impl FnOnce(T) -> Result<T2, E>,
+		sampler: impl FnOnce(S) -> Result<S2, E>,
+	) -> Result<NodeDescription<N2, T2, S2>, E> {
+		Ok(match self {
+			NodeDescription::Abs {node: n} => NodeDescription::Abs {node: node(n)?},
+			NodeDescription::Add {value1, value2} => NodeDescription::Add {
+				value1: node(value1)?,
+				value2: node(value2)?,
+			},
+			NodeDescription::BRDF {base_color, metallic, normal, roughness} => NodeDescription::BRDF {
+				base_color: node(base_color)?,
+				metallic: node(metallic)?,
+				normal: node(normal)?,
+				roughness: node(roughness)?,
+			},
+			NodeDescription::CameraPosition => NodeDescription::CameraPosition,
+			NodeDescription::Clamp {value, min, max} => NodeDescription::Clamp {
+				value: node(value)?,
+				min: node(min)?,
+				max: node(max)?,
+			},
+			NodeDescription::Compose {nodes} => NodeDescription::Compose {
+				nodes: nodes.into_iter().map(&mut node).collect::<Result<Vec<N2>, E>>()?,
+			},
+			NodeDescription::ConstFloat {value} => NodeDescription::ConstFloat {value},
+			NodeDescription::ConstVector3 {value} => NodeDescription::ConstVector3 {value},
+			NodeDescription::Cos {node: n} => NodeDescription::Cos {node: node(n)?},
+			NodeDescription::Cross {value1, value2} => NodeDescription::Cross {
+				value1: node(value1)?,
+				value2: node(value2)?,
+			},
+			NodeDescription::CustomCode {body, functions, inputs, output_type} => NodeDescription::CustomCode {
+				body,
+				functions,
+				inputs: inputs.into_iter().map(|(name, n, value_type)| {
+					Ok((name, node(n)?, value_type))
+				}).collect::<Result<Vec<(String, N2, ValueType)>, E>>()?,
+				output_type,
+			},
+			NodeDescription::DeltaTime => NodeDescription::DeltaTime,
+			NodeDescription::Dot {value1, value2} => NodeDescription::Dot {
+				value1: node(value1)?,
+				value2: node(value2)?,
+			},
+			NodeDescription::Float {value} => NodeDescription::Float {value},
+			NodeDescription::Fract {node: n} => NodeDescription::Fract {node: node(n)?},
+			NodeDescription::Length {node: n} => NodeDescription::Length {node: node(n)?},
+			NodeDescription::LinearToSRGB {node: n} => NodeDescription::LinearToSRGB {node: node(n)?},
+			NodeDescription::Max {value1, value2} => NodeDescription::Max {
+				value1: node(value1)?,
+				value2: node(value2)?,
+			},
+			NodeDescription::Min {value1, value2} => NodeDescription::Min {
+				value1: node(value1)?,
+				value2: node(value2)?,
+			},
+			NodeDescription::Mix {value1, value2, factor} => NodeDescription::Mix {
+				value1: node(value1)?,
+				value2: node(value2)?,
+				factor: node(factor)?,
+			},
+			NodeDescription::Multiply {value1, value2} => NodeDescription::Multiply {
+				value1: node(value1)?,
+				value2: node(value2)?,
+			},
+			NodeDescription::Normal => NodeDescription::Normal,
+			NodeDescription::NormalMatrix => NodeDescription::NormalMatrix,
+			NodeDescription::Normalize {node: n} => NodeDescription::Normalize {node: node(n)?},
+			NodeDescription::OneMinus {node: n} => NodeDescription::OneMinus {node: node(n)?},
+			NodeDescription::Position => NodeDescription::Position,
+			NodeDescription::Pow {base, exponent} => NodeDescription::Pow {
+				base: node(base)?,
+				exponent: node(exponent)?,
+			},
+			NodeDescription::Resolution => NodeDescription::Resolution,
+			NodeDescription::Saturate {node: n} => NodeDescription::Saturate {node: node(n)?},
+			NodeDescription::ScreenPosition => NodeDescription::ScreenPosition,
+			NodeDescription::Sin {node: n} => NodeDescription::Sin {node: node(n)?},
+			NodeDescription::Smoothstep {low, high, value} => NodeDescription::Smoothstep {
+				low: node(low)?,
+				high: node(high)?,
+				value: node(value)?,
+			},
+			NodeDescription::SRGBToLinear {node: n} => NodeDescription::SRGBToLinear {node: node(n)?},
+			NodeDescription::Step {edge, value} => NodeDescription::Step {
+				edge: node(edge)?,
+				value: node(value)?,
+			},
+			NodeDescription::Sub {value1, value2} => NodeDescription::Sub {
+				value1: node(value1)?,
+				value2: node(value2)?,
+			},
+			NodeDescription::Swizzle {node: n, components} => NodeDescription::Swizzle {
+				node: node(n)?,
+				components,
+			},
+			NodeDescription::TangentToObjectNormal {node: n} => NodeDescription::TangentToObjectNormal {node: node(n)?},
+			NodeDescription::Texture {texture: t, sampler: s} => NodeDescription::Texture {
+				texture: texture(t)?,
+				sampler: sampler(s)?,
+			},
+			NodeDescription::Time => NodeDescription::Time,
+			NodeDescription::Varying {node: n} => NodeDescription::Varying {node: node(n)?},
+			NodeDescription::Vector3 {value} => NodeDescription::Vector3 {value},
+			NodeDescription::VertexColor => NodeDescription::VertexColor,
+			NodeDescription::ViewDirection => NodeDescription::ViewDirection,
+			NodeDescription::WorldPosition => NodeDescription::WorldPosition,
+			NodeDescription::XYZ {node: n} => NodeDescription::XYZ {node: node(n)?},
+			NodeDescription::X {node: n} => NodeDescription::X {node: node(n)?},
+			NodeDescription::Y {node: n} => NodeDescription::Y {node: node(n)?},
+			NodeDescription::Z {node: n} => NodeDescription::Z {node: node(n)?},
+		})
+	}
+}
+
+impl ResourceNodeDescription {
+	pub fn create_node(self) -> Box<dyn MaterialNode> {
+		match self {
+			NodeDescription::Abs {node} => Box::new(AbsNode::new(node)),
+			NodeDescription::Add {value1, value2} => Box::new(AddNode::new(value1, value2)),
+			NodeDescription::BRDF {base_color, metallic, normal, roughness} => Box::new(BRDFNode::new(
+				BRDFNodeDescriptor {
+					base_color,
+					metallic,
+					normal,
+					roughness,
+				},
+			)),
+			NodeDescription::CameraPosition => Box::new(CameraPositionNode::new()),
+			NodeDescription::Clamp {value, min, max} => Box::new(ClampNode::new(value, min, max)),
+			NodeDescription::Compose {nodes} => Box::new(ComposeNode::new(nodes)),
+			NodeDescription::ConstFloat {value} => Box::new(ConstFloatNode::new(value)),
+			NodeDescription::ConstVector3 {value} => Box::new(ConstVector3Node::new(value)),
+			NodeDescription::Cos {node} => Box::new(CosNode::new(node)),
+			NodeDescription::Cross {value1, value2} => Box::new(CrossNode::new(value1, value2)),
+			NodeDescription::CustomCode {body, functions, inputs, output_type} => Box::new(CustomCodeNode::new(
+				CustomCodeNodeDescriptor {
+					body,
+					functions,
+					inputs: inputs.into_iter().map(|(name, node, value_type)| CustomCodeInput {
+						name,
+						node,
+						value_type,
+					}).collect(),
+					output_type,
+				},
+			)),
+			NodeDescription::DeltaTime => Box::new(DeltaTimeNode::new()),
+			NodeDescription::Dot {value1, value2} => Box::new(DotNode::new(value1, value2)),
+			NodeDescription::Float {value} => Box::new(FloatNode::new(value)),
+			NodeDescription::Fract {node} => Box::new(FractNode::new(node)),
+			NodeDescription::Length {node} => Box::new(LengthNode::new(node)),
+			NodeDescription::LinearToSRGB {node} => Box::new(LinearToSRGBNode::new(node)),
+			NodeDescription::Max {value1, value2} => Box::new(MaxNode::new(value1, value2)),
+			NodeDescription::Min {value1, value2} => Box::new(MinNode::new(value1, value2)),
+			NodeDescription::Mix {value1, value2, factor} => Box::new(MixNode::new(value1, value2, factor)),
+			NodeDescription::Multiply {value1, value2} => Box::new(MultiplyNode::new(value1, value2)),
+			NodeDescription::Normal => Box::new(NormalNode::new()),
+			NodeDescription::NormalMatrix => Box::new(NormalMatrixNode::new()),
+			NodeDescription::Normalize {node} => Box::new(NormalizeNode::new(node)),
+			NodeDescription::OneMinus {node} => Box::new(OneMinusNode::new(node)),
+			NodeDescription::Position => Box::new(PositionNode::new()),
+			NodeDescription::Pow {base, exponent} => Box::new(PowNode::new(base, exponent)),
+			NodeDescription::Resolution => Box::new(ResolutionNode::new()),
+			NodeDescription::Saturate {node} => Box::new(SaturateNode::new(node)),
+			NodeDescription::ScreenPosition => Box::new(ScreenPositionNode::new()),
+			NodeDescription::Sin {node} => Box::new(SinNode::new(node)),
+			NodeDescription::Smoothstep {low, high, value} => Box::new(SmoothstepNode::new(low, high, value)),
+			NodeDescription::SRGBToLinear {node} => Box::new(SRGBToLinearNode::new(node)),
+			NodeDescription::Step {edge, value} => Box::new(StepNode::new(edge, value)),
+			NodeDescription::Sub {value1, value2} => Box::new(SubNode::new(value1, value2)),
+			NodeDescription::Swizzle {node, components} => Box::new(SwizzleNode::new(node, &components)),
+			NodeDescription::TangentToObjectNormal {node} => Box::new(TangentToObjectNormalNode::new(node)),
+			NodeDescription::Texture {texture, sampler} => Box::new(TextureNode::new(texture, sampler)),
+			NodeDescription::Time => Box::new(TimeNode::new()),
+			NodeDescription::Varying {node} => Box::new(VaryingNode::new(node)),
+			NodeDescription::Vector3 {value} => Box::new(Vector3Node::new(value)),
+			NodeDescription::VertexColor => Box::new(VertexColorNode::new()),
+			NodeDescription::ViewDirection => Box::new(ViewDirectionNode::new()),
+			NodeDescription::WorldPosition => Box::new(WorldPositionNode::new()),
+			NodeDescription::XYZ {node} => Box::new(XYZNode::new(node)),
+			NodeDescription::X {node} => Box::new(XNode::new(node)),
+			NodeDescription::Y {node} => Box::new(YNode::new(node)),
+			NodeDescription::Z {node} => Box::new(ZNode::new(node)),
+		}
+	}
+}
+
+// Material and its node graph as data, e.g. to author materials in
+// files. Nodes are sorted so that inputs come first and refer to their
+// inputs by index. Textures and samplers are referred to by name and
+// resolved by the application when the material is created.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct MaterialDescription {
+	pub color: usize,
+	pub nodes: Vec<FileNodeDescription>,
+	#[serde(default)]
+	pub position_offset: Option<usize>,
+	#[serde(default)]
+	pub side: Side,
+}
+
+impl MaterialDescription {
+	// Textures and samplers missing in the name maps are named by id.
+	// Returns an error if a node can't be described.
+	pub fn from_material(
+		material: &Material,
+		pool: &ResourcePool<Box<dyn MaterialNode>>,
+		texture_names: &HashMap<ResourceId<Texture>, String>,
+		sampler_names: &HashMap<ResourceId<Sampler>, String>,
+	) -> Result<Self, MaterialError> {
+		let mut indices = HashMap::new();
+		let mut nodes = Vec::new();
+
+		for node_id in material.borrow_nodes(pool).iter() {
+			let desc = match pool.borrow(node_id).unwrap().describe() {
+				Some(desc) => desc,
+				None => return Err(MaterialError::new(
+					MaterialErrorKind::Description,
+					format!("Node {} can not be described", node_id.id),
+					None,
+					Some(*node_id),
+				)),
+			};
+			let desc = desc.map(
+				|node| Ok::<usize, ()>(*indices.get(&node).unwrap()),
+				|texture| Ok(match texture_names.get(&texture) {
+					Some(name) => name.clone(),
+					None => format!("texture_{}", texture.id),
+				}),
+				|sampler| Ok(match sampler_names.get(&sampler) {
+					Some(name) => name.clone(),
+					None => format!("sampler_{}", sampler.id),
+				}),
+			).unwrap();
+			indices.insert(*node_id, nodes.len());
+			nodes.push(desc);
+		}
+
+		Ok(MaterialDescription {
+			color: *indices.get(material.borrow_color()).unwrap(),
+			nodes,
+			position_offset: material.borrow_position_offset().map(|node| *indices.get(node).unwrap()),
+			side: *material.borrow_side(),
+		})
+	}
+
+	// Adds the nodes to the pool
+	pub fn create_material(
+		&self,
+		pool: &mut ResourcePool<Box<dyn MaterialNode>>,
+		textures: &HashMap<String, ResourceId<Texture>>,
+		samplers: &HashMap<String, ResourceId<Sampler>>,
+	) -> Result<Material, MaterialError> {
+		// Validates everything first so that no nodes are added on error
+		for (index, desc) in self.nodes.iter().enumerate() {
+			desc.clone().map(
+				|node| match node < index {
+					true => Ok(node),
+					false => Err(description_error(format!("Node {} refers to node {} which is not before it", index, node))),
+				},
+				|texture| match textures.contains_key(&texture) {
+					true => Ok(texture),
+					false => Err(description_error(format!("Unknown texture {} in node {}", texture, index))),
+				},
+				|sampler| match samplers.contains_key(&sampler) {
+					true => Ok(sampler),
+					false => Err(description_error(format!("Unknown sampler {} in node {}", sampler, index))),
+				},
+			)?;
+			check_parameters(desc).map_err(|message| {
+				description_error(format!("{} in node {}", message, index))
+			})?;
+		}
+
+		for (name, index) in [("color", Some(self.color)), ("position_offset", self.position_offset)].iter() {
+			if let Some(index) = index {
+				if *index >= self.nodes.len() {
+					return Err(description_error(format!("{} refers to unknown node {}", name, index)));
+				}
+			}
+		}
+
+		let mut node_ids = Vec::new();
+		for desc in self.nodes.iter() {
+			let desc = desc.clone().map(
+				|node| Ok::<ResourceId<Box<dyn MaterialNode>>, ()>(node_ids[node]),
+				|texture| Ok(*textures.get(&texture).unwrap()),
+				|sampler| Ok(*samplers.get(&sampler).unwrap()),
+			).unwrap();
+			node_ids.push(pool.add(desc.create_node()));
+		}
+
+		let mut material = Material::new(node_ids[self.color], self.side);
+		material.set_position_offset(self.position_offset.map(|index| node_ids[index]));
+		Ok(material)
+	}
+
+	pub fn from_json(json: &str) -> Result<Self, MaterialError> {
+		serde_json::from_str(json).map_err(|error| {
+			MaterialError::new(
+				MaterialErrorKind::Description,
+				error.to_string(),
+				Some(error.line()),
+				None,
+			)
+		})
+	}
+
+	pub fn to_json(&self) -> String {
+		serde_json::to_string_pretty(self).unwrap()
+	}
+}
+
+fn description_error(message: String) -> MaterialError {
+	MaterialError::new(
+		MaterialErrorKind::Description,
+		message,
+		None,
+		None,
+	)
+}
+
+// Node constructors panic on invalid parameters
+fn check_parameters(desc: &FileNodeDescription) -> Result<(), String> {
+	match desc {
+		NodeDescription::Compose {nodes} if nodes.is_empty() => {
+			Err("Compose needs at least one node".to_string())
+		},
+		NodeDescription::Swizzle {components, ..} if components.is_empty() ||
+			components.len() > 4 ||
+			!components.chars().all(|c| "xyzwrgba".contains(c)) => {
+			Err(format!("Invalid swizzle components {}", components))
+		},
+		_ => Ok(()),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::HashMap;
+	use crate::{
+		material::{
+			description::MaterialDescription,
+			error::MaterialErrorKind,
+			function_registry::FunctionRegistry,
+			material::{
+				Material,
+				Side,
+			},
+			node::{
+				float::FloatNode,
+				multiply::MultiplyNode,
+				node::{
+					MaterialNode,
+					UniformContents,
+					ValueType,
+				},
+				normal::NormalNode,
+				texture::TextureNode,
+				vector3::Vector3Node,
+				xyz::XYZNode,
+			},
+		},
+		resource::resource::{
+			ResourceId,
+			ResourcePool,
+		},
+		texture::{
+			sampler::{
+				Sampler,
+				SamplerDescriptor,
+			},
+			texture::{
+				Texture,
+				TextureFormat,
+			},
+		},
+	};
+
+	// Defined outside of the built-in nodes, doesn't implement describe()
+	struct ExternalNode {
+	}
+
+	impl MaterialNode for ExternalNode {
+		fn collect_nodes (
+			&self,
+			_pool: &ResourcePool<Box<dyn MaterialNode>>,
+			nodes: &mut Vec<ResourceId<Box<dyn MaterialNode>>>,
+			visited: &mut HashMap<ResourceId<Box<dyn MaterialNode>>, bool>,
+			self_rid: ResourceId<Box<dyn MaterialNode>>,
+		) {
+			if visited.insert(self_rid, true).is_none() {
+				nodes.push(self_rid);
+			}
+		}
+
+		fn borrow_contents(&self) -> Option<&UniformContents> {
+			None
+		}
+
+		fn build_declaration(&self, _self_id: usize) -> String {
+			String::new()
+		}
+
+		fn register_functions(&self, _registry: &mut FunctionRegistry, _self_id: usize) {
+		}
+
+		fn build_fragment_shader(
+			&self,
+			_pool: &ResourcePool<Box<dyn MaterialNode>>,
+			_visited: &mut HashMap<usize, bool>,
+			_self_id: usize,
+		) -> String {
+			String::new()
+		}
+
+		fn get_fragment_output(&self, _self_id: usize) -> String {
+			"vec3<f32>(1.0, 0.0, 0.0)".to_string()
+		}
+
+		fn get_input_types(
+			&self,
+			_pool: &ResourcePool<Box<dyn MaterialNode>>,
+		) -> Vec<(ResourceId<Box<dyn MaterialNode>>, ValueType)> {
+			Vec::new()
+		}
+
+		fn get_output_type(
+			&self,
+			_pool: &ResourcePool<Box<dyn MaterialNode>>,
+		) -> ValueType {
+			ValueType::Vector3
+		}
+	}
+
+	#[test]
+	fn from_material_round_trip() {
+		let mut pool: ResourcePool<Box<dyn MaterialNode>> = ResourcePool::new();
+		let color = pool.add(Box::new(Vector3Node::new([1.0, 0.0, 0.0])));
+		let material = Material::new(color, Side::default());
+
+		let desc = MaterialDescription::from_material(&material, &pool, &HashMap::new(), &HashMap::new()).unwrap();
+		let desc = MaterialDescription::from_json(&desc.to_json()).unwrap();
+
+		let mut pool2 = ResourcePool::new();
+		let material2 = desc.create_material(&mut pool2, &HashMap::new(), &HashMap::new()).unwrap();
+		assert_eq!(material.build_shader_code(&pool), material2.build_shader_code(&pool2));
+	}
+
+	#[test]
+	fn from_material_reports_undescribable_node() {
+		let mut pool: ResourcePool<Box<dyn MaterialNode>> = ResourcePool::new();
+		let color = pool.add(Box::new(ExternalNode {}));
+		let material = Material::new(color, Side::default());
+		assert!(material.validate(&pool).is_ok());
+
+		let error = MaterialDescription::from_material(&material, &pool, &HashMap::new(), &HashMap::new()).unwrap_err();
+		assert_eq!(error.get_kind(), MaterialErrorKind::Description);
+		assert_eq!(error.borrow_node(), Some(&color));
+	}
+
+	#[test]
+	fn from_material_round_trip_with_texture_and_position_offset() {
+		let mut textures = ResourcePool::new();
+		let texture = textures.add(Texture::new(1, 1, TextureFormat::default(), vec![255; 4]));
+		let mut samplers = ResourcePool::new();
+		let sampler = samplers.add(Sampler::new(SamplerDescriptor::default()));
+
+		// Added in the input first order so that the node ids match
+		// the ones created from the description
+		let mut pool: ResourcePool<Box<dyn MaterialNode>> = ResourcePool::new();
+		let texture_node = pool.add(Box::new(TextureNode::new(texture, sampler)));
+		let rgb = pool.add(Box::new(XYZNode::new(texture_node)));
+		let tint = pool.add(Box::new(Vector3Node::new([1.0, 0.5, 0.25])));
+		let color = pool.add(Box::new(MultiplyNode::new(rgb, tint)));
+		let normal = pool.add(Box::new(NormalNode::new()));
+		let scale = pool.add(Box::new(FloatNode::new(0.1)));
+		let offset = pool.add(Box::new(MultiplyNode::new(normal, scale)));
+		let mut material = Material::new(color, Side::DoubleSide);
+		material.set_position_offset(Some(offset));
+		assert!(material.validate(&pool).is_ok());
+
+		let mut texture_names = HashMap::new();
+		texture_names.insert(texture, "albedo".to_string());
+		let mut sampler_names = HashMap::new();
+		sampler_names.insert(sampler, "linear".to_string());
+		let desc = MaterialDescription::from_material(&material, &pool, &texture_names, &sampler_names).unwrap();
+		let json = desc.to_json();
+		assert!(json.contains("\"albedo\""));
+		assert!(json.contains("\"linear\""));
+
+		let desc = MaterialDescription::from_json(&json).unwrap();
+		assert_eq!(desc.nodes.len(), 7);
+		assert_eq!(desc.color, 3);
+		assert_eq!(desc.position_offset, Some(6));
+
+		let mut texture_ids = HashMap::new();
+		texture_ids.insert("albedo".to_string(), texture);
+		let mut sampler_ids = HashMap::new();
+		sampler_ids.insert("linear".to_string(), sampler);
+		let mut pool2 = ResourcePool::new();
+		let material2 = desc.create_material(&mut pool2, &texture_ids, &sampler_ids).unwrap();
+		assert!(matches!(material2.borrow_side(), Side::DoubleSide));
+		assert!(material2.validate(&pool2).is_ok());
+		assert_eq!(material.build_shader_code(&pool), material2.build_shader_code(&pool2));
+	}
+
+	#[test]
+	fn create_material_reports_invalid_description() {
+		let mut textures = ResourcePool::new();
+		let texture = textures.add(Texture::new(1, 1, TextureFormat::default(), vec![255; 4]));
+		let mut samplers = ResourcePool::new();
+		let sampler = samplers.add(Sampler::new(SamplerDescriptor::default()));
+		let mut texture_ids = HashMap::new();
+		texture_ids.insert("albedo".to_string(), texture);
+		let mut sampler_ids = HashMap::new();
+		sampler_ids.insert("linear".to_string(), sampler);
+
+		for json in [
+			// Forward reference
+			r#"{"color": 0, "nodes": [{"type": "Abs", "node": 1}, {"type": "Float", "value": 1.0}]}"#,
+			// Self reference
+			r#"{"color": 0, "nodes": [{"type": "Abs", "node": 0}]}"#,
+			// Unknown texture
+			r#"{"color": 0, "nodes": [{"type": "Texture", "texture": "missing", "sampler": "linear"}]}"#,
+			// Unknown sampler
+			r#"{"color": 0, "nodes": [{"type": "Texture", "texture": "albedo", "sampler": "missing"}]}"#,
+			// Bad swizzle
+			r#"{"color": 1, "nodes": [{"type": "Float", "value": 1.0}, {"type": "Swizzle", "node": 0, "components": "xq"}]}"#,
+			r#"{"color": 1, "nodes": [{"type": "Float", "value": 1.0}, {"type": "Swizzle", "node": 0, "components": "xyzwx"}]}"#,
+			// Unknown color and position_offset nodes
+			r#"{"color": 1, "nodes": [{"type": "Float", "value": 1.0}]}"#,
+			r#"{"color": 0, "position_offset": 1, "nodes": [{"type": "Float", "value": 1.0}]}"#,
+		].iter() {
+			let desc = MaterialDescription::from_json(json).unwrap();
+			let mut pool: ResourcePool<Box<dyn MaterialNode>> = ResourcePool::new();
+			let error = match desc.create_material(&mut pool, &texture_ids, &sampler_ids) {
+				Ok(_) => panic!("{} is accepted", json),
+				Err(error) => error,
+			};
+			assert_eq!(error.get_kind(), MaterialErrorKind::Description, "{}", json);
+
+			// No nodes are added on error
+			assert_eq!(pool.add(Box::new(NormalNode::new())).id, 0);
+		}
+
+		let error = MaterialDescription::from_json(r#"{"color": 0, "nodes": [{"type": "Unknown"}]}"#).unwrap_err();
+		assert_eq!(error.get_kind(), MaterialErrorKind::Description);
+	}
+}
@@ -0,0 +1,90 @@
+use std::{
+	error::Error,
+	fmt,
+};
+use crate::{
+	material::node::node::MaterialNode,
+	resource::resource::ResourceId,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MaterialErrorKind {
+	Description,
+	Parse,
+	Type,
+	Validation,
+}
+
+// Returned by Material::validate(). line is 1-based in the code
+// Material::build_shader_code() returns, or
+// Material::build_instanced_shader_code() if instanced, and node is
+// the material node which emitted that line, if any.
+#[derive(Debug)]
+pub struct MaterialError {
+	instanced: bool,
+	kind: MaterialErrorKind,
+	line: Option<usize>,
+	message: String,
+	node: Option<ResourceId<Box<dyn MaterialNode>>>,
+}
+
+impl MaterialError {
+	pub fn new(
+		kind: MaterialErrorKind,
+		message: String,
+		line: Option<usize>,
+		node: Option<ResourceId<Box<dyn MaterialNode>>>,
+	) -> Self {
+		MaterialError {
+			instanced: false,
+			kind,
+			line,
+			message,
+			node,
+		}
+	}
+
+	pub fn set_instanced(&mut self, instanced: bool) -> &mut Self {
+		self.instanced = instanced;
+		self
+	}
+
+	pub fn is_instanced(&self) -> bool {
+		self.instanced
+	}
+
+	pub fn get_kind(&self) -> MaterialErrorKind {
+		self.kind
+	}
+
+	pub fn get_line(&self) -> Option<usize> {
+		self.line
+	}
+
+	pub fn borrow_message(&self) -> &str {
+		&self.message
+	}
+
+	pub fn borrow_node(&self) -> Option<&ResourceId<Box<dyn MaterialNode>>> {
+		self.node.as_ref()
+	}
+}
+
+impl fmt::Display for MaterialError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{:?} error", self.kind)?;
+		if let Some(node) = self.node {
+			write!(f, " in material node {}", node.id)?;
+		}
+		if let Some(line) = self.line {
+			write!(f, " at line {}", line)?;
+		}
+		if self.instanced {
+			write!(f, " of the instanced shader")?;
+		}
+		write!(f, ": {}", self.message)
+	}
+}
+
+impl Error for MaterialError {
+}
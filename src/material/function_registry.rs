@@ -0,0 +1,48 @@
+use std::collections::HashMap;
+
+// Collects the helper functions material nodes need so that each chunk
+// is emitted once per shader even if several nodes register it.
+// Chunks are keyed by name. Nodes in this crate use their module path,
+// user node libraries should use their own paths to avoid collisions.
+pub struct FunctionRegistry {
+	chunks: Vec<String>,
+	names: HashMap<String, usize>,
+}
+
+impl Default for FunctionRegistry {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl FunctionRegistry {
+	pub fn new() -> Self {
+		FunctionRegistry {
+			chunks: Vec::new(),
+			names: HashMap::new(),
+		}
+	}
+
+	// Returns false and ignores the chunk if the name is already registered
+	pub fn register(&mut self, name: &str, chunk: &str) -> bool {
+		if self.names.contains_key(name) {
+			return false;
+		}
+		self.names.insert(name.to_string(), self.chunks.len());
+		self.chunks.push(chunk.to_string());
+		true
+	}
+
+	pub fn has(&self, name: &str) -> bool {
+		self.names.contains_key(name)
+	}
+
+	pub fn get_count(&self) -> usize {
+		self.chunks.len()
+	}
+
+	// In registration order
+	pub fn borrow_chunks(&self) -> &Vec<String> {
+		&self.chunks
+	}
+}
@@ -1,8 +1,35 @@
 use std::collections::HashMap;
+use naga::{
+	front::wgsl,
+	valid::{
+		Capabilities,
+		EntryPointError,
+		FunctionError,
+		ValidationError,
+		ValidationFlags,
+		Validator,
+	},
+};
+use serde::{
+	Deserialize,
+	Serialize,
+};
 use crate::{
-	material::node::node::{
-		MaterialNode,
-		UniformContents,
+	material::{
+		error::{
+			MaterialError,
+			MaterialErrorKind,
+		},
+		function_registry::FunctionRegistry,
+		node::{
+			node::{
+				MaterialNode,
+				UniformContents,
+				ValueType,
+				get_converted_fragment_output,
+			},
+			varying::get_varying_name,
+		},
 	},
 	resource::resource::{
 		ResourceId,
@@ -14,6 +41,7 @@ use crate::{
 	},
 };
 
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
 pub enum Side {
 	BackSide,
 	DoubleSide,
@@ -31,12 +59,19 @@ const PREFIX_CHUNK1: &str = "struct VertexOutput {
   [[location(1)]] normal: vec3<f32>;
   [[location(2)]] uv: vec2<f32>;
   [[location(3)]] view_position: vec3<f32>;
-};
+  [[location(4)]] world_position: vec3<f32>;
+  [[location(5)]] local_position: vec3<f32>;
+  [[location(6)]] color: vec4<f32>;
+  [[location(7)]] tangent: vec4<f32>;
+";
+
+const PREFIX_CHUNK2: &str = "};
 
 [[block]]
 struct Object {
   model_view_matrix: mat4x4<f32>;
   normal_matrix: mat3x3<f32>;
+  model_matrix: mat4x4<f32>;
 };
 
 [[block]]
@@ -44,11 +79,20 @@ struct Camera {
   projection_matrix: mat4x4<f32>;
 };
 
+[[block]]
+struct Globals {
+  camera_position: vec3<f32>;
+  time: f32;
+  resolution: vec2<f32>;
+  delta_time: f32;
+  camera_direction: vec3<f32>;
+};
+
 [[block]]
 struct Uniform {
 ";
 
-const PREFIX_CHUNK2: &str = "};
+const PREFIX_CHUNK3: &str = "};
 
 [[group(0), binding(0)]]
 var<uniform> object: Object;
@@ -58,9 +102,12 @@ var<uniform> camera: Camera;
 
 [[group(0), binding(2)]]
 var<uniform> unif: Uniform;
+
+[[group(0), binding(3)]]
+var<uniform> globals: Globals;
 ";
 
-const PREFIX_CHUNK3: &str = "
+const PREFIX_CHUNK4: &str = "
 let PI: f32 = 3.1415926535;
 
 fn less_than_equal_f32(value1: f32, value2: f32) -> f32 {
@@ -101,19 +148,61 @@ fn linear_to_srgb(value: vec4<f32>) -> vec4<f32> {
 }
 ";
 
-const VERTEX_CHUNK: &str = "
+const VERTEX_CHUNK1: &str = "
 [[stage(vertex)]]
 fn vs_main(
   [[location(0)]] position: vec3<f32>,
   [[location(1)]] normal: vec3<f32>,
   [[location(2)]] uv: vec2<f32>,
+  [[location(3)]] color: vec4<f32>,
+  [[location(4)]] tangent: vec4<f32>,
+";
+
+const VERTEX_OBJECT_CHUNK: &str = ") -> VertexOutput {
+  let model_matrix = object.model_matrix;
+  let model_view_matrix = object.model_view_matrix;
+  let normal_matrix = object.normal_matrix;
+  let vertex_color = color;
+";
+
+// The instance matrix is applied before the object matrices.
+// The normal matrix is the cofactor matrix, the inverse transpose
+// scaled by the determinant, because the normal is normalized anyway.
+const VERTEX_INSTANCE_CHUNK: &str = "  [[location(5)]] instance_matrix0: vec4<f32>,
+  [[location(6)]] instance_matrix1: vec4<f32>,
+  [[location(7)]] instance_matrix2: vec4<f32>,
+  [[location(8)]] instance_matrix3: vec4<f32>,
+  [[location(9)]] instance_color: vec4<f32>,
 ) -> VertexOutput {
+  let instance_matrix = mat4x4<f32>(instance_matrix0, instance_matrix1, instance_matrix2, instance_matrix3);
+  let model_matrix = object.model_matrix * instance_matrix;
+  let model_view_matrix = object.model_view_matrix * instance_matrix;
+  let mv0 = model_view_matrix[0].xyz;
+  let mv1 = model_view_matrix[1].xyz;
+  let mv2 = model_view_matrix[2].xyz;
+  let normal_matrix = mat3x3<f32>(cross(mv1, mv2), cross(mv2, mv0), cross(mv0, mv1)) *
+    sign(dot(mv0, cross(mv1, mv2)));
+  let vertex_color = color * instance_color;
+";
+
+const VERTEX_CHUNK2: &str = "
   var out: VertexOutput;
-  let mv_position = object.model_view_matrix * vec4<f32>(position, 1.0);
-  out.position = camera.projection_matrix * mv_position;
-  out.normal = normalize(object.normal_matrix * normal);
+  out.normal = normalize(normal_matrix * normal);
   out.uv = uv;
+  out.view_position = -(model_view_matrix * vec4<f32>(position, 1.0)).xyz;
+  out.world_position = (model_matrix * vec4<f32>(position, 1.0)).xyz;
+  out.local_position = position;
+  out.color = vertex_color;
+  out.tangent = vec4<f32>((model_view_matrix * vec4<f32>(tangent.xyz, 0.0)).xyz, tangent.w);
+  // Vertex stage nodes read the same inputs as fragment stage nodes
+  let in = out;
+";
+
+const VERTEX_CHUNK3: &str = "
+  let mv_position = model_view_matrix * vec4<f32>(offset_position, 1.0);
+  out.position = camera.projection_matrix * mv_position;
   out.view_position = -mv_position.xyz;
+  out.world_position = (model_matrix * vec4<f32>(offset_position, 1.0)).xyz;
   return out;
 }
 ";
@@ -141,6 +230,7 @@ const FRAGMENT_CHUNK2: &str = "
 
 pub struct Material {
 	color: ResourceId<Box<dyn MaterialNode>>,
+	position_offset: Option<ResourceId<Box<dyn MaterialNode>>>,
 	side: Side,
 }
 
@@ -151,16 +241,36 @@ impl Material {
 	) -> Self {
 		Material {
 			color: color,
+			position_offset: None,
 			side: side,
 		}
 	}
 
+	pub fn borrow_color(&self) -> &ResourceId<Box<dyn MaterialNode>> {
+		&self.color
+	}
+
 	pub fn borrow_side(&self) -> &Side {
 		&self.side
 	}
 
+	// Object space offset added to the vertex position in the vertex
+	// stage, e.g. for displacement.
+	pub fn set_position_offset(
+		&mut self,
+		position_offset: Option<ResourceId<Box<dyn MaterialNode>>>,
+	) -> &mut Self {
+		self.position_offset = position_offset;
+		self
+	}
+
+	pub fn borrow_position_offset(&self) -> Option<&ResourceId<Box<dyn MaterialNode>>> {
+		self.position_offset.as_ref()
+	}
+
+	// All the nodes of the color and position offset graphs, inputs first
 	// @TODO: Optimize?
-	fn borrow_nodes(
+	pub fn borrow_nodes(
 		&self,
 		pool: &ResourcePool<Box<dyn MaterialNode>>,
 	) -> Vec<ResourceId<Box<dyn MaterialNode>>> {
@@ -172,9 +282,54 @@ impl Material {
 			&mut visited,
 			self.color,
 		);
+		if let Some(position_offset) = self.position_offset {
+			pool.borrow(&position_offset).unwrap().collect_nodes(
+				pool,
+				&mut nodes,
+				&mut visited,
+				position_offset,
+			);
+		}
+		nodes
+	}
+
+	// Nodes evaluated in the fragment stage, inputs first.
+	// Inputs of varying nodes are evaluated in the vertex stage.
+	fn collect_fragment_nodes(
+		&self,
+		pool: &ResourcePool<Box<dyn MaterialNode>>,
+	) -> Vec<ResourceId<Box<dyn MaterialNode>>> {
+		let mut nodes = Vec::new();
+		let mut visited = HashMap::new();
+		collect_stage_nodes(pool, &self.color, true, &mut nodes, &mut visited);
+		nodes
+	}
+
+	// Nodes evaluated in the vertex stage, inputs first.
+	fn collect_vertex_nodes(
+		&self,
+		pool: &ResourcePool<Box<dyn MaterialNode>>,
+	) -> Vec<ResourceId<Box<dyn MaterialNode>>> {
+		let mut nodes = Vec::new();
+		let mut visited = HashMap::new();
+		if let Some(position_offset) = self.position_offset.as_ref() {
+			collect_stage_nodes(pool, position_offset, false, &mut nodes, &mut visited);
+		}
+		for node_id in self.collect_varying_nodes(pool).iter() {
+			collect_stage_nodes(pool, node_id, false, &mut nodes, &mut visited);
+		}
 		nodes
 	}
 
+	fn collect_varying_nodes(
+		&self,
+		pool: &ResourcePool<Box<dyn MaterialNode>>,
+	) -> Vec<ResourceId<Box<dyn MaterialNode>>> {
+		self.collect_fragment_nodes(pool).into_iter().filter(|node_id| {
+			pool.borrow(node_id).unwrap().is_varying()
+		}).collect()
+	}
+
 	// @TODO: Optimize?
 	pub fn borrow_contents<'a>(
 		&self,
@@ -238,68 +393,242 @@ impl Material {
 		&self,
 		pool: &ResourcePool<Box<dyn MaterialNode>>,
 	) -> String {
-		self.build_prefix(pool) +
-		&self.build_vertex_shader() +
-		&self.build_fragment_shader(pool)
+		self.build_shader_source(pool, false).code
 	}
 
-	fn build_prefix(
+	// For InstancedMesh, reads the instance matrix from vertex locations
+	// 5 to 8 and the instance color from location 9
+	pub fn build_instanced_shader_code(
 		&self,
 		pool: &ResourcePool<Box<dyn MaterialNode>>,
 	) -> String {
-		PREFIX_CHUNK1.to_string() +
-		&self.build_uniform_block_declaration(pool) +
-		PREFIX_CHUNK2 +
-		&self.build_texture_declaration(pool) +
-		PREFIX_CHUNK3 +
-		&self.build_functions(pool)
+		self.build_shader_source(pool, true).code
+	}
+
+	// Parses and validates the generated shader code with naga on CPU.
+	// No GPU device is needed so material node graphs can be checked
+	// before the render pipeline is created. Both the shader code for
+	// Mesh and for InstancedMesh are validated.
+	pub fn validate(
+		&self,
+		pool: &ResourcePool<Box<dyn MaterialNode>>,
+	) -> Result<(), MaterialError> {
+		self.check_types(pool)?;
+		self.check_variable_names(pool)?;
+		validate_source(&self.build_shader_source(pool, false))?;
+		validate_source(&self.build_shader_source(pool, true)).map_err(|mut error| {
+			error.set_instanced(true);
+			error
+		})
+	}
+
+	// Checks that the output of every input node can be converted to
+	// the type the node expects. Allowed conversions are inserted
+	// automatically when the shader code is built.
+	pub fn check_types(
+		&self,
+		pool: &ResourcePool<Box<dyn MaterialNode>>,
+	) -> Result<(), MaterialError> {
+		for node_id in self.borrow_nodes(pool).iter() {
+			let node = pool.borrow(node_id).unwrap();
+			for (input, value_type) in node.get_input_types(pool).iter() {
+				let input_type = pool.borrow(input).unwrap().get_output_type(pool);
+				if !input_type.can_convert(value_type) {
+					return Err(MaterialError::new(
+						MaterialErrorKind::Type,
+						format!("Can not convert {:?} output of node {} to {:?}", input_type, input.id, value_type),
+						None,
+						Some(*node_id),
+					));
+				}
+			}
+		}
+
+		let color_type = pool.borrow(&self.color).unwrap().get_output_type(pool);
+		if !color_type.can_convert(&ValueType::Vector3) {
+			return Err(MaterialError::new(
+				MaterialErrorKind::Type,
+				format!("Can not use {:?} output as color", color_type),
+				None,
+				Some(self.color),
+			));
+		}
+
+		if let Some(position_offset) = self.position_offset {
+			let offset_type = pool.borrow(&position_offset).unwrap().get_output_type(pool);
+			if !offset_type.can_convert(&ValueType::Vector3) {
+				return Err(MaterialError::new(
+					MaterialErrorKind::Type,
+					format!("Can not use {:?} output as position offset", offset_type),
+					None,
+					Some(position_offset),
+				));
+			}
+		}
+
+		Ok(())
+	}
+
+	// Checks that no two nodes declare variables with the same name.
+	// Outputs other than identifiers, e.g. in.normal or constants, are
+	// expressions and don't declare variables.
+	pub fn check_variable_names(
+		&self,
+		pool: &ResourcePool<Box<dyn MaterialNode>>,
+	) -> Result<(), MaterialError> {
+		let mut names = HashMap::new();
+		for node_id in self.borrow_nodes(pool).iter() {
+			let output = pool.borrow(node_id).unwrap().get_fragment_output(node_id.id);
+			if !is_identifier(&output) {
+				continue;
+			}
+			if let Some(other_id) = names.insert(output.clone(), *node_id) {
+				return Err(MaterialError::new(
+					MaterialErrorKind::Validation,
+					format!("Variable name {} is also declared by node {}", output, other_id.id),
+					None,
+					Some(*node_id),
+				));
+			}
+		}
+		Ok(())
 	}
 
-	fn build_vertex_shader(&self) -> String {
-		VERTEX_CHUNK.to_string()
+	fn build_shader_source(
+		&self,
+		pool: &ResourcePool<Box<dyn MaterialNode>>,
+		instanced: bool,
+	) -> ShaderSource {
+		let mut source = ShaderSource::new();
+		self.build_prefix(pool, &mut source);
+		self.build_vertex_shader(pool, &mut source, instanced);
+		self.build_fragment_shader(pool, &mut source);
+		source
+	}
+
+	fn build_prefix(
+		&self,
+		pool: &ResourcePool<Box<dyn MaterialNode>>,
+		source: &mut ShaderSource,
+	) {
+		source.push(PREFIX_CHUNK1, None);
+		self.build_varying_declaration(pool, source);
+		source.push(PREFIX_CHUNK2, None);
+		self.build_uniform_block_declaration(pool, source);
+		source.push(PREFIX_CHUNK3, None);
+		self.build_texture_declaration(pool, source);
+		source.push(PREFIX_CHUNK4, None);
+		self.build_functions(pool, source);
+	}
+
+	fn build_vertex_shader(
+		&self,
+		pool: &ResourcePool<Box<dyn MaterialNode>>,
+		source: &mut ShaderSource,
+		instanced: bool,
+	) {
+		source.push(VERTEX_CHUNK1, None);
+		source.push(match instanced {
+			true => VERTEX_INSTANCE_CHUNK,
+			false => VERTEX_OBJECT_CHUNK,
+		}, None);
+		source.push(VERTEX_CHUNK2, None);
+
+		let mut visited = HashMap::new();
+		for node_id in self.collect_vertex_nodes(pool).iter() {
+			source.push(
+				&pool.borrow(node_id).unwrap().build_vertex_shader(pool, &mut visited, node_id.id),
+				Some(*node_id),
+			);
+		}
+
+		match self.position_offset {
+			Some(position_offset) => {
+				source.push(
+					&format!("let offset_position = position + {};\n",
+						get_converted_fragment_output(pool, &position_offset, &ValueType::Vector3),
+					),
+					Some(position_offset),
+				);
+			},
+			None => {
+				source.push("let offset_position = position;\n", None);
+			},
+		};
+		source.push(VERTEX_CHUNK3, None);
 	}
 
 	fn build_fragment_shader(
 		&self,
 		pool: &ResourcePool<Box<dyn MaterialNode>>,
-	) -> String {
+		source: &mut ShaderSource,
+	) {
+		source.push(FRAGMENT_CHUNK1, None);
+
+		// Nodes are sorted so that inputs come first. Then each node emits
+		// only its own code because its inputs are already visited.
 		let mut visited = HashMap::new();
-		let color = pool.borrow(&self.color).unwrap();
+		for node_id in self.collect_fragment_nodes(pool).iter() {
+			source.push(
+				&pool.borrow(node_id).unwrap().build_fragment_shader(pool, &mut visited, node_id.id),
+				Some(*node_id),
+			);
+		}
 
-		FRAGMENT_CHUNK1.to_string() +
-		&color.build_fragment_shader(pool, &mut visited, self.color.id) +
-		&format!("var color: vec3<f32> = {};\n", color.get_fragment_output(self.color.id)) +
-		&FRAGMENT_CHUNK2.to_string()
+		source.push(
+			&format!("var color: vec3<f32> = {};\n",
+				get_converted_fragment_output(pool, &self.color, &ValueType::Vector3),
+			),
+			Some(self.color),
+		);
+		source.push(FRAGMENT_CHUNK2, None);
+	}
+
+	// Varyings start with location 8
+	fn build_varying_declaration(
+		&self,
+		pool: &ResourcePool<Box<dyn MaterialNode>>,
+		source: &mut ShaderSource,
+	) {
+		for (i, node_id) in self.collect_varying_nodes(pool).iter().enumerate() {
+			source.push(
+				&format!("  [[location({})]] {}: {};\n",
+					i + 8,
+					get_varying_name(node_id.id),
+					pool.borrow(node_id).unwrap().get_output_type(pool).get_wgsl_type(),
+				),
+				Some(*node_id),
+			);
+		}
 	}
 
 	// @TODO: Optimize?
 	fn build_uniform_block_declaration(
 		&self,
 		pool: &ResourcePool<Box<dyn MaterialNode>>,
-	) -> String {
-		// bindings for textures start with 3
-		let mut s = "".to_string();
+		source: &mut ShaderSource,
+	) {
 		for node_id in self.borrow_nodes(pool).iter() {
 			let node = pool.borrow(node_id).unwrap();
 			if let Some(contents) = node.borrow_contents() {
 				match contents {
 					UniformContents::Texture {..} => {},
 					_ => {
-						s += &node.build_declaration(node_id.id);
+						source.push(&node.build_declaration(node_id.id), Some(*node_id));
 					},
 				}
 			}
 		}
-		s
 	}
 
 	// @TODO: Optimize?
 	fn build_texture_declaration(
 		&self,
 		pool: &ResourcePool<Box<dyn MaterialNode>>,
-	) -> String {
-		// bindings for textures start with 3
-		let mut binding = 3;
+		source: &mut ShaderSource,
+	) {
+		// bindings for textures start with 4
+		let mut binding = 4;
 		let mut s = "".to_string();
 
 		// Textures first
@@ -315,17 +644,443 @@ impl Material {
 			s += &format!("var sampler_{}: sampler;\n", sampler.id);
 			binding += 1;
 		}
-		s
+		source.push(&s, None);
 	}
 
 	fn build_functions(
 		&self,
 		pool: &ResourcePool<Box<dyn MaterialNode>>,
-	) -> String {
-		let mut s = "".to_string();
+		source: &mut ShaderSource,
+	) {
+		let mut registry = FunctionRegistry::new();
 		for node_id in self.borrow_nodes(pool).iter() {
-			s += &pool.borrow(node_id).unwrap().build_functions(node_id.id);
+			let count = registry.get_count();
+			pool.borrow(node_id).unwrap().register_functions(&mut registry, node_id.id);
+			for chunk in registry.borrow_chunks()[count..].iter() {
+				source.push(chunk, Some(*node_id));
+			}
+		}
+	}
+}
+
+// Sorts the nodes so that inputs come first. Stops at varying nodes
+// if stop_at_varying is true because their inputs are evaluated in
+// the vertex stage.
+fn collect_stage_nodes(
+	pool: &ResourcePool<Box<dyn MaterialNode>>,
+	node_id: &ResourceId<Box<dyn MaterialNode>>,
+	stop_at_varying: bool,
+	nodes: &mut Vec<ResourceId<Box<dyn MaterialNode>>>,
+	visited: &mut HashMap<ResourceId<Box<dyn MaterialNode>>, bool>,
+) {
+	if visited.contains_key(node_id) {
+		return;
+	}
+	visited.insert(*node_id, true);
+
+	let node = pool.borrow(node_id).unwrap();
+	if !(stop_at_varying && node.is_varying()) {
+		for (input, _) in node.get_input_types(pool).iter() {
+			collect_stage_nodes(pool, input, stop_at_varying, nodes, visited);
+		}
+	}
+	nodes.push(*node_id);
+}
+
+// Generated shader code with the material node each line comes from
+struct ShaderSource {
+	code: String,
+	nodes: Vec<Option<ResourceId<Box<dyn MaterialNode>>>>,
+}
+
+impl ShaderSource {
+	fn new() -> Self {
+		ShaderSource {
+			code: "".to_string(),
+			nodes: Vec::new(),
+		}
+	}
+
+	fn push(&mut self, code: &str, node: Option<ResourceId<Box<dyn MaterialNode>>>) {
+		for c in code.chars() {
+			if self.code.is_empty() || self.code.ends_with('\n') {
+				self.nodes.push(node);
+			}
+			self.code.push(c);
+		}
+	}
+
+	// line is 1-based
+	fn get_node(&self, line: usize) -> Option<ResourceId<Box<dyn MaterialNode>>> {
+		match line {
+			0 => None,
+			_ => self.nodes.get(line - 1).cloned().flatten(),
+		}
+	}
+}
+
+fn get_error_message(error: &ValidationError) -> String {
+	let mut message = error.to_string();
+	let mut source = std::error::Error::source(error);
+	while let Some(error) = source {
+		message += &format!(": {}", error);
+		source = error.source();
+	}
+	message
+}
+
+fn is_identifier(name: &str) -> bool {
+	match name.chars().next() {
+		Some(c) if c.is_ascii_alphabetic() || c == '_' => {},
+		_ => return false,
+	};
+	name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn validate_source(source: &ShaderSource) -> Result<(), MaterialError> {
+	let module = match wgsl::parse_str(&source.code) {
+		Ok(module) => module,
+		Err(error) => {
+			let (line, _column) = error.location(&source.code);
+			return Err(MaterialError::new(
+				MaterialErrorKind::Parse,
+				error.to_string(),
+				Some(line),
+				source.get_node(line),
+			));
+		},
+	};
+
+	let mut validator = Validator::new(ValidationFlags::all(), Capabilities::empty());
+	if let Err(error) = validator.validate(&module) {
+		let line = get_error_span(&module, &error).map(|span| {
+			source.code[..span.start].matches('\n').count() + 1
+		});
+		return Err(MaterialError::new(
+			MaterialErrorKind::Validation,
+			get_error_message(&error),
+			line,
+			line.and_then(|line| source.get_node(line)),
+		));
+	}
+
+	Ok(())
+}
+
+fn get_error_span(
+	module: &naga::Module,
+	error: &ValidationError,
+) -> Option<std::ops::Range<usize>> {
+	match error {
+		ValidationError::Function {handle, error, ..} => {
+			get_function_error_span(&module.functions[*handle], error)
+		},
+		ValidationError::EntryPoint {name, error: EntryPointError::Function(error), ..} => {
+			match module.entry_points.iter().find(|entry_point| &entry_point.name == name) {
+				Some(entry_point) => get_function_error_span(&entry_point.function, error),
+				None => None,
+			}
+		},
+		_ => None,
+	}
+}
+
+fn get_function_error_span(
+	function: &naga::Function,
+	error: &FunctionError,
+) -> Option<std::ops::Range<usize>> {
+	let expression = match error {
+		FunctionError::LocalVariable {handle, ..} => {
+			return function.local_variables.get_span(*handle).to_range();
+		},
+		FunctionError::Expression {handle, ..} |
+		FunctionError::ExpressionAlreadyInScope(handle) |
+		FunctionError::InvalidReturnType(Some(handle)) |
+		FunctionError::InvalidIfType(handle) |
+		FunctionError::InvalidSwitchType(handle) |
+		FunctionError::InvalidStorePointer(handle) |
+		FunctionError::InvalidStoreValue(handle) |
+		FunctionError::InvalidStoreTypes {value: handle, ..} |
+		FunctionError::InvalidExpression(handle) |
+		FunctionError::NonUniformControlFlow(_, handle, _) => handle,
+		_ => return None,
+	};
+	function.expressions.get_span(*expression).to_range()
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::HashMap;
+	use crate::{
+		material::{
+			error::MaterialErrorKind,
+			function_registry::FunctionRegistry,
+			material::{
+				Material,
+				Side,
+			},
+			node::{
+				add::AddNode,
+				custom_code::{
+					CustomCodeInput,
+					CustomCodeNode,
+					CustomCodeNodeDescriptor,
+				},
+				float::FloatNode,
+				node::{
+					MaterialNode,
+					UniformContents,
+					ValueType,
+				},
+				normal_matrix::NormalMatrixNode,
+				screen_position::ScreenPositionNode,
+				swizzle::SwizzleNode,
+				tangent_to_object_normal::TangentToObjectNormalNode,
+				vector3::Vector3Node,
+				view_direction::ViewDirectionNode,
+				xyz::XYZNode,
+			},
+		},
+		resource::resource::{
+			ResourceId,
+			ResourcePool,
+		},
+	};
+
+	// Declares a variable with the same name regardless of the node id
+	struct FixedNameNode {
+		node: Option<ResourceId<Box<dyn MaterialNode>>>,
+	}
+
+	impl MaterialNode for FixedNameNode {
+		fn collect_nodes (
+			&self,
+			pool: &ResourcePool<Box<dyn MaterialNode>>,
+			nodes: &mut Vec<ResourceId<Box<dyn MaterialNode>>>,
+			visited: &mut HashMap<ResourceId<Box<dyn MaterialNode>>, bool>,
+			self_rid: ResourceId<Box<dyn MaterialNode>>,
+		) {
+			if let Some(node) = self.node {
+				pool.borrow(&node).unwrap().collect_nodes(pool, nodes, visited, node);
+			}
+			if visited.insert(self_rid, true).is_none() {
+				nodes.push(self_rid);
+			}
 		}
-		s
+
+		fn borrow_contents(&self) -> Option<&UniformContents> {
+			None
+		}
+
+		fn build_declaration(&self, _self_id: usize) -> String {
+			String::new()
+		}
+
+		fn register_functions(&self, _registry: &mut FunctionRegistry, _self_id: usize) {
+		}
+
+		fn build_fragment_shader(
+			&self,
+			pool: &ResourcePool<Box<dyn MaterialNode>>,
+			_visited: &mut HashMap<usize, bool>,
+			_self_id: usize,
+		) -> String {
+			let value = match self.node {
+				Some(node) => pool.borrow(&node).unwrap().get_fragment_output(node.id),
+				None => "vec3<f32>(1.0, 0.0, 0.0)".to_string(),
+			};
+			format!("let fixed_output: vec3<f32> = {};\n", value)
+		}
+
+		fn get_fragment_output(&self, _self_id: usize) -> String {
+			"fixed_output".to_string()
+		}
+
+		fn get_input_types(
+			&self,
+			_pool: &ResourcePool<Box<dyn MaterialNode>>,
+		) -> Vec<(ResourceId<Box<dyn MaterialNode>>, ValueType)> {
+			self.node.iter().map(|node| (*node, ValueType::Vector3)).collect()
+		}
+
+		fn get_output_type(
+			&self,
+			_pool: &ResourcePool<Box<dyn MaterialNode>>,
+		) -> ValueType {
+			ValueType::Vector3
+		}
+	}
+
+	fn add(
+		pool: &mut ResourcePool<Box<dyn MaterialNode>>,
+		node: Box<dyn MaterialNode>,
+	) -> ResourceId<Box<dyn MaterialNode>> {
+		pool.add(node)
+	}
+
+	#[test]
+	fn validate_valid_material() {
+		let mut pool = ResourcePool::new();
+		let float = add(&mut pool, Box::new(FloatNode::new(0.5)));
+		let vector3 = add(&mut pool, Box::new(Vector3Node::new([1.0, 0.0, 0.0])));
+		let sum = add(&mut pool, Box::new(AddNode::new(vector3, float)));
+		let material = Material::new(sum, Side::default());
+		assert!(material.validate(&pool).is_ok());
+	}
+
+	#[test]
+	fn check_types_reports_input_mismatch() {
+		let mut pool = ResourcePool::new();
+		let matrix = add(&mut pool, Box::new(NormalMatrixNode::new()));
+		let vector3 = add(&mut pool, Box::new(Vector3Node::new([1.0, 0.0, 0.0])));
+		let sum = add(&mut pool, Box::new(AddNode::new(vector3, matrix)));
+		let material = Material::new(sum, Side::default());
+
+		let error = material.check_types(&pool).unwrap_err();
+		assert_eq!(error.get_kind(), MaterialErrorKind::Type);
+		assert_eq!(error.borrow_node(), Some(&sum));
+		assert_eq!(error.get_line(), None);
+
+		// validate() checks the types first
+		let error = material.validate(&pool).unwrap_err();
+		assert_eq!(error.get_kind(), MaterialErrorKind::Type);
+	}
+
+	#[test]
+	fn check_types_reports_color_mismatch() {
+		let mut pool = ResourcePool::new();
+		let matrix = add(&mut pool, Box::new(NormalMatrixNode::new()));
+		let material = Material::new(matrix, Side::default());
+
+		let error = material.check_types(&pool).unwrap_err();
+		assert_eq!(error.get_kind(), MaterialErrorKind::Type);
+		assert_eq!(error.borrow_node(), Some(&matrix));
+	}
+
+	#[test]
+	fn check_types_accepts_conversions() {
+		let mut pool = ResourcePool::new();
+		let float = add(&mut pool, Box::new(FloatNode::new(0.5)));
+		let xyz = add(&mut pool, Box::new(XYZNode::new(float)));
+		let material = Material::new(xyz, Side::default());
+		assert!(material.check_types(&pool).is_ok());
+		assert!(material.validate(&pool).is_ok());
+	}
+
+	#[test]
+	fn validate_reports_line_and_node() {
+		let mut pool = ResourcePool::new();
+		let vector3 = add(&mut pool, Box::new(Vector3Node::new([1.0, 0.0, 0.0])));
+		let custom = add(&mut pool, Box::new(CustomCodeNode::new(CustomCodeNodeDescriptor {
+			body: "return a.x * unknown_value;".to_string(),
+			functions: Vec::new(),
+			inputs: vec![CustomCodeInput {
+				name: "a".to_string(),
+				node: vector3,
+				value_type: ValueType::Vector3,
+			}],
+			output_type: ValueType::Float,
+		})));
+		let sum = add(&mut pool, Box::new(AddNode::new(vector3, custom)));
+		let material = Material::new(sum, Side::default());
+
+		assert!(material.check_types(&pool).is_ok());
+
+		let error = material.validate(&pool).unwrap_err();
+		assert!(!error.is_instanced());
+		assert_eq!(error.borrow_node(), Some(&custom));
+
+		let line = error.get_line().unwrap();
+		let code = material.build_shader_code(&pool);
+		assert!(code.lines().nth(line - 1).unwrap().contains("unknown_value"));
+	}
+
+	#[test]
+	fn validate_reports_validation_error_node() {
+		let mut pool = ResourcePool::new();
+		let vector3 = add(&mut pool, Box::new(Vector3Node::new([1.0, 0.0, 0.0])));
+		let custom = add(&mut pool, Box::new(CustomCodeNode::new(CustomCodeNodeDescriptor {
+			body: "return a;".to_string(),
+			functions: Vec::new(),
+			inputs: vec![CustomCodeInput {
+				name: "a".to_string(),
+				node: vector3,
+				value_type: ValueType::Vector3,
+			}],
+			output_type: ValueType::Float,
+		})));
+		let sum = add(&mut pool, Box::new(AddNode::new(vector3, custom)));
+		let material = Material::new(sum, Side::default());
+
+		let error = material.validate(&pool).unwrap_err();
+		assert_eq!(error.get_kind(), MaterialErrorKind::Validation);
+		assert_eq!(error.borrow_node(), Some(&custom));
+	}
+
+	#[test]
+	fn check_variable_names_reports_duplicates() {
+		let mut pool = ResourcePool::new();
+		let first = add(&mut pool, Box::new(FixedNameNode {node: None}));
+		let second = add(&mut pool, Box::new(FixedNameNode {node: Some(first)}));
+		let material = Material::new(second, Side::default());
+
+		assert!(material.check_types(&pool).is_ok());
+
+		let error = material.check_variable_names(&pool).unwrap_err();
+		assert_eq!(error.get_kind(), MaterialErrorKind::Validation);
+		assert_eq!(error.borrow_node(), Some(&second));
+
+		let error = material.validate(&pool).unwrap_err();
+		assert_eq!(error.borrow_node(), Some(&second));
+	}
+
+	#[test]
+	fn check_variable_names_accepts_shared_expressions() {
+		let mut pool = ResourcePool::new();
+		let float1 = add(&mut pool, Box::new(FloatNode::new(0.5)));
+		let float2 = add(&mut pool, Box::new(FloatNode::new(0.5)));
+		let sum1 = add(&mut pool, Box::new(AddNode::new(float1, float2)));
+		let sum2 = add(&mut pool, Box::new(AddNode::new(sum1, float2)));
+		let material = Material::new(sum2, Side::default());
+		assert!(material.check_variable_names(&pool).is_ok());
+		assert!(material.validate(&pool).is_ok());
+	}
+
+	#[test]
+	fn validate_view_direction() {
+		let mut pool = ResourcePool::new();
+		let view_direction = add(&mut pool, Box::new(ViewDirectionNode::new()));
+		let mut material = Material::new(view_direction, Side::default());
+		material.set_position_offset(Some(view_direction));
+		assert!(material.validate(&pool).is_ok());
+	}
+
+	#[test]
+	fn validate_screen_position_in_vertex_stage() {
+		let mut pool = ResourcePool::new();
+		let screen_position = add(&mut pool, Box::new(ScreenPositionNode::new()));
+		let xyx = add(&mut pool, Box::new(SwizzleNode::new(screen_position, "xyx")));
+		let mut material = Material::new(xyx, Side::default());
+		material.set_position_offset(Some(xyx));
+		assert!(material.validate(&pool).is_ok());
+
+		let code = material.build_shader_code(&pool);
+		let vertex_code = code.split("fn fs_main").next().unwrap();
+		assert!(!vertex_code.contains("in.position"));
+	}
+
+	#[test]
+	fn validate_tangent_to_object_normal_in_vertex_stage() {
+		let mut pool = ResourcePool::new();
+		let vector3 = add(&mut pool, Box::new(Vector3Node::new([0.0, 0.0, 1.0])));
+		let normal = add(&mut pool, Box::new(TangentToObjectNormalNode::new(vector3)));
+		let mut material = Material::new(normal, Side::default());
+		material.set_position_offset(Some(normal));
+		assert!(material.validate(&pool).is_ok());
+
+		let code = material.build_shader_code(&pool);
+		let vertex_code = code.split("fn fs_main").next().unwrap();
+		let vertex_code = vertex_code.split("fn vs_main").nth(1).unwrap();
+		assert!(!vertex_code.contains("perturb_normal_with_tangent("));
 	}
 }
@@ -1,2 +1,5 @@
+pub mod description;
+pub mod error;
+pub mod function_registry;
 pub mod material;
 pub mod node;
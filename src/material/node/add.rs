@@ -1,8 +1,17 @@
 use std::collections::HashMap;
 use crate::{
-	material::node::node::{
-		MaterialNode,
-		UniformContents,
+	material::{
+		description::{
+			NodeDescription,
+			ResourceNodeDescription,
+		},
+		function_registry::FunctionRegistry,
+		node::node::{
+			MaterialNode,
+			UniformContents,
+			ValueType,
+			get_converted_fragment_output,
+		},
 	},
 	resource::resource::{
 		ResourceId,
@@ -55,8 +64,7 @@ impl MaterialNode for AddNode {
 		format!("")
 	}
 
-	fn build_functions(&self, _self_id: usize) -> String {
-		format!("")
+	fn register_functions(&self, _registry: &mut FunctionRegistry, _self_id: usize) {
 	}
 
 	fn build_fragment_shader(
@@ -72,17 +80,43 @@ impl MaterialNode for AddNode {
 
 		let value1 = pool.borrow(&self.value1).unwrap();
 		let value2 = pool.borrow(&self.value2).unwrap();
+		let input_types = self.get_input_types(pool);
 
 		value1.build_fragment_shader(pool, visited, self.value1.id) +
 		&value2.build_fragment_shader(pool, visited, self.value2.id) +
 		&format!("let {} = {} + {};\n",
 			self.get_fragment_output(self_id),
-			value1.get_fragment_output(self.value1.id),
-			value2.get_fragment_output(self.value2.id),
+			get_converted_fragment_output(pool, &self.value1, &input_types[0].1),
+			get_converted_fragment_output(pool, &self.value2, &input_types[1].1),
 		)
 	}
 
 	fn get_fragment_output(&self, self_id: usize) -> String {
 		format!("add_output_{}", self_id)
 	}
+
+	fn get_input_types(
+		&self,
+		pool: &ResourcePool<Box<dyn MaterialNode>>,
+	) -> Vec<(ResourceId<Box<dyn MaterialNode>>, ValueType)> {
+		let value_type = self.get_output_type(pool);
+		vec![(self.value1, value_type), (self.value2, value_type)]
+	}
+
+	fn get_output_type(
+		&self,
+		pool: &ResourcePool<Box<dyn MaterialNode>>,
+	) -> ValueType {
+		ValueType::get_common_type(
+			&pool.borrow(&self.value1).unwrap().get_output_type(pool),
+			&pool.borrow(&self.value2).unwrap().get_output_type(pool),
+		)
+	}
+
+	fn describe(&self) -> Option<ResourceNodeDescription> {
+		Some(NodeDescription::Add {
+			value1: self.value1,
+			value2: self.value2,
+		})
+	}
 }
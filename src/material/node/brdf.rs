@@ -1,8 +1,17 @@
 use std::collections::HashMap;
 use crate::{
-	material::node::node::{
-		MaterialNode,
-		UniformContents,
+	material::{
+		description::{
+			NodeDescription,
+			ResourceNodeDescription,
+		},
+		function_registry::FunctionRegistry,
+		node::node::{
+			MaterialNode,
+			UniformContents,
+			ValueType,
+			get_converted_fragment_output,
+		},
 	},
 	resource::resource::{
 		ResourceId,
@@ -109,9 +118,8 @@ impl MaterialNode for BRDFNode {
 		format!("")
 	}
 
-	fn build_functions(&self, _self_id: usize) -> String {
-		// @TODO: Add self_id suffix for unique function name
-		FUNCTION_CHUNK.to_string()
+	fn register_functions(&self, registry: &mut FunctionRegistry, _self_id: usize) {
+		registry.register(module_path!(), FUNCTION_CHUNK);
 	}
 
 	fn build_fragment_shader(
@@ -134,11 +142,12 @@ impl MaterialNode for BRDFNode {
 		&metallic.build_fragment_shader(pool, visited, self.desc.metallic.id) +
 		&normal.build_fragment_shader(pool, visited, self.desc.normal.id) +
 		&roughness.build_fragment_shader(pool, visited, self.desc.roughness.id) +
-		&format!("let brdf_v_{} = normalize(in.view_position);\n", self_id) +
+		// Orthographic projection has parallel view rays along the view space +Z
+		&format!("let brdf_v_{} = select(normalize(in.view_position), vec3<f32>(0.0, 0.0, 1.0), camera.projection_matrix[3][3] == 1.0);\n", self_id) +
 		&format!("let brdf_l_{} = normalize(light_dir);\n", self_id) +
 		&format!("let brdf_n_{} = normalize({});\n",
 			self_id,
-			normal.get_fragment_output(self.desc.normal.id),
+			get_converted_fragment_output(pool, &self.desc.normal, &ValueType::Vector3),
 		) +
 		&format!("let brdf_h_{} = normalize(brdf_l_{} + brdf_v_{});\n", self_id, self_id, self_id) +
 		&format!("let {} = brdf(brdf_v_{}, brdf_n_{}, brdf_h_{}, brdf_l_{}, {}, {}, {});\n",
@@ -147,9 +156,9 @@ impl MaterialNode for BRDFNode {
 			self_id,
 			self_id,
 			self_id,
-			base_color.get_fragment_output(self.desc.base_color.id),
-			metallic.get_fragment_output(self.desc.metallic.id),
-			roughness.get_fragment_output(self.desc.roughness.id)
+			get_converted_fragment_output(pool, &self.desc.base_color, &ValueType::Vector3),
+			get_converted_fragment_output(pool, &self.desc.metallic, &ValueType::Float),
+			get_converted_fragment_output(pool, &self.desc.roughness, &ValueType::Float),
 		) +
 		// @TODO: Fix me
 		&format!("use_directional_light = false;\n")
@@ -158,4 +167,32 @@ impl MaterialNode for BRDFNode {
 	fn get_fragment_output(&self, self_id: usize) -> String {
 		format!("brdf_output_{}", self_id)
 	}
+
+	fn get_input_types(
+		&self,
+		_pool: &ResourcePool<Box<dyn MaterialNode>>,
+	) -> Vec<(ResourceId<Box<dyn MaterialNode>>, ValueType)> {
+		vec![
+			(self.desc.base_color, ValueType::Vector3),
+			(self.desc.metallic, ValueType::Float),
+			(self.desc.normal, ValueType::Vector3),
+			(self.desc.roughness, ValueType::Float),
+		]
+	}
+
+	fn get_output_type(
+		&self,
+		_pool: &ResourcePool<Box<dyn MaterialNode>>,
+	) -> ValueType {
+		ValueType::Vector3
+	}
+
+	fn describe(&self) -> Option<ResourceNodeDescription> {
+		Some(NodeDescription::BRDF {
+			base_color: self.desc.base_color,
+			metallic: self.desc.metallic,
+			normal: self.desc.normal,
+			roughness: self.desc.roughness,
+		})
+	}
 }
@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+use crate::{
+	material::{
+		description::{
+			NodeDescription,
+			ResourceNodeDescription,
+		},
+		function_registry::FunctionRegistry,
+		node::node::{
+			MaterialNode,
+			UniformContents,
+			ValueType,
+		},
+	},
+	resource::resource::{
+		ResourceId,
+		ResourcePool,
+	},
+};
+
+// Camera position in world space
+pub struct CameraPositionNode {
+}
+
+impl Default for CameraPositionNode {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl CameraPositionNode {
+	pub fn new() -> Self {
+		CameraPositionNode {
+		}
+	}
+}
+
+impl MaterialNode for CameraPositionNode {
+	fn collect_nodes (
+		&self,
+		_pool: &ResourcePool<Box<dyn MaterialNode>>,
+		nodes: &mut Vec<ResourceId<Box<dyn MaterialNode>>>,
+		visited: &mut HashMap<ResourceId<Box<dyn MaterialNode>>, bool>,
+		self_rid: ResourceId<Box<dyn MaterialNode>>,
+	) {
+		if visited.insert(self_rid, true).is_none() {
+			nodes.push(self_rid);
+		}
+	}
+
+	fn borrow_contents(&self) -> Option<&UniformContents> {
+		None
+	}
+
+	fn build_declaration(&self, _self_id: usize) -> String {
+		String::new()
+	}
+
+	fn register_functions(&self, _registry: &mut FunctionRegistry, _self_id: usize) {
+	}
+
+	fn build_fragment_shader(
+		&self,
+		_pool: &ResourcePool<Box<dyn MaterialNode>>,
+		_visited: &mut HashMap<usize, bool>,
+		_self_id: usize,
+	) -> String {
+		String::new()
+	}
+
+	fn get_fragment_output(&self, _self_id: usize) -> String {
+		"globals.camera_position".to_string()
+	}
+
+	fn get_input_types(
+		&self,
+		_pool: &ResourcePool<Box<dyn MaterialNode>>,
+	) -> Vec<(ResourceId<Box<dyn MaterialNode>>, ValueType)> {
+		Vec::new()
+	}
+
+	fn get_output_type(
+		&self,
+		_pool: &ResourcePool<Box<dyn MaterialNode>>,
+	) -> ValueType {
+		ValueType::Vector3
+	}
+
+	fn describe(&self) -> Option<ResourceNodeDescription> {
+		Some(NodeDescription::CameraPosition)
+	}
+}
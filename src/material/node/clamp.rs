@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+use crate::{
+	material::{
+		description::{
+			NodeDescription,
+			ResourceNodeDescription,
+		},
+		function_registry::FunctionRegistry,
+		node::node::{
+			MaterialNode,
+			UniformContents,
+			ValueType,
+			get_converted_fragment_output,
+		},
+	},
+	resource::resource::{
+		ResourceId,
+		ResourcePool,
+	},
+};
+
+pub struct ClampNode {
+	value: ResourceId<Box<dyn MaterialNode>>,
+	min: ResourceId<Box<dyn MaterialNode>>,
+	max: ResourceId<Box<dyn MaterialNode>>,
+}
+
+impl ClampNode {
+	pub fn new(
+		value: ResourceId<Box<dyn MaterialNode>>,
+		min: ResourceId<Box<dyn MaterialNode>>,
+		max: ResourceId<Box<dyn MaterialNode>>,
+	) -> Self {
+		ClampNode {
+			value,
+			min,
+			max,
+		}
+	}
+}
+
+impl MaterialNode for ClampNode {
+	fn collect_nodes (
+		&self,
+		pool: &ResourcePool<Box<dyn MaterialNode>>,
+		nodes: &mut Vec<ResourceId<Box<dyn MaterialNode>>>,
+		visited: &mut HashMap<ResourceId<Box<dyn MaterialNode>>, bool>,
+		self_rid: ResourceId<Box<dyn MaterialNode>>,
+	) {
+		pool.borrow(&self.value).unwrap().collect_nodes(
+			pool, nodes, visited, self.value,
+		);
+		pool.borrow(&self.min).unwrap().collect_nodes(
+			pool, nodes, visited, self.min,
+		);
+		pool.borrow(&self.max).unwrap().collect_nodes(
+			pool, nodes, visited, self.max,
+		);
+		if visited.insert(self_rid, true).is_none() {
+			nodes.push(self_rid);
+		}
+	}
+
+	fn borrow_contents(&self) -> Option<&UniformContents> {
+		None
+	}
+
+	fn build_declaration(&self, _self_id: usize) -> String {
+		String::new()
+	}
+
+	fn register_functions(&self, _registry: &mut FunctionRegistry, _self_id: usize) {
+	}
+
+	fn build_fragment_shader(
+		&self,
+		pool: &ResourcePool<Box<dyn MaterialNode>>,
+		visited: &mut HashMap<usize, bool>,
+		self_id: usize,
+	) -> String {
+		if visited.contains_key(&self_id) {
+			return "".to_string();
+		}
+		visited.insert(self_id, true);
+
+		let value = pool.borrow(&self.value).unwrap();
+		let min = pool.borrow(&self.min).unwrap();
+		let max = pool.borrow(&self.max).unwrap();
+		let value_type = self.get_output_type(pool);
+
+		value.build_fragment_shader(pool, visited, self.value.id) +
+		&min.build_fragment_shader(pool, visited, self.min.id) +
+		&max.build_fragment_shader(pool, visited, self.max.id) +
+		&format!("let {} = clamp({}, {}, {});\n",
+			self.get_fragment_output(self_id),
+			get_converted_fragment_output(pool, &self.value, &value_type),
+			get_converted_fragment_output(pool, &self.min, &value_type),
+			get_converted_fragment_output(pool, &self.max, &value_type),
+		)
+	}
+
+	fn get_fragment_output(&self, self_id: usize) -> String {
+		format!("clamp_output_{}", self_id)
+	}
+
+	fn get_input_types(
+		&self,
+		pool: &ResourcePool<Box<dyn MaterialNode>>,
+	) -> Vec<(ResourceId<Box<dyn MaterialNode>>, ValueType)> {
+		let value_type = self.get_output_type(pool);
+		vec![
+			(self.value, value_type),
+			(self.min, value_type),
+			(self.max, value_type),
+		]
+	}
+
+	fn get_output_type(
+		&self,
+		pool: &ResourcePool<Box<dyn MaterialNode>>,
+	) -> ValueType {
+		ValueType::get_common_type(
+			&ValueType::get_common_type(
+				&pool.borrow(&self.value).unwrap().get_output_type(pool),
+				&pool.borrow(&self.min).unwrap().get_output_type(pool),
+			),
+			&pool.borrow(&self.max).unwrap().get_output_type(pool),
+		).get_numeric_type()
+	}
+
+	fn describe(&self) -> Option<ResourceNodeDescription> {
+		Some(NodeDescription::Clamp {
+			value: self.value,
+			min: self.min,
+			max: self.max,
+		})
+	}
+}
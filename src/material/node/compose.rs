@@ -0,0 +1,135 @@
+use std::collections::HashMap;
+use crate::{
+	material::{
+		description::{
+			NodeDescription,
+			ResourceNodeDescription,
+		},
+		function_registry::FunctionRegistry,
+		node::node::{
+			MaterialNode,
+			UniformContents,
+			ValueType,
+			get_converted_fragment_output,
+		},
+	},
+	resource::resource::{
+		ResourceId,
+		ResourcePool,
+	},
+};
+
+// Builds a vector from floats and vectors in order,
+// e.g. vec4 from rgb and alpha or vec2 from two floats.
+pub struct ComposeNode {
+	nodes: Vec<ResourceId<Box<dyn MaterialNode>>>,
+}
+
+impl ComposeNode {
+	pub fn new(
+		nodes: Vec<ResourceId<Box<dyn MaterialNode>>>,
+	) -> Self {
+		// @TODO: Proper error handling
+		if nodes.is_empty() {
+			panic!("ComposeNode needs at least one node");
+		}
+
+		ComposeNode {
+			nodes,
+		}
+	}
+}
+
+impl MaterialNode for ComposeNode {
+	fn collect_nodes (
+		&self,
+		pool: &ResourcePool<Box<dyn MaterialNode>>,
+		nodes: &mut Vec<ResourceId<Box<dyn MaterialNode>>>,
+		visited: &mut HashMap<ResourceId<Box<dyn MaterialNode>>, bool>,
+		self_rid: ResourceId<Box<dyn MaterialNode>>,
+	) {
+		for node in self.nodes.iter() {
+			pool.borrow(node).unwrap().collect_nodes(
+				pool, nodes, visited, *node,
+			);
+		}
+		if visited.insert(self_rid, true).is_none() {
+			nodes.push(self_rid);
+		}
+	}
+
+	fn borrow_contents(&self) -> Option<&UniformContents> {
+		None
+	}
+
+	fn build_declaration(&self, _self_id: usize) -> String {
+		String::new()
+	}
+
+	fn register_functions(&self, _registry: &mut FunctionRegistry, _self_id: usize) {
+	}
+
+	fn build_fragment_shader(
+		&self,
+		pool: &ResourcePool<Box<dyn MaterialNode>>,
+		visited: &mut HashMap<usize, bool>,
+		self_id: usize,
+	) -> String {
+		if visited.contains_key(&self_id) {
+			return "".to_string();
+		}
+		visited.insert(self_id, true);
+
+		let mut s = "".to_string();
+		let mut arguments = Vec::new();
+		for (node, value_type) in self.get_input_types(pool).iter() {
+			s += &pool.borrow(node).unwrap().build_fragment_shader(pool, visited, node.id);
+			arguments.push(get_converted_fragment_output(pool, node, value_type));
+		}
+
+		s + &format!("let {} = {}({});\n",
+			self.get_fragment_output(self_id),
+			self.get_output_type(pool).get_wgsl_type(),
+			arguments.join(", "),
+		)
+	}
+
+	fn get_fragment_output(&self, self_id: usize) -> String {
+		format!("compose_output_{}", self_id)
+	}
+
+	fn get_input_types(
+		&self,
+		pool: &ResourcePool<Box<dyn MaterialNode>>,
+	) -> Vec<(ResourceId<Box<dyn MaterialNode>>, ValueType)> {
+		let mut input_types = Vec::new();
+		for node in self.nodes.iter() {
+			input_types.push((
+				*node,
+				pool.borrow(node).unwrap().get_output_type(pool).get_numeric_type(),
+			));
+		}
+		input_types
+	}
+
+	// More than four components in total is reported by Material::validate()
+	fn get_output_type(
+		&self,
+		pool: &ResourcePool<Box<dyn MaterialNode>>,
+	) -> ValueType {
+		let mut count = 0;
+		for (_, value_type) in self.get_input_types(pool).iter() {
+			count += value_type.get_component_count().unwrap();
+		}
+		match ValueType::from_component_count(count) {
+			Some(value_type) => value_type,
+			None => ValueType::Vector4,
+		}
+	}
+
+	fn describe(&self) -> Option<ResourceNodeDescription> {
+		Some(NodeDescription::Compose {
+			nodes: self.nodes.clone(),
+		})
+	}
+}
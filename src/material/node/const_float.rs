@@ -1,8 +1,16 @@
 use std::collections::HashMap;
 use crate::{
-	material::node::node::{
-		MaterialNode,
-		UniformContents,
+	material::{
+		description::{
+			NodeDescription,
+			ResourceNodeDescription,
+		},
+		function_registry::FunctionRegistry,
+		node::node::{
+			MaterialNode,
+			UniformContents,
+			ValueType,
+		},
 	},
 	resource::resource::{
 		ResourceId,
@@ -44,8 +52,7 @@ impl MaterialNode for ConstFloatNode {
 		format!("")
 	}
 
-	fn build_functions(&self, _self_id: usize) -> String {
-		format!("")
+	fn register_functions(&self, _registry: &mut FunctionRegistry, _self_id: usize) {
 	}
 
 	fn build_fragment_shader(
@@ -60,4 +67,24 @@ impl MaterialNode for ConstFloatNode {
 	fn get_fragment_output(&self, _self_id: usize) -> String {
 		format!("{:.16}", self.value)
 	}
+
+	fn get_input_types(
+		&self,
+		_pool: &ResourcePool<Box<dyn MaterialNode>>,
+	) -> Vec<(ResourceId<Box<dyn MaterialNode>>, ValueType)> {
+		Vec::new()
+	}
+
+	fn get_output_type(
+		&self,
+		_pool: &ResourcePool<Box<dyn MaterialNode>>,
+	) -> ValueType {
+		ValueType::Float
+	}
+
+	fn describe(&self) -> Option<ResourceNodeDescription> {
+		Some(NodeDescription::ConstFloat {
+			value: self.value,
+		})
+	}
 }
@@ -1,8 +1,16 @@
 use std::collections::HashMap;
 use crate::{
-	material::node::node::{
-		MaterialNode,
-		UniformContents,
+	material::{
+		description::{
+			NodeDescription,
+			ResourceNodeDescription,
+		},
+		function_registry::FunctionRegistry,
+		node::node::{
+			MaterialNode,
+			UniformContents,
+			ValueType,
+		},
 	},
 	resource::resource::{
 		ResourceId,
@@ -44,8 +52,7 @@ impl MaterialNode for ConstVector3Node {
 		format!("")
 	}
 
-	fn build_functions(&self, _self_id: usize) -> String {
-		format!("")
+	fn register_functions(&self, _registry: &mut FunctionRegistry, _self_id: usize) {
 	}
 
 	fn build_fragment_shader(
@@ -64,4 +71,24 @@ impl MaterialNode for ConstVector3Node {
 			self.value[2],
 		)
 	}
+
+	fn get_input_types(
+		&self,
+		_pool: &ResourcePool<Box<dyn MaterialNode>>,
+	) -> Vec<(ResourceId<Box<dyn MaterialNode>>, ValueType)> {
+		Vec::new()
+	}
+
+	fn get_output_type(
+		&self,
+		_pool: &ResourcePool<Box<dyn MaterialNode>>,
+	) -> ValueType {
+		ValueType::Vector3
+	}
+
+	fn describe(&self) -> Option<ResourceNodeDescription> {
+		Some(NodeDescription::ConstVector3 {
+			value: self.value,
+		})
+	}
 }
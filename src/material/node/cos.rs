@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+use crate::{
+	material::{
+		description::{
+			NodeDescription,
+			ResourceNodeDescription,
+		},
+		function_registry::FunctionRegistry,
+		node::node::{
+			MaterialNode,
+			UniformContents,
+			ValueType,
+			get_converted_fragment_output,
+		},
+	},
+	resource::resource::{
+		ResourceId,
+		ResourcePool,
+	},
+};
+
+pub struct CosNode {
+	node: ResourceId<Box<dyn MaterialNode>>,
+}
+
+impl CosNode {
+	pub fn new(
+		node: ResourceId<Box<dyn MaterialNode>>,
+	) -> Self {
+		CosNode {
+			node,
+		}
+	}
+}
+
+impl MaterialNode for CosNode {
+	fn collect_nodes (
+		&self,
+		pool: &ResourcePool<Box<dyn MaterialNode>>,
+		nodes: &mut Vec<ResourceId<Box<dyn MaterialNode>>>,
+		visited: &mut HashMap<ResourceId<Box<dyn MaterialNode>>, bool>,
+		self_rid: ResourceId<Box<dyn MaterialNode>>,
+	) {
+		pool.borrow(&self.node).unwrap().collect_nodes(
+			pool, nodes, visited, self.node,
+		);
+		if visited.insert(self_rid, true).is_none() {
+			nodes.push(self_rid);
+		}
+	}
+
+	fn borrow_contents(&self) -> Option<&UniformContents> {
+		None
+	}
+
+	fn build_declaration(&self, _self_id: usize) -> String {
+		String::new()
+	}
+
+	fn register_functions(&self, _registry: &mut FunctionRegistry, _self_id: usize) {
+	}
+
+	fn build_fragment_shader(
+		&self,
+		pool: &ResourcePool<Box<dyn MaterialNode>>,
+		visited: &mut HashMap<usize, bool>,
+		self_id: usize,
+	) -> String {
+		if visited.contains_key(&self_id) {
+			return "".to_string();
+		}
+		visited.insert(self_id, true);
+
+		let node = pool.borrow(&self.node).unwrap();
+		let value_type = self.get_output_type(pool);
+
+		node.build_fragment_shader(pool, visited, self.node.id) +
+		&format!("let {} = cos({});\n",
+			self.get_fragment_output(self_id),
+			get_converted_fragment_output(pool, &self.node, &value_type),
+		)
+	}
+
+	fn get_fragment_output(&self, self_id: usize) -> String {
+		format!("cos_output_{}", self_id)
+	}
+
+	fn get_input_types(
+		&self,
+		pool: &ResourcePool<Box<dyn MaterialNode>>,
+	) -> Vec<(ResourceId<Box<dyn MaterialNode>>, ValueType)> {
+		vec![(self.node, self.get_output_type(pool))]
+	}
+
+	fn get_output_type(
+		&self,
+		pool: &ResourcePool<Box<dyn MaterialNode>>,
+	) -> ValueType {
+		pool.borrow(&self.node).unwrap().get_output_type(pool).get_numeric_type()
+	}
+
+	fn describe(&self) -> Option<ResourceNodeDescription> {
+		Some(NodeDescription::Cos {
+			node: self.node,
+		})
+	}
+}
@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+use crate::{
+	material::{
+		description::{
+			NodeDescription,
+			ResourceNodeDescription,
+		},
+		function_registry::FunctionRegistry,
+		node::node::{
+			MaterialNode,
+			UniformContents,
+			ValueType,
+			get_converted_fragment_output,
+		},
+	},
+	resource::resource::{
+		ResourceId,
+		ResourcePool,
+	},
+};
+
+pub struct CustomCodeInput {
+	pub name: String,
+	pub node: ResourceId<Box<dyn MaterialNode>>,
+	pub value_type: ValueType,
+}
+
+// body is a WGSL function body which reads the inputs by name and
+// returns a value of output_type, e.g. "return a * sin(b);".
+// functions are helper function chunks keyed by name, chunks with
+// the same name are emitted once even if several nodes use them.
+pub struct CustomCodeNodeDescriptor {
+	pub body: String,
+	pub functions: Vec<(String, String)>,
+	pub inputs: Vec<CustomCodeInput>,
+	pub output_type: ValueType,
+}
+
+pub struct CustomCodeNode {
+	desc: CustomCodeNodeDescriptor,
+}
+
+impl CustomCodeNode {
+	pub fn new(
+		desc: CustomCodeNodeDescriptor,
+	) -> Self {
+		CustomCodeNode {
+			desc,
+		}
+	}
+
+	// The body is wrapped in its own function so that input names
+	// and local variables can't collide with other nodes.
+	fn get_function_name(&self, self_id: usize) -> String {
+		format!("custom_code_{}", self_id)
+	}
+}
+
+impl MaterialNode for CustomCodeNode {
+	fn collect_nodes (
+		&self,
+		pool: &ResourcePool<Box<dyn MaterialNode>>,
+		nodes: &mut Vec<ResourceId<Box<dyn MaterialNode>>>,
+		visited: &mut HashMap<ResourceId<Box<dyn MaterialNode>>, bool>,
+		self_rid: ResourceId<Box<dyn MaterialNode>>,
+	) {
+		for input in self.desc.inputs.iter() {
+			pool.borrow(&input.node).unwrap().collect_nodes(
+				pool, nodes, visited, input.node,
+			);
+		}
+		if visited.insert(self_rid, true).is_none() {
+			nodes.push(self_rid);
+		}
+	}
+
+	fn borrow_contents(&self) -> Option<&UniformContents> {
+		None
+	}
+
+	fn build_declaration(&self, _self_id: usize) -> String {
+		String::new()
+	}
+
+	fn register_functions(&self, registry: &mut FunctionRegistry, self_id: usize) {
+		for (name, chunk) in self.desc.functions.iter() {
+			registry.register(name, chunk);
+		}
+
+		let params = self.desc.inputs.iter().map(|input| {
+			format!("{}: {}", input.name, input.value_type.get_wgsl_type())
+		}).collect::<Vec<String>>().join(", ");
+
+		registry.register(
+			&format!("{}::{}", module_path!(), self_id),
+			&format!("\nfn {}({}) -> {} {{\n{}\n}}\n",
+				self.get_function_name(self_id),
+				params,
+				self.desc.output_type.get_wgsl_type(),
+				self.desc.body,
+			),
+		);
+	}
+
+	fn build_fragment_shader(
+		&self,
+		pool: &ResourcePool<Box<dyn MaterialNode>>,
+		visited: &mut HashMap<usize, bool>,
+		self_id: usize,
+	) -> String {
+		if visited.contains_key(&self_id) {
+			return "".to_string();
+		}
+		visited.insert(self_id, true);
+
+		let mut s = "".to_string();
+		for input in self.desc.inputs.iter() {
+			s += &pool.borrow(&input.node).unwrap().build_fragment_shader(pool, visited, input.node.id);
+		}
+
+		let args = self.desc.inputs.iter().map(|input| {
+			get_converted_fragment_output(pool, &input.node, &input.value_type)
+		}).collect::<Vec<String>>().join(", ");
+
+		s + &format!("let {} = {}({});\n",
+			self.get_fragment_output(self_id),
+			self.get_function_name(self_id),
+			args,
+		)
+	}
+
+	fn get_fragment_output(&self, self_id: usize) -> String {
+		format!("custom_code_output_{}", self_id)
+	}
+
+	fn get_input_types(
+		&self,
+		_pool: &ResourcePool<Box<dyn MaterialNode>>,
+	) -> Vec<(ResourceId<Box<dyn MaterialNode>>, ValueType)> {
+		self.desc.inputs.iter().map(|input| (input.node, input.value_type)).collect()
+	}
+
+	fn get_output_type(
+		&self,
+		_pool: &ResourcePool<Box<dyn MaterialNode>>,
+	) -> ValueType {
+		self.desc.output_type
+	}
+
+	fn describe(&self) -> Option<ResourceNodeDescription> {
+		Some(NodeDescription::CustomCode {
+			body: self.desc.body.clone(),
+			functions: self.desc.functions.clone(),
+			inputs: self.desc.inputs.iter().map(|input| {
+				(input.name.clone(), input.node, input.value_type)
+			}).collect(),
+			output_type: self.desc.output_type,
+		})
+	}
+}
@@ -1,8 +1,16 @@
 use std::collections::HashMap;
 use crate::{
-	material::node::node::{
-		MaterialNode,
-		UniformContents,
+	material::{
+		description::{
+			NodeDescription,
+			ResourceNodeDescription,
+		},
+		function_registry::FunctionRegistry,
+		node::node::{
+			MaterialNode,
+			UniformContents,
+			ValueType,
+		},
 	},
 	resource::resource::{
 		ResourceId,
@@ -46,8 +54,7 @@ impl MaterialNode for FloatNode {
 		format!("f32_{}: f32;\n", self_id)
 	}
 
-	fn build_functions(&self, _self_id: usize) -> String {
-		format!("")
+	fn register_functions(&self, _registry: &mut FunctionRegistry, _self_id: usize) {
 	}
 
 	fn build_fragment_shader(
@@ -62,4 +69,27 @@ impl MaterialNode for FloatNode {
 	fn get_fragment_output(&self, self_id: usize) -> String {
 		format!("unif.f32_{}", self_id)
 	}
+
+	fn get_input_types(
+		&self,
+		_pool: &ResourcePool<Box<dyn MaterialNode>>,
+	) -> Vec<(ResourceId<Box<dyn MaterialNode>>, ValueType)> {
+		Vec::new()
+	}
+
+	fn get_output_type(
+		&self,
+		_pool: &ResourcePool<Box<dyn MaterialNode>>,
+	) -> ValueType {
+		ValueType::Float
+	}
+
+	fn describe(&self) -> Option<ResourceNodeDescription> {
+		Some(match self.contents {
+			UniformContents::Float {value} => NodeDescription::Float {
+				value: value[0],
+			},
+			_ => panic!(),
+		})
+	}
 }
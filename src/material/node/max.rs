@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+use crate::{
+	material::{
+		description::{
+			NodeDescription,
+			ResourceNodeDescription,
+		},
+		function_registry::FunctionRegistry,
+		node::node::{
+			MaterialNode,
+			UniformContents,
+			ValueType,
+			get_converted_fragment_output,
+		},
+	},
+	resource::resource::{
+		ResourceId,
+		ResourcePool,
+	},
+};
+
+pub struct MaxNode {
+	value1: ResourceId<Box<dyn MaterialNode>>,
+	value2: ResourceId<Box<dyn MaterialNode>>,
+}
+
+impl MaxNode {
+	pub fn new(
+		value1: ResourceId<Box<dyn MaterialNode>>,
+		value2: ResourceId<Box<dyn MaterialNode>>,
+	) -> Self {
+		MaxNode {
+			value1,
+			value2,
+		}
+	}
+}
+
+impl MaterialNode for MaxNode {
+	fn collect_nodes (
+		&self,
+		pool: &ResourcePool<Box<dyn MaterialNode>>,
+		nodes: &mut Vec<ResourceId<Box<dyn MaterialNode>>>,
+		visited: &mut HashMap<ResourceId<Box<dyn MaterialNode>>, bool>,
+		self_rid: ResourceId<Box<dyn MaterialNode>>,
+	) {
+		pool.borrow(&self.value1).unwrap().collect_nodes(
+			pool, nodes, visited, self.value1,
+		);
+		pool.borrow(&self.value2).unwrap().collect_nodes(
+			pool, nodes, visited, self.value2,
+		);
+		if visited.insert(self_rid, true).is_none() {
+			nodes.push(self_rid);
+		}
+	}
+
+	fn borrow_contents(&self) -> Option<&UniformContents> {
+		None
+	}
+
+	fn build_declaration(&self, _self_id: usize) -> String {
+		String::new()
+	}
+
+	fn register_functions(&self, _registry: &mut FunctionRegistry, _self_id: usize) {
+	}
+
+	fn build_fragment_shader(
+		&self,
+		pool: &ResourcePool<Box<dyn MaterialNode>>,
+		visited: &mut HashMap<usize, bool>,
+		self_id: usize,
+	) -> String {
+		if visited.contains_key(&self_id) {
+			return "".to_string();
+		}
+		visited.insert(self_id, true);
+
+		let value1 = pool.borrow(&self.value1).unwrap();
+		let value2 = pool.borrow(&self.value2).unwrap();
+		let value_type = self.get_output_type(pool);
+
+		value1.build_fragment_shader(pool, visited, self.value1.id) +
+		&value2.build_fragment_shader(pool, visited, self.value2.id) +
+		&format!("let {} = max({}, {});\n",
+			self.get_fragment_output(self_id),
+			get_converted_fragment_output(pool, &self.value1, &value_type),
+			get_converted_fragment_output(pool, &self.value2, &value_type),
+		)
+	}
+
+	fn get_fragment_output(&self, self_id: usize) -> String {
+		format!("max_output_{}", self_id)
+	}
+
+	fn get_input_types(
+		&self,
+		pool: &ResourcePool<Box<dyn MaterialNode>>,
+	) -> Vec<(ResourceId<Box<dyn MaterialNode>>, ValueType)> {
+		let value_type = self.get_output_type(pool);
+		vec![
+			(self.value1, value_type),
+			(self.value2, value_type),
+		]
+	}
+
+	fn get_output_type(
+		&self,
+		pool: &ResourcePool<Box<dyn MaterialNode>>,
+	) -> ValueType {
+		ValueType::get_common_type(
+			&pool.borrow(&self.value1).unwrap().get_output_type(pool),
+			&pool.borrow(&self.value2).unwrap().get_output_type(pool),
+		).get_numeric_type()
+	}
+
+	fn describe(&self) -> Option<ResourceNodeDescription> {
+		Some(NodeDescription::Max {
+			value1: self.value1,
+			value2: self.value2,
+		})
+	}
+}
@@ -1,18 +1,48 @@
+pub mod abs;
 pub mod add;
 pub mod brdf;
+pub mod camera_position;
+pub mod clamp;
+pub mod compose;
 pub mod const_float;
 pub mod const_vector3;
+pub mod cos;
+pub mod cross;
+pub mod custom_code;
+pub mod delta_time;
+pub mod dot;
 pub mod float;
+pub mod fract;
+pub mod length;
 pub mod linear_to_srgb;
+pub mod max;
+pub mod min;
+pub mod mix;
 pub mod multiply;
 pub mod node;
 pub mod normal;
 pub mod normal_matrix;
+pub mod normalize;
+pub mod one_minus;
+pub mod position;
+pub mod pow;
+pub mod resolution;
+pub mod saturate;
+pub mod screen_position;
+pub mod sin;
+pub mod smoothstep;
 pub mod srgb_to_linear;
+pub mod step;
 pub mod sub;
+pub mod swizzle;
 pub mod tangent_to_object_normal;
 pub mod texture;
+pub mod time;
+pub mod varying;
 pub mod vector3;
+pub mod vertex_color;
+pub mod view_direction;
+pub mod world_position;
 pub mod xyz;
 pub mod x;
 pub mod y;
@@ -1,5 +1,13 @@
 use std::collections::HashMap;
+use serde::{
+	Deserialize,
+	Serialize,
+};
 use crate::{
+	material::{
+		description::ResourceNodeDescription,
+		function_registry::FunctionRegistry,
+	},
 	resource::resource::{
 		ResourceId,
 		ResourcePool,
@@ -20,6 +28,96 @@ pub enum UniformContents {
 	},
 }
 
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub enum ValueType {
+	Float,
+	Matrix3,
+	Vector2,
+	Vector3,
+	Vector4,
+}
+
+impl ValueType {
+	pub fn get_wgsl_type(&self) -> &'static str {
+		match self {
+			ValueType::Float => "f32",
+			ValueType::Matrix3 => "mat3x3<f32>",
+			ValueType::Vector2 => "vec2<f32>",
+			ValueType::Vector3 => "vec3<f32>",
+			ValueType::Vector4 => "vec4<f32>",
+		}
+	}
+
+	pub fn get_component_count(&self) -> Option<u32> {
+		match self {
+			ValueType::Float => Some(1),
+			ValueType::Vector2 => Some(2),
+			ValueType::Vector3 => Some(3),
+			ValueType::Vector4 => Some(4),
+			ValueType::Matrix3 => None,
+		}
+	}
+
+	// Non numeric types are treated as float where a number is needed
+	// so that they fail the type check.
+	pub fn get_numeric_type(&self) -> ValueType {
+		match self.get_component_count() {
+			Some(_) => *self,
+			None => ValueType::Float,
+		}
+	}
+
+	pub fn from_component_count(count: u32) -> Option<ValueType> {
+		match count {
+			1 => Some(ValueType::Float),
+			2 => Some(ValueType::Vector2),
+			3 => Some(ValueType::Vector3),
+			4 => Some(ValueType::Vector4),
+			_ => None,
+		}
+	}
+
+	// The type both values are converted to in component-wise operations.
+	// The wider one is chosen so that floats are splatted to vectors.
+	pub fn get_common_type(type1: &ValueType, type2: &ValueType) -> ValueType {
+		match (type1.get_component_count(), type2.get_component_count()) {
+			(Some(count1), Some(count2)) if count2 > count1 => *type2,
+			_ => *type1,
+		}
+	}
+
+	// Automatic conversions are splatting a float to a vector,
+	// dropping trailing vector components, and appending 1.0 as alpha
+	// to a vec3.
+	pub fn can_convert(&self, to: &ValueType) -> bool {
+		match (self, to) {
+			(from, to) if from == to => true,
+			(ValueType::Float, ValueType::Vector2) |
+			(ValueType::Float, ValueType::Vector3) |
+			(ValueType::Float, ValueType::Vector4) |
+			(ValueType::Vector3, ValueType::Vector2) |
+			(ValueType::Vector4, ValueType::Vector2) |
+			(ValueType::Vector4, ValueType::Vector3) |
+			(ValueType::Vector3, ValueType::Vector4) => true,
+			_ => false,
+		}
+	}
+
+	pub fn convert(&self, to: &ValueType, expression: &str) -> Option<String> {
+		if !self.can_convert(to) {
+			return None;
+		}
+
+		Some(match (self, to) {
+			(from, to) if from == to => expression.to_string(),
+			(ValueType::Float, _) => format!("{}({})", to.get_wgsl_type(), expression),
+			(_, ValueType::Vector2) => format!("{}.xy", expression),
+			(_, ValueType::Vector3) => format!("{}.xyz", expression),
+			_ => format!("vec4<f32>({}, 1.0)", expression),
+		})
+	}
+}
+
 pub trait MaterialNode {
 	fn collect_nodes (
 		&self,
@@ -30,14 +128,61 @@ pub trait MaterialNode {
 	);
 	fn borrow_contents(&self) -> Option<&UniformContents>;
 	fn build_declaration(&self, self_id: usize) -> String;
-	fn build_functions(&self, self_id: usize) -> String;
+	fn register_functions(&self, registry: &mut FunctionRegistry, self_id: usize);
 	fn build_fragment_shader(
 		&self,
 		pool: &ResourcePool<Box<dyn MaterialNode>>,
 		visited: &mut HashMap<usize, bool>,
 		self_id: usize,
 	) -> String;
+	// A variable the node declares is named with self_id, e.g.
+	// add_output_{self_id}, so that it is unique in the shader.
+	// Material::check_variable_names() reports duplicated names.
 	fn get_fragment_output(&self, self_id: usize) -> String;
+	// Input nodes and the types this node expects from them
+	fn get_input_types(
+		&self,
+		pool: &ResourcePool<Box<dyn MaterialNode>>,
+	) -> Vec<(ResourceId<Box<dyn MaterialNode>>, ValueType)>;
+	fn get_output_type(
+		&self,
+		pool: &ResourcePool<Box<dyn MaterialNode>>,
+	) -> ValueType;
+	// Node type and parameters for serialization. Nodes defined outside
+	// of this crate can't be serialized and return None by default.
+	fn describe(&self) -> Option<ResourceNodeDescription> {
+		None
+	}
+	// The vertex stage provides the same inputs as `in` so most nodes
+	// emit the same code as in the fragment stage. Nodes whose code is
+	// fragment stage only, e.g. implicit derivatives, override this.
+	fn build_vertex_shader(
+		&self,
+		pool: &ResourcePool<Box<dyn MaterialNode>>,
+		visited: &mut HashMap<usize, bool>,
+		self_id: usize,
+	) -> String {
+		self.build_fragment_shader(pool, visited, self_id)
+	}
+	// Whether the inputs are evaluated in the vertex stage and passed
+	// to the fragment stage through VertexOutput. See VaryingNode.
+	fn is_varying(&self) -> bool {
+		false
+	}
 }
 
-// @TODO: Ensure unique variable names
+// Returns the fragment output of the node converted to the type.
+// Falls back to the output as is if it can not be converted,
+// Material::check_types() reports such graphs.
+pub fn get_converted_fragment_output(
+	pool: &ResourcePool<Box<dyn MaterialNode>>,
+	node_rid: &ResourceId<Box<dyn MaterialNode>>,
+	value_type: &ValueType,
+) -> String {
+	let node = pool.borrow(node_rid).unwrap();
+	let output = node.get_fragment_output(node_rid.id);
+	match node.get_output_type(pool).convert(value_type, &output) {
+		Some(converted) => converted,
+		None => output,
+	}
+}
@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+use crate::{
+	material::{
+		description::{
+			NodeDescription,
+			ResourceNodeDescription,
+		},
+		function_registry::FunctionRegistry,
+		node::node::{
+			MaterialNode,
+			UniformContents,
+			ValueType,
+			get_converted_fragment_output,
+		},
+	},
+	resource::resource::{
+		ResourceId,
+		ResourcePool,
+	},
+};
+
+pub struct PowNode {
+	base: ResourceId<Box<dyn MaterialNode>>,
+	exponent: ResourceId<Box<dyn MaterialNode>>,
+}
+
+impl PowNode {
+	pub fn new(
+		base: ResourceId<Box<dyn MaterialNode>>,
+		exponent: ResourceId<Box<dyn MaterialNode>>,
+	) -> Self {
+		PowNode {
+			base,
+			exponent,
+		}
+	}
+}
+
+impl MaterialNode for PowNode {
+	fn collect_nodes (
+		&self,
+		pool: &ResourcePool<Box<dyn MaterialNode>>,
+		nodes: &mut Vec<ResourceId<Box<dyn MaterialNode>>>,
+		visited: &mut HashMap<ResourceId<Box<dyn MaterialNode>>, bool>,
+		self_rid: ResourceId<Box<dyn MaterialNode>>,
+	) {
+		pool.borrow(&self.base).unwrap().collect_nodes(
+			pool, nodes, visited, self.base,
+		);
+		pool.borrow(&self.exponent).unwrap().collect_nodes(
+			pool, nodes, visited, self.exponent,
+		);
+		if visited.insert(self_rid, true).is_none() {
+			nodes.push(self_rid);
+		}
+	}
+
+	fn borrow_contents(&self) -> Option<&UniformContents> {
+		None
+	}
+
+	fn build_declaration(&self, _self_id: usize) -> String {
+		String::new()
+	}
+
+	fn register_functions(&self, _registry: &mut FunctionRegistry, _self_id: usize) {
+	}
+
+	fn build_fragment_shader(
+		&self,
+		pool: &ResourcePool<Box<dyn MaterialNode>>,
+		visited: &mut HashMap<usize, bool>,
+		self_id: usize,
+	) -> String {
+		if visited.contains_key(&self_id) {
+			return "".to_string();
+		}
+		visited.insert(self_id, true);
+
+		let base = pool.borrow(&self.base).unwrap();
+		let exponent = pool.borrow(&self.exponent).unwrap();
+		let value_type = self.get_output_type(pool);
+
+		base.build_fragment_shader(pool, visited, self.base.id) +
+		&exponent.build_fragment_shader(pool, visited, self.exponent.id) +
+		&format!("let {} = pow({}, {});\n",
+			self.get_fragment_output(self_id),
+			get_converted_fragment_output(pool, &self.base, &value_type),
+			get_converted_fragment_output(pool, &self.exponent, &value_type),
+		)
+	}
+
+	fn get_fragment_output(&self, self_id: usize) -> String {
+		format!("pow_output_{}", self_id)
+	}
+
+	fn get_input_types(
+		&self,
+		pool: &ResourcePool<Box<dyn MaterialNode>>,
+	) -> Vec<(ResourceId<Box<dyn MaterialNode>>, ValueType)> {
+		let value_type = self.get_output_type(pool);
+		vec![
+			(self.base, value_type),
+			(self.exponent, value_type),
+		]
+	}
+
+	fn get_output_type(
+		&self,
+		pool: &ResourcePool<Box<dyn MaterialNode>>,
+	) -> ValueType {
+		ValueType::get_common_type(
+			&pool.borrow(&self.base).unwrap().get_output_type(pool),
+			&pool.borrow(&self.exponent).unwrap().get_output_type(pool),
+		).get_numeric_type()
+	}
+
+	fn describe(&self) -> Option<ResourceNodeDescription> {
+		Some(NodeDescription::Pow {
+			base: self.base,
+			exponent: self.exponent,
+		})
+	}
+}
@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+use crate::{
+	material::{
+		description::{
+			NodeDescription,
+			ResourceNodeDescription,
+		},
+		function_registry::FunctionRegistry,
+		node::node::{
+			MaterialNode,
+			UniformContents,
+			ValueType,
+		},
+	},
+	resource::resource::{
+		ResourceId,
+		ResourcePool,
+	},
+};
+
+// Fragment position on the screen, (0.0, 0.0) at top-left and (1.0, 1.0) at bottom-right
+pub struct ScreenPositionNode {
+}
+
+impl Default for ScreenPositionNode {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl ScreenPositionNode {
+	pub fn new() -> Self {
+		ScreenPositionNode {
+		}
+	}
+}
+
+impl MaterialNode for ScreenPositionNode {
+	fn collect_nodes (
+		&self,
+		_pool: &ResourcePool<Box<dyn MaterialNode>>,
+		nodes: &mut Vec<ResourceId<Box<dyn MaterialNode>>>,
+		visited: &mut HashMap<ResourceId<Box<dyn MaterialNode>>, bool>,
+		self_rid: ResourceId<Box<dyn MaterialNode>>,
+	) {
+		if visited.insert(self_rid, true).is_none() {
+			nodes.push(self_rid);
+		}
+	}
+
+	fn borrow_contents(&self) -> Option<&UniformContents> {
+		None
+	}
+
+	fn build_declaration(&self, _self_id: usize) -> String {
+		String::new()
+	}
+
+	fn register_functions(&self, _registry: &mut FunctionRegistry, _self_id: usize) {
+	}
+
+	fn build_fragment_shader(
+		&self,
+		_pool: &ResourcePool<Box<dyn MaterialNode>>,
+		visited: &mut HashMap<usize, bool>,
+		self_id: usize,
+	) -> String {
+		if visited.insert(self_id, true).is_some() {
+			return String::new();
+		}
+
+		format!("let {} = in.position.xy / globals.resolution;\n",
+			self.get_fragment_output(self_id),
+		)
+	}
+
+	fn get_fragment_output(&self, self_id: usize) -> String {
+		format!("screen_position_output_{}", self_id)
+	}
+
+	// in.position is not available yet in the vertex stage so the same
+	// value is computed from the clip space position of the vertex
+	fn build_vertex_shader(
+		&self,
+		_pool: &ResourcePool<Box<dyn MaterialNode>>,
+		visited: &mut HashMap<usize, bool>,
+		self_id: usize,
+	) -> String {
+		if visited.insert(self_id, true).is_some() {
+			return String::new();
+		}
+
+		format!("let screen_position_clip_{} = camera.projection_matrix * model_view_matrix * vec4<f32>(position, 1.0);\n", self_id) +
+		&format!("let {} = screen_position_clip_{}.xy / screen_position_clip_{}.w * vec2<f32>(0.5, -0.5) + 0.5;\n",
+			self.get_fragment_output(self_id),
+			self_id,
+			self_id,
+		)
+	}
+
+	fn get_input_types(
+		&self,
+		_pool: &ResourcePool<Box<dyn MaterialNode>>,
+	) -> Vec<(ResourceId<Box<dyn MaterialNode>>, ValueType)> {
+		Vec::new()
+	}
+
+	fn get_output_type(
+		&self,
+		_pool: &ResourcePool<Box<dyn MaterialNode>>,
+	) -> ValueType {
+		ValueType::Vector2
+	}
+
+	fn describe(&self) -> Option<ResourceNodeDescription> {
+		Some(NodeDescription::ScreenPosition)
+	}
+}
@@ -1,8 +1,17 @@
 use std::collections::HashMap;
 use crate::{
-	material::node::node::{
-		MaterialNode,
-		UniformContents,
+	material::{
+		description::{
+			NodeDescription,
+			ResourceNodeDescription,
+		},
+		function_registry::FunctionRegistry,
+		node::node::{
+			MaterialNode,
+			UniformContents,
+			ValueType,
+			get_converted_fragment_output,
+		},
 	},
 	resource::resource::{
 		ResourceId,
@@ -49,8 +58,7 @@ impl MaterialNode for SRGBToLinearNode {
 		format!("")
 	}
 
-	fn build_functions(&self, _self_id: usize) -> String {
-		format!("")
+	fn register_functions(&self, _registry: &mut FunctionRegistry, _self_id: usize) {
 	}
 
 	fn build_fragment_shader(
@@ -69,11 +77,31 @@ impl MaterialNode for SRGBToLinearNode {
 		node.build_fragment_shader(pool, visited, self.node.id) +
 		&format!("let {} = srgb_to_linear({});\n",
 			self.get_fragment_output(self_id),
-			node.get_fragment_output(self.node.id),
+			get_converted_fragment_output(pool, &self.node, &ValueType::Vector4),
 		)
 	}
 
 	fn get_fragment_output(&self, self_id: usize) -> String {
 		format!("srgb_to_linear_output_{}", self_id)
 	}
+
+	fn get_input_types(
+		&self,
+		_pool: &ResourcePool<Box<dyn MaterialNode>>,
+	) -> Vec<(ResourceId<Box<dyn MaterialNode>>, ValueType)> {
+		vec![(self.node, ValueType::Vector4)]
+	}
+
+	fn get_output_type(
+		&self,
+		_pool: &ResourcePool<Box<dyn MaterialNode>>,
+	) -> ValueType {
+		ValueType::Vector4
+	}
+
+	fn describe(&self) -> Option<ResourceNodeDescription> {
+		Some(NodeDescription::SRGBToLinear {
+			node: self.node,
+		})
+	}
 }
@@ -0,0 +1,125 @@
+use std::collections::HashMap;
+use crate::{
+	material::{
+		description::{
+			NodeDescription,
+			ResourceNodeDescription,
+		},
+		function_registry::FunctionRegistry,
+		node::node::{
+			MaterialNode,
+			UniformContents,
+			ValueType,
+			get_converted_fragment_output,
+		},
+	},
+	resource::resource::{
+		ResourceId,
+		ResourcePool,
+	},
+};
+
+// 0.0 if value is less than edge, otherwise 1.0
+pub struct StepNode {
+	edge: ResourceId<Box<dyn MaterialNode>>,
+	value: ResourceId<Box<dyn MaterialNode>>,
+}
+
+impl StepNode {
+	pub fn new(
+		edge: ResourceId<Box<dyn MaterialNode>>,
+		value: ResourceId<Box<dyn MaterialNode>>,
+	) -> Self {
+		StepNode {
+			edge,
+			value,
+		}
+	}
+}
+
+impl MaterialNode for StepNode {
+	fn collect_nodes (
+		&self,
+		pool: &ResourcePool<Box<dyn MaterialNode>>,
+		nodes: &mut Vec<ResourceId<Box<dyn MaterialNode>>>,
+		visited: &mut HashMap<ResourceId<Box<dyn MaterialNode>>, bool>,
+		self_rid: ResourceId<Box<dyn MaterialNode>>,
+	) {
+		pool.borrow(&self.edge).unwrap().collect_nodes(
+			pool, nodes, visited, self.edge,
+		);
+		pool.borrow(&self.value).unwrap().collect_nodes(
+			pool, nodes, visited, self.value,
+		);
+		if visited.insert(self_rid, true).is_none() {
+			nodes.push(self_rid);
+		}
+	}
+
+	fn borrow_contents(&self) -> Option<&UniformContents> {
+		None
+	}
+
+	fn build_declaration(&self, _self_id: usize) -> String {
+		String::new()
+	}
+
+	fn register_functions(&self, _registry: &mut FunctionRegistry, _self_id: usize) {
+	}
+
+	fn build_fragment_shader(
+		&self,
+		pool: &ResourcePool<Box<dyn MaterialNode>>,
+		visited: &mut HashMap<usize, bool>,
+		self_id: usize,
+	) -> String {
+		if visited.contains_key(&self_id) {
+			return "".to_string();
+		}
+		visited.insert(self_id, true);
+
+		let edge = pool.borrow(&self.edge).unwrap();
+		let value = pool.borrow(&self.value).unwrap();
+		let value_type = self.get_output_type(pool);
+
+		edge.build_fragment_shader(pool, visited, self.edge.id) +
+		&value.build_fragment_shader(pool, visited, self.value.id) +
+		&format!("let {} = step({}, {});\n",
+			self.get_fragment_output(self_id),
+			get_converted_fragment_output(pool, &self.edge, &value_type),
+			get_converted_fragment_output(pool, &self.value, &value_type),
+		)
+	}
+
+	fn get_fragment_output(&self, self_id: usize) -> String {
+		format!("step_output_{}", self_id)
+	}
+
+	fn get_input_types(
+		&self,
+		pool: &ResourcePool<Box<dyn MaterialNode>>,
+	) -> Vec<(ResourceId<Box<dyn MaterialNode>>, ValueType)> {
+		let value_type = self.get_output_type(pool);
+		vec![
+			(self.edge, value_type),
+			(self.value, value_type),
+		]
+	}
+
+	fn get_output_type(
+		&self,
+		pool: &ResourcePool<Box<dyn MaterialNode>>,
+	) -> ValueType {
+		ValueType::get_common_type(
+			&pool.borrow(&self.edge).unwrap().get_output_type(pool),
+			&pool.borrow(&self.value).unwrap().get_output_type(pool),
+		).get_numeric_type()
+	}
+
+	fn describe(&self) -> Option<ResourceNodeDescription> {
+		Some(NodeDescription::Step {
+			edge: self.edge,
+			value: self.value,
+		})
+	}
+}
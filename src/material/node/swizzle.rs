@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+use crate::{
+	material::{
+		description::{
+			NodeDescription,
+			ResourceNodeDescription,
+		},
+		function_registry::FunctionRegistry,
+		node::node::{
+			MaterialNode,
+			UniformContents,
+			ValueType,
+			get_converted_fragment_output,
+		},
+	},
+	resource::resource::{
+		ResourceId,
+		ResourcePool,
+	},
+};
+
+// Picks components in any order, e.g. "zyx", "xy" or "rgba".
+// Floats are splatted.
+pub struct SwizzleNode {
+	components: String,
+	node: ResourceId<Box<dyn MaterialNode>>,
+}
+
+impl SwizzleNode {
+	pub fn new(
+		node: ResourceId<Box<dyn MaterialNode>>,
+		components: &str,
+	) -> Self {
+		// @TODO: Proper error handling
+		if components.is_empty() || components.len() > 4 {
+			panic!("Invalid swizzle components, {}", components);
+		}
+
+		// WGSL doesn't allow mixing xyzw and rgba so unify to xyzw
+		let mut xyzw = "".to_string();
+		for c in components.chars() {
+			xyzw.push(match c {
+				'x' | 'r' => 'x',
+				'y' | 'g' => 'y',
+				'z' | 'b' => 'z',
+				'w' | 'a' => 'w',
+				_ => panic!("Invalid swizzle components, {}", components),
+			});
+		}
+
+		SwizzleNode {
+			components: xyzw,
+			node,
+		}
+	}
+
+	fn get_required_component_count(&self) -> u32 {
+		let mut count = 2;
+		for c in self.components.chars() {
+			count = match c {
+				'z' if count < 3 => 3,
+				'w' => 4,
+				_ => count,
+			};
+		}
+		count
+	}
+}
+
+impl MaterialNode for SwizzleNode {
+	fn collect_nodes (
+		&self,
+		pool: &ResourcePool<Box<dyn MaterialNode>>,
+		nodes: &mut Vec<ResourceId<Box<dyn MaterialNode>>>,
+		visited: &mut HashMap<ResourceId<Box<dyn MaterialNode>>, bool>,
+		self_rid: ResourceId<Box<dyn MaterialNode>>,
+	) {
+		pool.borrow(&self.node).unwrap().collect_nodes(
+			pool, nodes, visited, self.node,
+		);
+		if visited.insert(self_rid, true).is_none() {
+			nodes.push(self_rid);
+		}
+	}
+
+	fn borrow_contents(&self) -> Option<&UniformContents> {
+		None
+	}
+
+	fn build_declaration(&self, _self_id: usize) -> String {
+		String::new()
+	}
+
+	fn register_functions(&self, _registry: &mut FunctionRegistry, _self_id: usize) {
+	}
+
+	fn build_fragment_shader(
+		&self,
+		pool: &ResourcePool<Box<dyn MaterialNode>>,
+		visited: &mut HashMap<usize, bool>,
+		self_id: usize,
+	) -> String {
+		if visited.contains_key(&self_id) {
+			return "".to_string();
+		}
+		visited.insert(self_id, true);
+
+		let node = pool.borrow(&self.node).unwrap();
+		let (_, value_type) = self.get_input_types(pool)[0];
+
+		node.build_fragment_shader(pool, visited, self.node.id) +
+		&format!("let {} = {}.{};\n",
+			self.get_fragment_output(self_id),
+			get_converted_fragment_output(pool, &self.node, &value_type),
+			self.components,
+		)
+	}
+
+	fn get_fragment_output(&self, self_id: usize) -> String {
+		format!("swizzle_output_{}", self_id)
+	}
+
+	fn get_input_types(
+		&self,
+		pool: &ResourcePool<Box<dyn MaterialNode>>,
+	) -> Vec<(ResourceId<Box<dyn MaterialNode>>, ValueType)> {
+		let required_count = self.get_required_component_count();
+		let input_type = pool.borrow(&self.node).unwrap().get_output_type(pool);
+		let value_type = match input_type.get_component_count() {
+			Some(count) if count >= required_count => input_type,
+			_ => ValueType::from_component_count(required_count).unwrap(),
+		};
+		vec![(self.node, value_type)]
+	}
+
+	fn get_output_type(
+		&self,
+		_pool: &ResourcePool<Box<dyn MaterialNode>>,
+	) -> ValueType {
+		ValueType::from_component_count(self.components.len() as u32).unwrap()
+	}
+
+	fn describe(&self) -> Option<ResourceNodeDescription> {
+		Some(NodeDescription::Swizzle {
+			node: self.node,
+			components: self.components.clone(),
+		})
+	}
+}
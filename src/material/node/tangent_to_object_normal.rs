@@ -1,8 +1,17 @@
 use std::collections::HashMap;
 use crate::{
-	material::node::node::{
-		MaterialNode,
-		UniformContents,
+	material::{
+		description::{
+			NodeDescription,
+			ResourceNodeDescription,
+		},
+		function_registry::FunctionRegistry,
+		node::node::{
+			MaterialNode,
+			UniformContents,
+			ValueType,
+			get_converted_fragment_output,
+		},
 	},
 	resource::resource::{
 		ResourceId,
@@ -35,8 +44,42 @@ fn perturb_normal_to_arb(
   }
   return normalize(t * (map_n.x * scale) + b * (map_n.y * scale) + n * map_n.z);
 }
+
+// No derivatives so usable in the vertex stage
+fn perturb_normal_with_tangent_frame(
+  surf_norm: vec3<f32>,
+  tangent: vec4<f32>,
+  map_n: vec3<f32>
+) -> vec3<f32> {
+  if (dot(tangent.xyz, tangent.xyz) == 0.0) {
+    return normalize(surf_norm);
+  }
+  let n: vec3<f32> = normalize(surf_norm);
+  let t: vec3<f32> = normalize(tangent.xyz - n * dot(n, tangent.xyz));
+  let b: vec3<f32> = cross(n, t) * tangent.w;
+  return normalize(t * map_n.x + b * map_n.y + n * map_n.z);
+}
+
+fn perturb_normal_with_tangent(
+  eye_pos: vec3<f32>,
+  surf_norm: vec3<f32>,
+  tangent: vec4<f32>,
+  map_n: vec3<f32>,
+  uv: vec2<f32>
+) -> vec3<f32> {
+  // Derivatives need uniform control flow so always compute the fallback
+  let arb_n: vec3<f32> = perturb_normal_to_arb(eye_pos, surf_norm, map_n, uv);
+  if (dot(tangent.xyz, tangent.xyz) == 0.0) {
+    return arb_n;
+  }
+  return perturb_normal_with_tangent_frame(surf_norm, tangent, map_n);
+}
 ";
 
+// Uses the interpolated tangent frame if the geometry has tangents,
+// otherwise reconstructs it from screen space derivatives. In the vertex
+// stage derivatives are unavailable so the normal is returned as is
+// if the geometry has no tangents.
 pub struct TangentToObjectNormalNode {
 	node: ResourceId<Box<dyn MaterialNode>>,
 }
@@ -46,7 +89,7 @@ impl TangentToObjectNormalNode {
 		node: ResourceId<Box<dyn MaterialNode>>,
 	) -> Self {
 		TangentToObjectNormalNode {
-			node: node,
+			node,
 		}
 	}
 }
@@ -62,8 +105,7 @@ impl MaterialNode for TangentToObjectNormalNode {
 		pool.borrow(&self.node).unwrap().collect_nodes(
 			pool, nodes, visited, self.node,
 		);
-		if !visited.contains_key(&self_rid) {
-			visited.insert(self_rid, true);
+		if visited.insert(self_rid, true).is_none() {
 			nodes.push(self_rid);
 		}
 	}
@@ -73,12 +115,11 @@ impl MaterialNode for TangentToObjectNormalNode {
 	}
 
 	fn build_declaration(&self, _self_id: usize) -> String {
-		format!("")
+		String::new()
 	}
 
-	fn build_functions(&self, _self_id: usize) -> String {
-		// @TODO: Add self_id suffix for unique function name
-		FUNCTION_CHUNK.to_string()
+	fn register_functions(&self, registry: &mut FunctionRegistry, _self_id: usize) {
+		registry.register(module_path!(), FUNCTION_CHUNK);
 	}
 
 	fn build_fragment_shader(
@@ -95,13 +136,53 @@ impl MaterialNode for TangentToObjectNormalNode {
 		let node = pool.borrow(&self.node).unwrap();
 
 		node.build_fragment_shader(pool, visited, self.node.id) +
-		&format!("let {} = perturb_normal_to_arb(-in.view_position, in.normal, {}, in.uv);\n",
+		&format!("let {} = perturb_normal_with_tangent(-in.view_position, in.normal, in.tangent, {}, in.uv);\n",
 			self.get_fragment_output(self_id),
-			node.get_fragment_output(self.node.id),
+			get_converted_fragment_output(pool, &self.node, &ValueType::Vector3),
 		)
 	}
 
 	fn get_fragment_output(&self, self_id: usize) -> String {
 		format!("tangent_to_object_normal_output_{}", self_id)
 	}
+
+	// No implicit derivatives in the vertex stage
+	fn build_vertex_shader(
+		&self,
+		pool: &ResourcePool<Box<dyn MaterialNode>>,
+		visited: &mut HashMap<usize, bool>,
+		self_id: usize,
+	) -> String {
+		if visited.insert(self_id, true).is_some() {
+			return String::new();
+		}
+
+		let node = pool.borrow(&self.node).unwrap();
+
+		node.build_vertex_shader(pool, visited, self.node.id) +
+		&format!("let {} = perturb_normal_with_tangent_frame(in.normal, in.tangent, {});\n",
+			self.get_fragment_output(self_id),
+			get_converted_fragment_output(pool, &self.node, &ValueType::Vector3),
+		)
+	}
+
+	fn get_input_types(
+		&self,
+		_pool: &ResourcePool<Box<dyn MaterialNode>>,
+	) -> Vec<(ResourceId<Box<dyn MaterialNode>>, ValueType)> {
+		vec![(self.node, ValueType::Vector3)]
+	}
+
+	fn get_output_type(
+		&self,
+		_pool: &ResourcePool<Box<dyn MaterialNode>>,
+	) -> ValueType {
+		ValueType::Vector3
+	}
+
+	fn describe(&self) -> Option<ResourceNodeDescription> {
+		Some(NodeDescription::TangentToObjectNormal {
+			node: self.node,
+		})
+	}
 }
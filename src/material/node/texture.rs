@@ -1,8 +1,16 @@
 use std::collections::HashMap;
 use crate::{
-	material::node::node::{
-		MaterialNode,
-		UniformContents,
+	material::{
+		description::{
+			NodeDescription,
+			ResourceNodeDescription,
+		},
+		function_registry::FunctionRegistry,
+		node::node::{
+			MaterialNode,
+			UniformContents,
+			ValueType,
+		},
 	},
 	resource::resource::{
 		ResourceId,
@@ -72,8 +80,7 @@ impl MaterialNode for TextureNode {
 		format!("")
 	}
 
-	fn build_functions(&self, _self_id: usize) -> String {
-		format!("")
+	fn register_functions(&self, _registry: &mut FunctionRegistry, _self_id: usize) {
 	}
 
 	fn build_fragment_shader(
@@ -97,4 +104,47 @@ impl MaterialNode for TextureNode {
 	fn get_fragment_output(&self, self_id: usize) -> String {
 		format!("texture_output_{}", self_id)
 	}
+
+	fn get_input_types(
+		&self,
+		_pool: &ResourcePool<Box<dyn MaterialNode>>,
+	) -> Vec<(ResourceId<Box<dyn MaterialNode>>, ValueType)> {
+		Vec::new()
+	}
+
+	fn get_output_type(
+		&self,
+		_pool: &ResourcePool<Box<dyn MaterialNode>>,
+	) -> ValueType {
+		ValueType::Vector4
+	}
+
+	fn describe(&self) -> Option<ResourceNodeDescription> {
+		Some(match self.contents {
+			UniformContents::Texture {texture, sampler} => NodeDescription::Texture {
+				texture,
+				sampler,
+			},
+			_ => panic!(),
+		})
+	}
+
+	// No implicit derivatives in the vertex stage
+	fn build_vertex_shader(
+		&self,
+		_pool: &ResourcePool<Box<dyn MaterialNode>>,
+		visited: &mut HashMap<usize, bool>,
+		self_id: usize,
+	) -> String {
+		if visited.contains_key(&self_id) {
+			return "".to_string();
+		}
+		visited.insert(self_id, true);
+
+		format!("let {} = textureSampleLevel({}, {}, in.uv, 0.0);\n",
+			self.get_fragment_output(self_id),
+			self.get_texture_name(),
+			self.get_sampler_name(),
+		)
+	}
 }
@@ -1,8 +1,17 @@
 use std::collections::HashMap;
 use crate::{
-	material::node::node::{
-		MaterialNode,
-		UniformContents,
+	material::{
+		description::{
+			NodeDescription,
+			ResourceNodeDescription,
+		},
+		function_registry::FunctionRegistry,
+		node::node::{
+			MaterialNode,
+			UniformContents,
+			ValueType,
+			get_converted_fragment_output,
+		},
 	},
 	resource::resource::{
 		ResourceId,
@@ -49,8 +58,7 @@ impl MaterialNode for ZNode {
 		format!("")
 	}
 
-	fn build_functions(&self, _self_id: usize) -> String {
-		format!("")
+	fn register_functions(&self, _registry: &mut FunctionRegistry, _self_id: usize) {
 	}
 
 	fn build_fragment_shader(
@@ -65,15 +73,41 @@ impl MaterialNode for ZNode {
 		visited.insert(self_id, true);
 
 		let node = pool.borrow(&self.node).unwrap();
+		let (_, value_type) = self.get_input_types(pool)[0];
 
 		node.build_fragment_shader(pool, visited, self.node.id) +
 		&format!("let {} = {}.z;\n",
 			self.get_fragment_output(self_id),
-			node.get_fragment_output(self.node.id),
+			get_converted_fragment_output(pool, &self.node, &value_type),
 		)
 	}
 
 	fn get_fragment_output(&self, self_id: usize) -> String {
 		format!("z_output_{}", self_id)
 	}
+
+	fn get_input_types(
+		&self,
+		pool: &ResourcePool<Box<dyn MaterialNode>>,
+	) -> Vec<(ResourceId<Box<dyn MaterialNode>>, ValueType)> {
+		// Any vector with three or more components works, a float is splatted
+		let value_type = match pool.borrow(&self.node).unwrap().get_output_type(pool) {
+			ValueType::Vector4 => ValueType::Vector4,
+			_ => ValueType::Vector3,
+		};
+		vec![(self.node, value_type)]
+	}
+
+	fn get_output_type(
+		&self,
+		_pool: &ResourcePool<Box<dyn MaterialNode>>,
+	) -> ValueType {
+		ValueType::Float
+	}
+
+	fn describe(&self) -> Option<ResourceNodeDescription> {
+		Some(NodeDescription::Z {
+			node: self.node,
+		})
+	}
 }
@@ -0,0 +1,92 @@
+use crate::math::vector3::Vector3;
+
+const ELEMENT_NUM: usize = 6;
+type Elements = [f32; ELEMENT_NUM];
+
+// Axis aligned bounding box, min xyz and max xyz
+pub struct Box3 {
+}
+
+impl Box3 {
+	// Empty
+	pub fn create() -> Elements {
+		let mut elements = [0.0; ELEMENT_NUM];
+		Self::make_empty(&mut elements);
+		elements
+	}
+
+	pub fn make_empty(b: &mut Elements) -> &mut Elements {
+		for i in 0..3 {
+			b[i] = f32::INFINITY;
+			b[i + 3] = f32::NEG_INFINITY;
+		}
+		b
+	}
+
+	pub fn copy<'a>(b: &'a mut Elements, src: &'a Elements) -> &'a mut Elements {
+		b.copy_from_slice(src);
+		b
+	}
+
+	pub fn is_empty(b: &Elements) -> bool {
+		b[3] < b[0] || b[4] < b[1] || b[5] < b[2]
+	}
+
+	pub fn expand_by_point<'a>(b: &'a mut Elements, p: &'a [f32; 3]) -> &'a mut Elements {
+		for i in 0..3 {
+			b[i] = b[i].min(p[i]);
+			b[i + 3] = b[i + 3].max(p[i]);
+		}
+		b
+	}
+
+	pub fn union<'a>(b: &'a mut Elements, b2: &'a Elements) -> &'a mut Elements {
+		for i in 0..3 {
+			b[i] = b[i].min(b2[i]);
+			b[i + 3] = b[i + 3].max(b2[i + 3]);
+		}
+		b
+	}
+
+	pub fn get_center<'a>(center: &'a mut [f32; 3], b: &'a Elements) -> &'a mut [f32; 3] {
+		for i in 0..3 {
+			center[i] = (b[i] + b[i + 3]) * 0.5;
+		}
+		center
+	}
+
+	pub fn get_size<'a>(size: &'a mut [f32; 3], b: &'a Elements) -> &'a mut [f32; 3] {
+		for i in 0..3 {
+			size[i] = b[i + 3] - b[i];
+		}
+		size
+	}
+
+	pub fn contains_point(b: &Elements, p: &[f32; 3]) -> bool {
+		(0..3).all(|i| p[i] >= b[i] && p[i] <= b[i + 3])
+	}
+
+	// Transforms the eight corners and takes their bounds
+	pub fn apply_matrix4<'a>(b: &'a mut Elements, m: &'a [f32; 16]) -> &'a mut Elements {
+		if Self::is_empty(b) {
+			return b;
+		}
+
+		let mut src = Self::create();
+		Self::copy(&mut src, b);
+		Self::make_empty(b);
+
+		let mut corner = Vector3::create();
+		for i in 0..8 {
+			Vector3::set(
+				&mut corner,
+				src[if i & 1 == 0 { 0 } else { 3 }],
+				src[if i & 2 == 0 { 1 } else { 4 }],
+				src[if i & 4 == 0 { 2 } else { 5 }],
+			);
+			Vector3::apply_matrix4(&mut corner, m);
+			Self::expand_by_point(b, &corner);
+		}
+		b
+	}
+}
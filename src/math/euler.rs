@@ -1,3 +1,5 @@
+use crate::math::matrix4::Matrix4;
+
 const ELEMENT_NUM: usize = 3;
 type Elements = [f32; ELEMENT_NUM];
 
@@ -14,29 +16,36 @@ impl Euler {
 		q: &'a [f32; 4],
 	) -> &'a mut Elements {
 		// Assume XYZ order
-		let q0 = q[0];
-		let q1 = q[1];
-		let q2 = q[2];
-		let q3 = q[3];
-
-		let q0q0 = q0 * q0;
-		let q0q1 = q0 * q1;
-		let q0q2 = q0 * q2;
-		let q0q3 = q0 * q3;
-		let q1q1 = q1 * q1;
-		let q1q2 = q1 * q2;
-		let q1q3 = q1 * q3;
-		let q2q2 = q2 * q2;
-		let q2q3 = q2 * q3;
-		let q3q3 = q3 * q3;
-
-		let roll = (2.0 * (q2q3 + q0q1)).atan2(q0q0 - q1q1 - q2q2 + q3q3);
-		let pitch = (2.0 * (q0q2 - q1q3)).asin();
-		let yaw = (2.0 * (q1q2 + q0q3)).atan2(q0q0 + q1q1 - q2q2 - q3q3);
-
-		e[0] = roll;
-		e[1] = pitch;
-		e[2] = yaw;
+		let quaternion = *q;
+		let mut m = Matrix4::create();
+		Matrix4::compose(&mut m, &[0.0; 3], &quaternion, &[1.0; 3]);
+		Self::set_from_rotation_matrix(e, &m);
+		e
+	}
+
+	// Assumes m is a pure rotation matrix, not scaled
+	pub fn set_from_rotation_matrix<'a>(
+		e: &'a mut Elements,
+		m: &'a [f32; 16],
+	) -> &'a mut Elements {
+		// Assume XYZ order
+		let m11 = m[0];
+		let m12 = m[4];
+		let m13 = m[8];
+		let m22 = m[5];
+		let m23 = m[9];
+		let m32 = m[6];
+		let m33 = m[10];
+
+		e[1] = m13.clamp(-1.0, 1.0).asin();
+
+		if m13.abs() < 0.9999999 {
+			e[0] = (-m23).atan2(m33);
+			e[2] = (-m12).atan2(m11);
+		} else {
+			e[0] = m32.atan2(m22);
+			e[2] = 0.0;
+		}
 
 		e
 	}
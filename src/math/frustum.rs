@@ -0,0 +1,60 @@
+const ELEMENT_NUM: usize = 24;
+type Elements = [f32; ELEMENT_NUM];
+
+// Six planes, each of them is normal xyz and constant, pointing inside.
+// Left, right, bottom, top, near and far in order.
+pub struct Frustum {
+}
+
+impl Frustum {
+	pub fn create() -> Elements {
+		[0.0; ELEMENT_NUM]
+	}
+
+	// From projection matrix * view matrix. Depth is in [0.0, 1.0]
+	// as the matrices Matrix4::make_perspective/orthographic() make.
+	pub fn set_from_projection_matrix<'a>(
+		f: &'a mut Elements,
+		m: &'a [f32; 16],
+	) -> &'a mut Elements {
+		let row = |i: usize| [m[i], m[i + 4], m[i + 8], m[i + 12]];
+		let r0 = row(0);
+		let r1 = row(1);
+		let r2 = row(2);
+		let r3 = row(3);
+
+		let planes = [
+			[r3[0] + r0[0], r3[1] + r0[1], r3[2] + r0[2], r3[3] + r0[3]],
+			[r3[0] - r0[0], r3[1] - r0[1], r3[2] - r0[2], r3[3] - r0[3]],
+			[r3[0] + r1[0], r3[1] + r1[1], r3[2] + r1[2], r3[3] + r1[3]],
+			[r3[0] - r1[0], r3[1] - r1[1], r3[2] - r1[2], r3[3] - r1[3]],
+			r2,
+			[r3[0] - r2[0], r3[1] - r2[1], r3[2] - r2[2], r3[3] - r2[3]],
+		];
+
+		for (i, plane) in planes.iter().enumerate() {
+			let length = (plane[0] * plane[0] + plane[1] * plane[1] + plane[2] * plane[2]).sqrt();
+			for j in 0..4 {
+				f[i * 4 + j] = plane[j] / length;
+			}
+		}
+
+		f
+	}
+
+	// Empty spheres don't intersect
+	pub fn intersects_sphere(f: &Elements, s: &[f32; 4]) -> bool {
+		if s[3] < 0.0 {
+			return false;
+		}
+
+		for plane in f.chunks(4) {
+			let distance = plane[0] * s[0] + plane[1] * s[1] + plane[2] * s[2] + plane[3];
+			if distance < -s[3] {
+				return false;
+			}
+		}
+
+		true
+	}
+}
@@ -325,6 +325,84 @@ impl Matrix4 {
 		)
 	}
 
+	// Rotation which turns -Z toward the target from the eye as cameras look.
+	// Translation is zero. If the up is parallel to the view direction
+	// the up is slightly tilted.
+	pub fn make_look_at<'a>(
+		m: &'a mut Elements,
+		eye: &[f32; 3],
+		target: &[f32; 3],
+		up: &[f32; 3],
+	) -> &'a mut Elements {
+		let mut z = *eye;
+		Vector3::sub(&mut z, target);
+		if Vector3::length(&z) == 0.0 {
+			z[2] = 1.0;
+		}
+		Vector3::normalize(&mut z);
+
+		let mut x = *up;
+		Vector3::cross(&mut x, &z);
+		if Vector3::length(&x) == 0.0 {
+			match up[2].abs() == 1.0 {
+				true => z[0] += 0.0001,
+				false => z[2] += 0.0001,
+			};
+			Vector3::normalize(&mut z);
+			Vector3::copy(&mut x, up);
+			Vector3::cross(&mut x, &z);
+		}
+		Vector3::normalize(&mut x);
+
+		let mut y = z;
+		Vector3::cross(&mut y, &x);
+
+		Self::identity(m);
+		m[0] = x[0];
+		m[1] = x[1];
+		m[2] = x[2];
+		m[4] = y[0];
+		m[5] = y[1];
+		m[6] = y[2];
+		m[8] = z[0];
+		m[9] = z[1];
+		m[10] = z[2];
+		m
+	}
+
+	pub fn make_rotation_x(m: &mut Elements, theta: f32) -> &mut Elements {
+		let c = theta.cos();
+		let s = theta.sin();
+		Self::identity(m);
+		m[5] = c;
+		m[6] = s;
+		m[9] = -s;
+		m[10] = c;
+		m
+	}
+
+	pub fn make_rotation_y(m: &mut Elements, theta: f32) -> &mut Elements {
+		let c = theta.cos();
+		let s = theta.sin();
+		Self::identity(m);
+		m[0] = c;
+		m[2] = -s;
+		m[8] = s;
+		m[10] = c;
+		m
+	}
+
+	pub fn make_rotation_z(m: &mut Elements, theta: f32) -> &mut Elements {
+		let c = theta.cos();
+		let s = theta.sin();
+		Self::identity(m);
+		m[0] = c;
+		m[1] = s;
+		m[4] = -s;
+		m[5] = c;
+		m
+	}
+
 	pub fn make_perspective(
 		m: &mut Elements,
 		fovy: f32,
@@ -354,4 +432,38 @@ impl Matrix4 {
 
 		m
 	}
+
+	// Depth is mapped to [0.0, 1.0] as the perspective one
+	pub fn make_orthographic(
+		m: &mut Elements,
+		left: f32,
+		right: f32,
+		top: f32,
+		bottom: f32,
+		near: f32,
+		far: f32
+	) -> &mut Elements {
+		let w = 1.0 / (right - left);
+		let h = 1.0 / (top - bottom);
+		let nf = 1.0 / (near - far);
+
+		m[0] = 2.0 * w;
+		m[1] = 0.0;
+		m[2] = 0.0;
+		m[3] = 0.0;
+		m[4] = 0.0;
+		m[5] = 2.0 * h;
+		m[6] = 0.0;
+		m[7] = 0.0;
+		m[8] = 0.0;
+		m[9] = 0.0;
+		m[10] = nf;
+		m[11] = 0.0;
+		m[12] = -(right + left) * w;
+		m[13] = -(top + bottom) * h;
+		m[14] = near * nf;
+		m[15] = 1.0;
+
+		m
+	}
 }
@@ -1,7 +1,11 @@
+pub mod box3;
 pub mod color;
 pub mod euler;
+pub mod frustum;
 pub mod matrix3;
 pub mod matrix3gpu;
 pub mod matrix4;
 pub mod quaternion;
+pub mod ray;
+pub mod sphere;
 pub mod vector3;
\ No newline at end of file
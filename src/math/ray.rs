@@ -0,0 +1,139 @@
+use crate::math::vector3::Vector3;
+
+const ELEMENT_NUM: usize = 6;
+type Elements = [f32; ELEMENT_NUM];
+
+// Origin xyz and normalized direction xyz
+pub struct Ray {
+}
+
+impl Ray {
+	// From the origin toward -Z
+	pub fn create() -> Elements {
+		[0.0, 0.0, 0.0, 0.0, 0.0, -1.0]
+	}
+
+	// The direction is normalized
+	pub fn set<'a>(r: &'a mut Elements, origin: &[f32; 3], direction: &[f32; 3]) -> &'a mut Elements {
+		let mut d = *direction;
+		Vector3::normalize(&mut d);
+		r[0] = origin[0];
+		r[1] = origin[1];
+		r[2] = origin[2];
+		r[3] = d[0];
+		r[4] = d[1];
+		r[5] = d[2];
+		r
+	}
+
+	pub fn copy<'a>(r: &'a mut Elements, src: &'a Elements) -> &'a mut Elements {
+		r.copy_from_slice(src);
+		r
+	}
+
+	pub fn get_origin(r: &Elements) -> [f32; 3] {
+		[r[0], r[1], r[2]]
+	}
+
+	pub fn get_direction(r: &Elements) -> [f32; 3] {
+		[r[3], r[4], r[5]]
+	}
+
+	// The point at the distance t from the origin
+	pub fn at<'a>(r: &Elements, t: f32, v: &'a mut [f32; 3]) -> &'a mut [f32; 3] {
+		v[0] = r[0] + r[3] * t;
+		v[1] = r[1] + r[4] * t;
+		v[2] = r[2] + r[5] * t;
+		v
+	}
+
+	// The direction is normalized again, so distances along the ray
+	// are not preserved if the matrix has scale
+	pub fn apply_matrix4<'a>(r: &'a mut Elements, m: &'a [f32; 16]) -> &'a mut Elements {
+		let mut origin = Self::get_origin(r);
+		let mut direction = Self::get_direction(r);
+		Vector3::apply_matrix4(&mut origin, m);
+		Vector3::transform_direction(&mut direction, m);
+		Self::set(r, &origin, &direction)
+	}
+
+	// Distance to the sphere surface, or to the exit point if
+	// the origin is inside. None if the ray misses or the sphere is empty.
+	pub fn intersect_sphere(r: &Elements, s: &[f32; 4]) -> Option<f32> {
+		if s[3] < 0.0 {
+			return None;
+		}
+
+		let mut to_center = [s[0], s[1], s[2]];
+		Vector3::sub(&mut to_center, &Self::get_origin(r));
+		let tca = Vector3::dot(&to_center, &Self::get_direction(r));
+		let d2 = Vector3::dot(&to_center, &to_center) - tca * tca;
+		let radius2 = s[3] * s[3];
+		if d2 > radius2 {
+			return None;
+		}
+
+		let thc = (radius2 - d2).sqrt();
+		let t0 = tca - thc;
+		let t1 = tca + thc;
+		if t1 < 0.0 {
+			None
+		} else if t0 < 0.0 {
+			Some(t1)
+		} else {
+			Some(t0)
+		}
+	}
+
+	// Moller-Trumbore. Returns the distance and the barycentric coordinates
+	// u and v of b and c. Front faces are counter-clockwise seen from
+	// the ray origin and back faces are ignored if backface_culling is true.
+	pub fn intersect_triangle(
+		r: &Elements,
+		a: &[f32; 3],
+		b: &[f32; 3],
+		c: &[f32; 3],
+		backface_culling: bool,
+	) -> Option<[f32; 3]> {
+		const EPSILON: f32 = 0.0000001;
+
+		let direction = Self::get_direction(r);
+
+		let mut edge1 = *b;
+		let mut edge2 = *c;
+		Vector3::sub(&mut edge1, a);
+		Vector3::sub(&mut edge2, a);
+
+		let mut p = direction;
+		Vector3::cross(&mut p, &edge2);
+		let det = Vector3::dot(&edge1, &p);
+		if backface_culling && det < EPSILON {
+			return None;
+		}
+		if det.abs() < EPSILON {
+			return None;
+		}
+		let inv_det = 1.0 / det;
+
+		let mut s = Self::get_origin(r);
+		Vector3::sub(&mut s, a);
+		let u = Vector3::dot(&s, &p) * inv_det;
+		if !(0.0..=1.0).contains(&u) {
+			return None;
+		}
+
+		let mut q = s;
+		Vector3::cross(&mut q, &edge1);
+		let v = Vector3::dot(&direction, &q) * inv_det;
+		if v < 0.0 || u + v > 1.0 {
+			return None;
+		}
+
+		let t = Vector3::dot(&edge2, &q) * inv_det;
+		if t < 0.0 {
+			return None;
+		}
+
+		Some([t, u, v])
+	}
+}
@@ -0,0 +1,50 @@
+use crate::math::vector3::Vector3;
+
+const ELEMENT_NUM: usize = 4;
+type Elements = [f32; ELEMENT_NUM];
+
+// Bounding sphere, center xyz and radius. Negative radius is empty.
+pub struct Sphere {
+}
+
+impl Sphere {
+	// Empty
+	pub fn create() -> Elements {
+		[0.0, 0.0, 0.0, -1.0]
+	}
+
+	pub fn set<'a>(s: &'a mut Elements, center: &[f32; 3], radius: f32) -> &'a mut Elements {
+		s[0] = center[0];
+		s[1] = center[1];
+		s[2] = center[2];
+		s[3] = radius;
+		s
+	}
+
+	pub fn copy<'a>(s: &'a mut Elements, src: &'a Elements) -> &'a mut Elements {
+		s.copy_from_slice(src);
+		s
+	}
+
+	pub fn is_empty(s: &Elements) -> bool {
+		s[3] < 0.0
+	}
+
+	pub fn contains_point(s: &Elements, p: &[f32; 3]) -> bool {
+		Vector3::distance(&[s[0], s[1], s[2]], p) <= s[3]
+	}
+
+	// The radius is scaled by the largest axis scale
+	pub fn apply_matrix4<'a>(s: &'a mut Elements, m: &'a [f32; 16]) -> &'a mut Elements {
+		let mut center = [s[0], s[1], s[2]];
+		Vector3::apply_matrix4(&mut center, m);
+
+		let scale_x = m[0] * m[0] + m[1] * m[1] + m[2] * m[2];
+		let scale_y = m[4] * m[4] + m[5] * m[5] + m[6] * m[6];
+		let scale_z = m[8] * m[8] + m[9] * m[9] + m[10] * m[10];
+		let scale = scale_x.max(scale_y).max(scale_z).sqrt();
+
+		let radius = s[3] * scale;
+		Self::set(s, &center, radius)
+	}
+}
@@ -53,4 +53,76 @@ impl Vector3 {
 
 		v
 	}
+
+	pub fn scale(v: &mut Elements, s: f32) -> &mut Elements {
+		for e in v.iter_mut() {
+			*e *= s;
+		}
+		v
+	}
+
+	pub fn dot(v: &Elements, v2: &Elements) -> f32 {
+		v[0] * v2[0] + v[1] * v2[1] + v[2] * v2[2]
+	}
+
+	pub fn cross<'a>(v: &'a mut Elements, v2: &'a Elements) -> &'a mut Elements {
+		let x = v[1] * v2[2] - v[2] * v2[1];
+		let y = v[2] * v2[0] - v[0] * v2[2];
+		let z = v[0] * v2[1] - v[1] * v2[0];
+		v[0] = x;
+		v[1] = y;
+		v[2] = z;
+		v
+	}
+
+	pub fn min<'a>(v: &'a mut Elements, v2: &'a Elements) -> &'a mut Elements {
+		for i in 0..ELEMENT_NUM {
+			v[i] = v[i].min(v2[i]);
+		}
+		v
+	}
+
+	pub fn max<'a>(v: &'a mut Elements, v2: &'a Elements) -> &'a mut Elements {
+		for i in 0..ELEMENT_NUM {
+			v[i] = v[i].max(v2[i]);
+		}
+		v
+	}
+
+	pub fn distance(v: &Elements, v2: &Elements) -> f32 {
+		((v[0] - v2[0]).powf(2.0) + (v[1] - v2[1]).powf(2.0) + (v[2] - v2[2]).powf(2.0)).sqrt()
+	}
+
+	// As a position, w = 1.0 with perspective divide
+	pub fn apply_matrix4<'a>(v: &'a mut Elements, m: &'a [f32; 16]) -> &'a mut Elements {
+		let x = v[0];
+		let y = v[1];
+		let z = v[2];
+		let w = 1.0 / (m[3] * x + m[7] * y + m[11] * z + m[15]);
+		v[0] = (m[0] * x + m[4] * y + m[8] * z + m[12]) * w;
+		v[1] = (m[1] * x + m[5] * y + m[9] * z + m[13]) * w;
+		v[2] = (m[2] * x + m[6] * y + m[10] * z + m[14]) * w;
+		v
+	}
+
+	pub fn apply_matrix3<'a>(v: &'a mut Elements, m: &'a [f32; 9]) -> &'a mut Elements {
+		let x = v[0];
+		let y = v[1];
+		let z = v[2];
+		v[0] = m[0] * x + m[3] * y + m[6] * z;
+		v[1] = m[1] * x + m[4] * y + m[7] * z;
+		v[2] = m[2] * x + m[5] * y + m[8] * z;
+		v
+	}
+
+	// As a direction, w = 0.0, and normalizes
+	pub fn transform_direction<'a>(v: &'a mut Elements, m: &'a [f32; 16]) -> &'a mut Elements {
+		let x = v[0];
+		let y = v[1];
+		let z = v[2];
+		v[0] = m[0] * x + m[4] * y + m[8] * z;
+		v[1] = m[1] * x + m[5] * y + m[9] * z;
+		v[2] = m[2] * x + m[6] * y + m[10] * z;
+		Self::normalize(v)
+	}
 }
@@ -1,6 +1,7 @@
 pub mod wgpu_attributes;
 pub mod wgpu_bindings;
 pub mod wgpu_indices;
+pub mod wgpu_instances;
 pub mod wgpu_render_pipeline;
 pub mod wgpu_renderer;
 pub mod wgpu_samplers;
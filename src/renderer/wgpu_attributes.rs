@@ -1,7 +1,10 @@
 use std::collections::HashMap;
 
 use crate::{
-	geometry::attribute::Attribute,
+	geometry::attribute::{
+		Attribute,
+		AttributeData,
+	},
 	resource::resource::{
 		ResourceId,
 		ResourcePools,
@@ -10,12 +13,19 @@ use crate::{
 
 pub struct WGPUAttributes {
 	attributes: HashMap<ResourceId<Attribute>, wgpu::Buffer>,
+	default_color: wgpu::Buffer,
+	// Number of the values in the default buffers
+	default_count: usize,
+	default_tangent: wgpu::Buffer,
 }
 
 impl WGPUAttributes {
-	pub fn new() -> Self {
+	pub fn new(device: &wgpu::Device) -> Self {
 		WGPUAttributes {
-			attributes: HashMap::new()
+			attributes: HashMap::new(),
+			default_color: create_default_buffer(device, 1.0, 1),
+			default_count: 1,
+			default_tangent: create_default_buffer(device, 0.0, 1),
 		}
 	}
 
@@ -23,6 +33,28 @@ impl WGPUAttributes {
 		self.attributes.get(attribute)
 	}
 
+	// White, bound with instance step mode for geometries without colors
+	pub fn borrow_default_color(&self) -> &wgpu::Buffer {
+		&self.default_color
+	}
+
+	// The default buffers are read per instance so they need a value
+	// for each instance of the instanced meshes
+	pub fn reserve_defaults(&mut self, device: &wgpu::Device, instance_count: usize) {
+		if instance_count > self.default_count {
+			self.default_color.destroy();
+			self.default_tangent.destroy();
+			self.default_color = create_default_buffer(device, 1.0, instance_count);
+			self.default_tangent = create_default_buffer(device, 0.0, instance_count);
+			self.default_count = instance_count;
+		}
+	}
+
+	// Zero, shaders fall back to derivatives for geometries without tangents
+	pub fn borrow_default_tangent(&self) -> &wgpu::Buffer {
+		&self.default_tangent
+	}
+
 	// @TODO: Implement correctly
 	pub fn update(
 		&mut self,
@@ -34,7 +66,7 @@ impl WGPUAttributes {
 			if let Some(attribute) = pools.borrow::<Attribute>().borrow(attribute_rid) {
 				self.attributes.insert(*attribute_rid, create_buffer(
 					device,
-					bytemuck::cast_slice(attribute.borrow_data()),
+					attribute.borrow_bytes(),
 					wgpu::BufferUsages::VERTEX,
 				));
 			}
@@ -50,3 +82,27 @@ fn create_buffer(device: &wgpu::Device, contents: &[u8], usage: wgpu::BufferUsag
 		usage: usage,
 	})
 }
+
+// Four component values per instance
+fn create_default_buffer(device: &wgpu::Device, value: f32, count: usize) -> wgpu::Buffer {
+	create_buffer(
+		device,
+		bytemuck::cast_slice(&vec![value; count * 4]),
+		wgpu::BufferUsages::VERTEX,
+	)
+}
+
+// @TODO: Proper error handling
+pub fn get_vertex_format(attribute: &Attribute) -> wgpu::VertexFormat {
+	match (attribute.borrow_attribute_data(), attribute.get_item_size()) {
+		(AttributeData::Float32(_), 1) => wgpu::VertexFormat::Float32,
+		(AttributeData::Float32(_), 2) => wgpu::VertexFormat::Float32x2,
+		(AttributeData::Float32(_), 3) => wgpu::VertexFormat::Float32x3,
+		(AttributeData::Float32(_), 4) => wgpu::VertexFormat::Float32x4,
+		(AttributeData::Unorm8(_), 2) => wgpu::VertexFormat::Unorm8x2,
+		(AttributeData::Unorm8(_), 4) => wgpu::VertexFormat::Unorm8x4,
+		(AttributeData::Unorm16(_), 2) => wgpu::VertexFormat::Unorm16x2,
+		(AttributeData::Unorm16(_), 4) => wgpu::VertexFormat::Unorm16x4,
+		(_, item_size) => panic!("Unsupported attribute item size {}", item_size),
+	}
+}
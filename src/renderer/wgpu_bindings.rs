@@ -22,15 +22,48 @@ use crate::{
 		ResourcePools,
 	},
 	scene::{
-		camera::PerspectiveCamera,
+		camera::Camera,
 		node::Node,
 	},
 };
 
+// Per-frame values shared by all the objects
+pub struct WGPUGlobals {
+	// World space direction the camera looks at
+	pub camera_direction: [f32; 3],
+	pub camera_position: [f32; 3],
+	pub delta_time: f32,
+	pub resolution: [f32; 2],
+	pub time: f32,
+}
+
+impl WGPUGlobals {
+	// Laid out as Globals block in the shader
+	fn to_array(&self) -> [f32; 12] {
+		[
+			self.camera_position[0],
+			self.camera_position[1],
+			self.camera_position[2],
+			self.time,
+			self.resolution[0],
+			self.resolution[1],
+			self.delta_time,
+			0.0,
+			self.camera_direction[0],
+			self.camera_direction[1],
+			self.camera_direction[2],
+			0.0,
+		]
+	}
+}
+
 pub struct WGPUBinding {
 	buffers: Vec<wgpu::Buffer>,
 	group: wgpu::BindGroup,
 	layout: wgpu::BindGroupLayout,
+	// Node and camera node world matrix versions the object buffer
+	// was written with
+	object_versions: Option<(u64, ResourceId<Node>, u64)>,
 }
 
 impl WGPUBinding {
@@ -76,6 +109,7 @@ impl WGPUBinding {
 			buffers: buffers,
 			group: group,
 			layout: layout,
+			object_versions: None,
 		}
 	}
 
@@ -91,14 +125,45 @@ impl WGPUBinding {
 		&self.buffers[index]
 	}
 
+	#[allow(clippy::too_many_arguments)]
 	pub fn update(
-		&self,
+		&mut self,
 		queue: &wgpu::Queue,
 		pools: &ResourcePools,
 		node: &Node,
-		camera: &PerspectiveCamera,
+		camera: &dyn Camera,
+		camera_node_rid: &ResourceId<Node>,
 		camera_node: &Node,
+		globals: &WGPUGlobals,
 		material: &Material,
+	) {
+		// binding 0 : Object (model-view matrix, normal matrix, model matrix)
+		// binding 1 : Camera (projection matrix)
+		// binding 2 : Uniform buffers
+		// binding 3 : Globals (camera position, time, resolution, delta time)
+		let object_versions = (
+			node.get_world_matrix_version(),
+			*camera_node_rid,
+			camera_node.get_world_matrix_version(),
+		);
+		if self.object_versions != Some(object_versions) {
+			self.update_object(queue, node, camera_node);
+			self.object_versions = Some(object_versions);
+		}
+
+		// @TODO: Should we calculate projection matrix * model-view matrix in CPU?
+		queue.write_buffer(&self.buffers[1], 0, bytemuck::cast_slice(camera.borrow_projection_matrix()));
+		queue.write_buffer(&self.buffers[3], 0, bytemuck::cast_slice(&globals.to_array()));
+
+		self.update_uniforms(queue, pools, material);
+	}
+
+	// Only depends on the node and camera node world matrices
+	fn update_object(
+		&self,
+		queue: &wgpu::Queue,
+		node: &Node,
+		camera_node: &Node,
 	) {
 		// @TODO: Is calculating them here inefficient?
 		let mut model_view_matrix = Matrix4::create();
@@ -111,14 +176,17 @@ impl WGPUBinding {
 		Matrix3::make_normal_from_matrix4(&mut normal_matrix, &model_view_matrix);
 		Matrix3GPU::copy_from_matrix3(&mut normal_matrix_gpu, &normal_matrix);
 
-		// binding 0 : Object (model-view matrix, normal matrix)
-		// binding 1 : Camera (projection matrix)
-		// binding 2 : Uniform buffers
-		// @TODO: Should we calculate projection matrix * model-view matrix in CPU?
 		queue.write_buffer(&self.buffers[0], 0, bytemuck::cast_slice(&model_view_matrix));
 		queue.write_buffer(&self.buffers[0], 64, bytemuck::cast_slice(&normal_matrix_gpu));
-		queue.write_buffer(&self.buffers[1], 0, bytemuck::cast_slice(camera.borrow_projection_matrix()));
+		queue.write_buffer(&self.buffers[0], 112, bytemuck::cast_slice(node.borrow_world_matrix()));
+	}
 
+	fn update_uniforms(
+		&self,
+		queue: &wgpu::Queue,
+		pools: &ResourcePools,
+		material: &Material,
+	) {
 		let mut offset = 0;
 		// @TODO: Optimize
 		for contents in material.borrow_contents(
@@ -168,10 +236,11 @@ impl WGPUBinding {
 		let mut buffer_size = 0;
 		let mut max_align = 0;
 
-		// binding 0 : Object (model-view matrix, normal matrix)
+		// binding 0 : Object (model-view matrix, normal matrix, model matrix)
 		// binding 1 : Camera (projection matrix)
 		// binding 2 : Uniform buffers
-		// binding 3- : Textures
+		// binding 3 : Globals (camera position, time, resolution, delta time)
+		// binding 4- : Textures
 		// binding n- : Samplers
 
 		for contents in material.borrow_contents(
@@ -200,7 +269,7 @@ impl WGPUBinding {
 			pools.borrow::<Box<dyn MaterialNode>>(),
 		).iter() {
 			entries.push(wgpu::BindGroupLayoutEntry {
-				binding: entries.len() as u32 + 3,
+				binding: entries.len() as u32 + 4,
 				count: None,
 				ty: wgpu::BindingType::Texture {
 					multisampled: false,
@@ -219,7 +288,7 @@ impl WGPUBinding {
 			pools.borrow::<Box<dyn MaterialNode>>(),
 		).iter() {
 			entries.push(wgpu::BindGroupLayoutEntry {
-				binding: entries.len() as u32 + 3,
+				binding: entries.len() as u32 + 4,
 				count: None,
 				// @TODO: Fix me if needed
 				ty: wgpu::BindingType::Sampler {
@@ -239,7 +308,7 @@ impl WGPUBinding {
 			ty: wgpu::BindingType::Buffer {
 				ty: wgpu::BufferBindingType::Uniform,
 				has_dynamic_offset: false,
-				min_binding_size: wgpu::BufferSize::new((16 + 12) * 4),
+				min_binding_size: wgpu::BufferSize::new((16 + 12 + 16) * 4),
 			},
 			visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
 		});
@@ -252,7 +321,7 @@ impl WGPUBinding {
 				has_dynamic_offset: false,
 				min_binding_size: wgpu::BufferSize::new(16 * 4),
 			},
-			visibility: wgpu::ShaderStages::VERTEX,
+			visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
 		});
 
 		entries.push(wgpu::BindGroupLayoutEntry {
@@ -267,6 +336,17 @@ impl WGPUBinding {
 			visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
 		});
 
+		entries.push(wgpu::BindGroupLayoutEntry {
+			binding: 3,
+			count: None,
+			ty: wgpu::BindingType::Buffer {
+				ty: wgpu::BufferBindingType::Uniform,
+				has_dynamic_offset: false,
+				min_binding_size: wgpu::BufferSize::new(8 * 4),
+			},
+			visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+		});
+
 		device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
 			entries: &entries,
 			label: None,
@@ -322,11 +402,12 @@ impl WGPUBinding {
 	) -> Vec<wgpu::Buffer> {
 		let mut buffers = Vec::new();
 
-		// binding 0 : Object (model-view matrix, normal matrix)
+		// binding 0 : Object (model-view matrix, normal matrix, model matrix)
 		// binding 1 : Camera (projection matrix)
 		// binding 2 : Uniform buffers
+		// binding 3 : Globals (camera position, time, resolution, delta time)
 
-		buffers.push(create_buffer(device, (16 + 9) * 4));
+		buffers.push(create_buffer(device, (16 + 12 + 16) * 4));
 		buffers.push(create_buffer(device, 16 * 4));
 
 		let mut buffer_size = 0;
@@ -346,26 +427,36 @@ impl WGPUBinding {
 		}
 
 		buffers.push(create_buffer(device, buffer_size as usize));
+		buffers.push(create_buffer(device, 12 * 4));
 		buffers
 	}
 }
 
-pub struct WGPUBindings {
-	groups: HashMap<ResourceId<Node>, WGPUBinding>
+// Per node, mesh and mesh material index because a node can have
+// multiple meshes and a mesh can have multiple materials.
+// T is Mesh or InstancedMesh.
+pub struct WGPUBindings<T> {
+	groups: HashMap<(ResourceId<Node>, ResourceId<T>, usize), WGPUBinding>
 }
 
 // @TODO: Implement correctly
-impl WGPUBindings {
+impl<T> WGPUBindings<T> {
 	pub fn new() -> Self {
 		WGPUBindings {
 			groups: HashMap::new()
 		}
 	}
 
-	pub fn borrow(&self, node: &ResourceId<Node>) -> Option<&WGPUBinding> {
-		self.groups.get(node)
+	pub fn borrow(
+		&self,
+		node: &ResourceId<Node>,
+		mesh: &ResourceId<T>,
+		material_index: usize,
+	) -> Option<&WGPUBinding> {
+		self.groups.get(&(*node, *mesh, material_index))
 	}
 
+	#[allow(clippy::too_many_arguments)]
 	pub fn update(&mut self,
 		device: &wgpu::Device,
 		queue: &wgpu::Queue,
@@ -373,23 +464,25 @@ impl WGPUBindings {
 		wgpu_samplers: &WGPUSamplers,
 		pools: &ResourcePools,
 		node_rid: &ResourceId<Node>,
-		camera: &PerspectiveCamera,
+		mesh_rid: &ResourceId<T>,
+		material_index: usize,
+		camera: &dyn Camera,
+		camera_node_rid: &ResourceId<Node>,
 		camera_node: &Node,
+		globals: &WGPUGlobals,
 		material: &Material,
 	) {
-		if !self.groups.contains_key(node_rid) {
-			self.groups.insert(*node_rid, WGPUBinding::new(
-				device,
-				wgpu_textures,
-				wgpu_samplers,
-				pools,
-				material
-			));
-		}
+		let key = (*node_rid, *mesh_rid, material_index);
+		let binding = self.groups.entry(key).or_insert_with(|| WGPUBinding::new(
+			device,
+			wgpu_textures,
+			wgpu_samplers,
+			pools,
+			material
+		));
 
 		if let Some(node) = pools.borrow::<Node>().borrow(node_rid) {
-			let binding = self.groups.get(node_rid).unwrap();
-			binding.update(queue, pools, node, camera, camera_node, material);
+			binding.update(queue, pools, node, camera, camera_node_rid, camera_node, globals, material);
 		}
 	}
 }
@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+
+use crate::{
+	resource::resource::ResourceId,
+	scene::{
+		instanced_mesh::InstancedMesh,
+		node::Node,
+	},
+};
+
+// Per-instance vertex buffers of an instanced mesh, holding only the
+// instances passed the frustum culling
+pub struct WGPUInstanceBuffers {
+	// Number of the instances the buffers can hold
+	capacity: usize,
+	colors: Option<wgpu::Buffer>,
+	count: u32,
+	matrices: wgpu::Buffer,
+	// Instanced mesh version and the visible instances the buffers
+	// were written with
+	written: Option<(u64, Vec<usize>)>,
+}
+
+impl WGPUInstanceBuffers {
+	fn new(device: &wgpu::Device, capacity: usize, has_colors: bool) -> Self {
+		WGPUInstanceBuffers {
+			capacity,
+			colors: match has_colors {
+				true => Some(create_buffer(device, capacity * 3 * 4)),
+				false => None,
+			},
+			count: 0,
+			matrices: create_buffer(device, capacity * 16 * 4),
+			written: None,
+		}
+	}
+
+	pub fn borrow_matrices(&self) -> &wgpu::Buffer {
+		&self.matrices
+	}
+
+	// None if the instanced mesh has no colors
+	pub fn borrow_colors(&self) -> Option<&wgpu::Buffer> {
+		self.colors.as_ref()
+	}
+
+	// Number of the instances to draw
+	pub fn get_count(&self) -> u32 {
+		self.count
+	}
+}
+
+// Per node and instanced mesh because the culling depends on
+// the node world matrix
+pub struct WGPUInstances {
+	buffers: HashMap<(ResourceId<Node>, ResourceId<InstancedMesh>), WGPUInstanceBuffers>,
+}
+
+impl Default for WGPUInstances {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl WGPUInstances {
+	pub fn new() -> Self {
+		WGPUInstances {
+			buffers: HashMap::new(),
+		}
+	}
+
+	pub fn borrow(
+		&self,
+		node: &ResourceId<Node>,
+		instanced_mesh: &ResourceId<InstancedMesh>,
+	) -> Option<&WGPUInstanceBuffers> {
+		self.buffers.get(&(*node, *instanced_mesh))
+	}
+
+	// Uploads the data of the visible instances, packed from the start,
+	// unless neither the instanced mesh nor the visible instances changed
+	// since the last upload. The buffers are recreated if they are too
+	// small or the instanced mesh got colors.
+	pub fn update(
+		&mut self,
+		device: &wgpu::Device,
+		queue: &wgpu::Queue,
+		node_rid: &ResourceId<Node>,
+		instanced_mesh_rid: &ResourceId<InstancedMesh>,
+		instanced_mesh: &InstancedMesh,
+		visible_instances: &[usize],
+	) {
+		let key = (*node_rid, *instanced_mesh_rid);
+		let capacity = instanced_mesh.get_count().max(1);
+		let has_colors = instanced_mesh.borrow_colors().is_some();
+
+		let recreate = match self.buffers.get(&key) {
+			Some(buffers) => buffers.capacity < capacity || buffers.colors.is_some() != has_colors,
+			None => true,
+		};
+
+		if recreate {
+			self.buffers.insert(key, WGPUInstanceBuffers::new(device, capacity, has_colors));
+		}
+
+		let buffers = self.buffers.get_mut(&key).unwrap();
+		buffers.count = visible_instances.len() as u32;
+
+		if visible_instances.is_empty() {
+			return;
+		}
+
+		if let Some((version, instances)) = &buffers.written {
+			if *version == instanced_mesh.get_version() && instances == visible_instances {
+				return;
+			}
+		}
+		buffers.written = Some((instanced_mesh.get_version(), visible_instances.to_vec()));
+
+		let matrices = instanced_mesh.borrow_matrices();
+		let mut data = Vec::with_capacity(visible_instances.len() * 16);
+		for index in visible_instances.iter() {
+			data.extend_from_slice(&matrices[index * 16..index * 16 + 16]);
+		}
+		queue.write_buffer(&buffers.matrices, 0, bytemuck::cast_slice(&data));
+
+		if let (Some(colors), Some(buffer)) = (instanced_mesh.borrow_colors(), &buffers.colors) {
+			let mut data = Vec::with_capacity(visible_instances.len() * 3);
+			for index in visible_instances.iter() {
+				data.extend_from_slice(&colors[index * 3..index * 3 + 3]);
+			}
+			queue.write_buffer(buffer, 0, bytemuck::cast_slice(&data));
+		}
+	}
+}
+
+fn create_buffer(device: &wgpu::Device, size_in_byte: usize) -> wgpu::Buffer {
+	device.create_buffer(&wgpu::BufferDescriptor {
+		label: None,
+		mapped_at_creation: false,
+		size: size_in_byte as u64,
+		usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+	})
+}
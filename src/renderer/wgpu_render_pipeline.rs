@@ -2,11 +2,13 @@ use std::borrow::Cow;
 use std::collections::HashMap;
 
 use crate::{
+	geometry::geometry::PrimitiveTopology,
 	material::{
 		material::{
 			Material,
 			Side,
 		},
+		error::MaterialError,
 		node::node::MaterialNode,
 	},
 	resource::resource::{
@@ -16,17 +18,34 @@ use crate::{
 	scene::node::Node,
 };
 
+// Per-instance vertex inputs of instanced meshes
+pub struct WGPUInstancing {
+	pub has_color: bool,
+}
+
 pub struct WGPURenderPipeline {
-	pipeline: wgpu::RenderPipeline
+	// The geometry properties baked into the pipeline, the pipeline is
+	// rebuilt if they change
+	color_format: Option<wgpu::VertexFormat>,
+	has_tangent: bool,
+	// None if not instanced
+	instance_has_color: Option<bool>,
+	pipeline: wgpu::RenderPipeline,
+	topology: PrimitiveTopology,
 }
 
 impl WGPURenderPipeline {
+	#[allow(clippy::too_many_arguments)]
 	fn new(
 		device: &wgpu::Device,
 		bind_group_layout: &wgpu::BindGroupLayout,
 		shader_code: &str,
 		sample_count: u32,
 		side: &Side,
+		color_format: Option<wgpu::VertexFormat>,
+		has_tangent: bool,
+		topology: &PrimitiveTopology,
+		instancing: Option<&WGPUInstancing>,
 	) -> Self {
 		// For debug
 		//println!("{}", shader_code);
@@ -42,8 +61,19 @@ impl WGPURenderPipeline {
 			push_constant_ranges: &[],
 		});
 
+		let color_attributes = [
+			wgpu::VertexAttribute {
+				format: match color_format {
+					Some(format) => format,
+					None => wgpu::VertexFormat::Float32x4,
+				},
+				offset: 0,
+				shader_location: 3,
+			},
+		];
+
 		// @TODO: Programmable
-		let vertex_buffers = [
+		let mut vertex_buffers = vec![
 			// position
 			wgpu::VertexBufferLayout {
 				array_stride: 3 * 4,
@@ -80,8 +110,89 @@ impl WGPURenderPipeline {
 					},
 				],
 			},
+			// color, falls back to a single white color if the geometry
+			// doesn't have colors
+			match color_format {
+				Some(format) => wgpu::VertexBufferLayout {
+					array_stride: format.size(),
+					step_mode: wgpu::VertexStepMode::Vertex,
+					attributes: &color_attributes,
+				},
+				None => wgpu::VertexBufferLayout {
+					array_stride: 4 * 4,
+					step_mode: wgpu::VertexStepMode::Instance,
+					attributes: &color_attributes,
+				},
+			},
+			// tangent, falls back to a single zero tangent
+			wgpu::VertexBufferLayout {
+				array_stride: 4 * 4,
+				step_mode: match has_tangent {
+					true => wgpu::VertexStepMode::Vertex,
+					false => wgpu::VertexStepMode::Instance,
+				},
+				attributes: &[
+					wgpu::VertexAttribute {
+						format: wgpu::VertexFormat::Float32x4,
+						offset: 0,
+						shader_location: 4,
+					},
+				],
+			},
+		];
+
+		let instance_matrix_attributes = [
+			wgpu::VertexAttribute {
+				format: wgpu::VertexFormat::Float32x4,
+				offset: 0,
+				shader_location: 5,
+			},
+			wgpu::VertexAttribute {
+				format: wgpu::VertexFormat::Float32x4,
+				offset: 4 * 4,
+				shader_location: 6,
+			},
+			wgpu::VertexAttribute {
+				format: wgpu::VertexFormat::Float32x4,
+				offset: 8 * 4,
+				shader_location: 7,
+			},
+			wgpu::VertexAttribute {
+				format: wgpu::VertexFormat::Float32x4,
+				offset: 12 * 4,
+				shader_location: 8,
+			},
+		];
+
+		let instance_color_attributes = [
+			wgpu::VertexAttribute {
+				format: match instancing {
+					Some(instancing) if instancing.has_color => wgpu::VertexFormat::Float32x3,
+					_ => wgpu::VertexFormat::Float32x4,
+				},
+				offset: 0,
+				shader_location: 9,
+			},
 		];
 
+		if let Some(instancing) = instancing {
+			// instance matrix
+			vertex_buffers.push(wgpu::VertexBufferLayout {
+				array_stride: 16 * 4,
+				step_mode: wgpu::VertexStepMode::Instance,
+				attributes: &instance_matrix_attributes,
+			});
+			// instance color, falls back to the default white colors
+			vertex_buffers.push(wgpu::VertexBufferLayout {
+				array_stride: match instancing.has_color {
+					true => 3 * 4,
+					false => 4 * 4,
+				},
+				step_mode: wgpu::VertexStepMode::Instance,
+				attributes: &instance_color_attributes,
+			});
+		}
+
 		let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
 			label: None,
 			layout: Some(&layout),
@@ -109,6 +220,11 @@ impl WGPURenderPipeline {
 					Side::DoubleSide |
 					Side::FrontSide => wgpu::FrontFace::Ccw,
 				},
+				topology: match topology {
+					PrimitiveTopology::LineList => wgpu::PrimitiveTopology::LineList,
+					PrimitiveTopology::PointList => wgpu::PrimitiveTopology::PointList,
+					PrimitiveTopology::TriangleList => wgpu::PrimitiveTopology::TriangleList,
+				},
 				..Default::default()
 			},
 			depth_stencil: Some(wgpu::DepthStencilState {
@@ -125,52 +241,99 @@ impl WGPURenderPipeline {
 		});
 
 		WGPURenderPipeline {
-			pipeline: pipeline
+			color_format,
+			has_tangent,
+			instance_has_color: instancing.map(|instancing| instancing.has_color),
+			pipeline,
+			topology: *topology,
 		}
 	}
 }
 
-pub struct WGPURenderPipelines {
-	pipelines: HashMap::<ResourceId<Node>, WGPURenderPipeline>
+pub struct WGPURenderPipelines<T> {
+	// Per node, mesh and mesh material index because a node can have
+	// multiple meshes and a mesh can have multiple materials.
+	// T is Mesh or InstancedMesh.
+	pipelines: HashMap::<(ResourceId<Node>, ResourceId<T>, usize), WGPURenderPipeline>
 }
 
-impl WGPURenderPipelines {
+impl<T> WGPURenderPipelines<T> {
 	pub fn new() -> Self {
 		WGPURenderPipelines {
 			pipelines: HashMap::new()
 		}
 	}
 
-	pub fn borrow(&self, node: &ResourceId<Node>) -> Option<&wgpu::RenderPipeline> {
-		if let Some(pipeline) = &self.pipelines.get(node) {
+	pub fn borrow(
+		&self,
+		node: &ResourceId<Node>,
+		mesh: &ResourceId<T>,
+		material_index: usize,
+	) -> Option<&wgpu::RenderPipeline> {
+		if let Some(pipeline) = &self.pipelines.get(&(*node, *mesh, material_index)) {
 			Some(&pipeline.pipeline)
 		} else {
 			None
 		}
 	}
 
+	#[allow(clippy::too_many_arguments)]
 	pub fn update(
 		&mut self,
 		device: &wgpu::Device,
 		pools: &ResourcePools,
 		node: &ResourceId<Node>,
+		mesh: &ResourceId<T>,
+		material_index: usize,
 		material: &Material,
 		bind_group_layout: &wgpu::BindGroupLayout,
 		sample_count: u32,
-	) {
-		if !self.pipelines.contains_key(&node) {
+		color_format: Option<wgpu::VertexFormat>,
+		has_tangent: bool,
+		topology: &PrimitiveTopology,
+		instancing: Option<&WGPUInstancing>,
+	) -> Result<(), MaterialError> {
+		let key = (*node, *mesh, material_index);
+		let outdated = match self.pipelines.get(&key) {
+			Some(pipeline) => pipeline.color_format != color_format ||
+				pipeline.has_tangent != has_tangent ||
+				pipeline.instance_has_color != instancing.map(|instancing| instancing.has_color) ||
+				pipeline.topology != *topology,
+			None => true,
+		};
+
+		if outdated {
+			// Report mistakes in the material node graph with the node and
+			// the line rather than letting wgpu fail to create the pipeline.
+			// The outdated pipeline is removed not to be drawn with.
+			if let Err(error) = material.validate(pools.borrow::<Box<dyn MaterialNode>>()) {
+				self.pipelines.remove(&key);
+				return Err(error);
+			}
+
 			self.pipelines.insert(
-				*node,
+				key,
 				WGPURenderPipeline::new(
 					device,
 					bind_group_layout,
-					&material.build_shader_code(
-						pools.borrow::<Box<dyn MaterialNode>>(),
-					),
+					&match instancing {
+						Some(_) => material.build_instanced_shader_code(
+							pools.borrow::<Box<dyn MaterialNode>>(),
+						),
+						None => material.build_shader_code(
+							pools.borrow::<Box<dyn MaterialNode>>(),
+						),
+					},
 					sample_count,
 					material.borrow_side(),
+					color_format,
+					has_tangent,
+					topology,
+					instancing,
 				)
 			);
 		}
+
+		Ok(())
 	}
 }
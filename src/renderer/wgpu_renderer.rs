@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use winit::window::Window;
 
 use crate::{
@@ -6,12 +7,31 @@ use crate::{
 		geometry::Geometry,
 		index::Index,
 	},
-	material::material::Material,
+	material::{
+		error::MaterialError,
+		material::Material,
+	},
+	math::{
+		frustum::Frustum,
+		matrix4::Matrix4,
+		sphere::Sphere,
+		vector3::Vector3,
+	},
 	renderer::{
-		wgpu_attributes::WGPUAttributes,
-		wgpu_bindings::WGPUBindings,
+		wgpu_attributes::{
+			WGPUAttributes,
+			get_vertex_format,
+		},
+		wgpu_bindings::{
+			WGPUBindings,
+			WGPUGlobals,
+		},
 		wgpu_indices::WGPUIndices,
-		wgpu_render_pipeline::WGPURenderPipelines,
+		wgpu_instances::WGPUInstances,
+		wgpu_render_pipeline::{
+			WGPUInstancing,
+			WGPURenderPipelines,
+		},
 		wgpu_samplers::WGPUSamplers,
 		wgpu_textures::WGPUTextures,
 	},
@@ -20,11 +40,16 @@ use crate::{
 		ResourcePools,
 	},
 	scene::{
-		camera::PerspectiveCamera,
+		camera::Camera,
+		instanced_mesh::InstancedMesh,
 		mesh::Mesh,
 		node::Node,
 		scene::Scene,
 	},
+	utils::{
+		clock::Clock,
+		geometry_processor::GeometryProcessor,
+	},
 };
 
 pub struct WGPURendererOptions {
@@ -41,20 +66,33 @@ impl Default for WGPURendererOptions {
 
 pub struct WGPURenderer {
 	attributes: WGPUAttributes,
-	bindings: WGPUBindings,
+	bindings: WGPUBindings<Mesh>,
+	// Keyed by position attribute because attributes are replaced
+	// rather than edited in place
+	bounding_spheres: HashMap<ResourceId<Attribute>, [f32; 4]>,
+	clock: Clock,
 	color_buffer: wgpu::Texture,
 	device: wgpu::Device,
 	depth_buffer: wgpu::Texture,
 	height: f64,
 	indices: WGPUIndices,
+	instanced_bindings: WGPUBindings<InstancedMesh>,
+	instanced_render_pipelines: WGPURenderPipelines<InstancedMesh>,
+	instances: WGPUInstances,
 	pixel_ratio: f64,
 	queue: wgpu::Queue,
-	render_pipelines: WGPURenderPipelines,
+	render_pipelines: WGPURenderPipelines<Mesh>,
 	sample_count: u32,
 	samplers: WGPUSamplers,
 	surface: wgpu::Surface,
 	surface_configuration: wgpu::SurfaceConfiguration,
 	textures: WGPUTextures,
+	// Node and instanced mesh pairs having any instance passed the
+	// frustum culling in update(), drawn in render_internal()
+	visible_instanced_meshes: Vec<(ResourceId<Node>, ResourceId<InstancedMesh>)>,
+	// Node and mesh pairs passed the frustum culling in update(),
+	// drawn in render_internal()
+	visible_meshes: Vec<(ResourceId<Node>, ResourceId<Mesh>)>,
 	width: f64,
 }
 
@@ -99,8 +137,10 @@ impl WGPURenderer {
 		surface.configure(&device, &surface_configuration);
 
 		WGPURenderer {
-			attributes: WGPUAttributes::new(),
+			attributes: WGPUAttributes::new(&device),
 			bindings: WGPUBindings::new(),
+			bounding_spheres: HashMap::new(),
+			clock: Clock::new(),
 			color_buffer: create_color_buffer(
 				&device,
 				width,
@@ -118,6 +158,9 @@ impl WGPURenderer {
 			device: device,
 			height: height,
 			indices: WGPUIndices::new(),
+			instanced_bindings: WGPUBindings::new(),
+			instanced_render_pipelines: WGPURenderPipelines::new(),
+			instances: WGPUInstances::new(),
 			pixel_ratio: pixel_ratio,
 			queue: queue,
 			render_pipelines: WGPURenderPipelines::new(),
@@ -126,6 +169,8 @@ impl WGPURenderer {
 			surface: surface,
 			surface_configuration: surface_configuration,
 			textures: WGPUTextures::new(),
+			visible_instanced_meshes: Vec::new(),
+			visible_meshes: Vec::new(),
 			width: width
 		}
 	}
@@ -154,107 +199,327 @@ impl WGPURenderer {
 		self.pixel_ratio
 	}
 
-	fn update(
+	fn update<T: Camera + 'static>(
 		&mut self,
 		pools: &ResourcePools,
 		scene_rid: &ResourceId<Scene>,
-		camera_rid: &ResourceId<PerspectiveCamera>,
-	) {
+		camera_rid: &ResourceId<T>,
+	) -> Result<(), MaterialError> {
+		let attribute_pool = pools.borrow::<Attribute>();
 		let geometry_pool = pools.borrow::<Geometry>();
+		let instanced_mesh_pool = pools.borrow::<InstancedMesh>();
 		let mesh_pool = pools.borrow::<Mesh>();
 		let material_pool = pools.borrow::<Material>();
 		let node_pool = pools.borrow::<Node>();
 
 		// @TODO: Error handling
 
+		// The meshes with invalid materials are skipped and
+		// the first error is returned after the others are updated
+		let mut result = Ok(());
+
 		let scene = match pools.borrow::<Scene>().borrow(scene_rid) {
 			Some(scene) => scene,
-			None => return,
+			None => return Ok(()),
 		};
 
-		let camera = match pools.borrow::<PerspectiveCamera>().borrow(camera_rid) {
+		let camera = match pools.borrow::<T>().borrow(camera_rid) {
 			Some(camera) => camera,
-			None => return,
+			None => return Ok(()),
 		};
 
-		let camera_node = match scene.borrow_assigned_from::<PerspectiveCamera>(camera_rid) {
-			Some(rid) => match node_pool.borrow(rid) {
-				Some(node) => node,
-				None => return,
-			},
-			None => return,
+		let camera_node_rid = match scene.borrow_assigned_from::<T>(camera_rid) {
+			Some(rid) => *rid,
+			None => return Ok(()),
+		};
+
+		let camera_node = match node_pool.borrow(&camera_node_rid) {
+			Some(node) => node,
+			None => return Ok(()),
+		};
+
+		let camera_matrix = camera_node.borrow_world_matrix();
+
+		let mut frustum = Frustum::create();
+		{
+			let mut view_matrix = *camera_matrix;
+			Matrix4::invert(&mut view_matrix);
+			let mut matrix = Matrix4::create();
+			Matrix4::multiply(&mut matrix, camera.borrow_projection_matrix(), &view_matrix);
+			Frustum::set_from_projection_matrix(&mut frustum, &matrix);
+		}
+
+		self.visible_instanced_meshes.clear();
+		self.visible_meshes.clear();
+
+		// The camera looks at its -Z axis
+		let mut camera_direction = [-camera_matrix[8], -camera_matrix[9], -camera_matrix[10]];
+		Vector3::normalize(&mut camera_direction);
+
+		let globals = WGPUGlobals {
+			camera_direction,
+			camera_position: [camera_matrix[12], camera_matrix[13], camera_matrix[14]],
+			delta_time: self.clock.get_delta() as f32,
+			resolution: [
+				(self.width * self.pixel_ratio) as f32,
+				(self.height * self.pixel_ratio) as f32,
+			],
+			time: self.clock.get_elapsed_time() as f32,
 		};
 
-		for node_rid in scene.collect_nodes(pools).iter() {
-			let mesh = match scene.borrow_assigned_to::<Mesh>(node_rid) {
-				Some(rid) => match mesh_pool.borrow(rid) {
+		let camera_layers = camera.get_layers();
+
+		for node_rid in scene.collect_visible_nodes(pools).iter() {
+			if node_pool.borrow(node_rid).unwrap().get_layers() & camera_layers == 0 {
+				continue;
+			}
+
+			for mesh_rid in scene.borrow_all_assigned_to::<Mesh>(node_rid).iter() {
+				let mesh = match mesh_pool.borrow(mesh_rid) {
 					Some(mesh) => mesh,
 					None => continue,
-				},
-				None => continue,
-			};
-
-			let geometry = match geometry_pool.borrow(mesh.borrow_geometry()) {
-				Some(geometry) => geometry,
-				None => continue,
-			};
-
-			let material = match material_pool.borrow(mesh.borrow_material()) {
-				Some(material) => material,
-				None => continue,
-			};
-
-			// @TODO: Implement correctly
-			if let Some(rid) = geometry.borrow_attribute("position") {
-				self.attributes.update(&self.device, pools, rid);
-			}
-			if let Some(rid) = geometry.borrow_attribute("normal") {
-				self.attributes.update(&self.device, pools, rid);
-			}
-			if let Some(rid) = geometry.borrow_attribute("uv") {
-				self.attributes.update(&self.device, pools, rid);
+				};
+
+				let geometry = match geometry_pool.borrow(mesh.borrow_geometry()) {
+					Some(geometry) => geometry,
+					None => continue,
+				};
+
+				let mut materials = Vec::new();
+				for rid in mesh.borrow_materials().iter() {
+					match material_pool.borrow(rid) {
+						Some(material) => materials.push(material),
+						None => break,
+					};
+				}
+
+				if materials.len() != mesh.borrow_materials().len() {
+					continue;
+				}
+
+				// The bounding sphere doesn't cover vertices moved in the shader
+				if mesh.is_frustum_culled() &&
+					materials.iter().all(|material| material.borrow_position_offset().is_none()) {
+					if let Some(mut sphere) = self.get_bounding_sphere(pools, mesh.borrow_geometry()) {
+						let node = node_pool.borrow(node_rid).unwrap();
+						Sphere::apply_matrix4(&mut sphere, node.borrow_world_matrix());
+						if !Frustum::intersects_sphere(&frustum, &sphere) {
+							continue;
+						}
+					}
+				}
+
+				self.visible_meshes.push((*node_rid, *mesh_rid));
+
+				self.update_geometry(pools, geometry);
+
+				for (material_index, material) in materials.iter().enumerate() {
+					self.update_material(pools, material);
+
+					self.bindings.update(
+						&self.device,
+						&self.queue,
+						&self.textures,
+						&self.samplers,
+						pools,
+						node_rid,
+						mesh_rid,
+						material_index,
+						camera,
+						&camera_node_rid,
+						camera_node,
+						&globals,
+						material,
+					);
+
+					if let Err(error) = self.render_pipelines.update(
+						&self.device,
+						pools,
+						node_rid,
+						mesh_rid,
+						material_index,
+						material,
+						self.bindings.borrow(node_rid, mesh_rid, material_index).unwrap().borrow_layout(),
+						self.sample_count,
+						match geometry.borrow_attribute("color") {
+							Some(rid) => attribute_pool.borrow(rid).map(get_vertex_format),
+							None => None,
+						},
+						geometry.borrow_attribute("tangent").is_some(),
+						geometry.borrow_topology(),
+						None,
+					) {
+						if result.is_ok() {
+							result = Err(error);
+						}
+					}
+				}
 			}
 
-			if let Some(rid) = geometry.borrow_index() {
-				self.indices.update(&self.device, pools, rid);
+			for instanced_mesh_rid in scene.borrow_all_assigned_to::<InstancedMesh>(node_rid).iter() {
+				let instanced_mesh = match instanced_mesh_pool.borrow(instanced_mesh_rid) {
+					Some(instanced_mesh) => instanced_mesh,
+					None => continue,
+				};
+
+				let geometry = match geometry_pool.borrow(instanced_mesh.borrow_geometry()) {
+					Some(geometry) => geometry,
+					None => continue,
+				};
+
+				let material = match material_pool.borrow(instanced_mesh.borrow_material()) {
+					Some(material) => material,
+					None => continue,
+				};
+
+				let count = instanced_mesh.get_count();
+				let node = node_pool.borrow(node_rid).unwrap();
+
+				// Culled per instance with the bounding sphere moved by the instance matrix
+				let sphere = match instanced_mesh.is_frustum_culled() && material.borrow_position_offset().is_none() {
+					true => self.get_bounding_sphere(pools, instanced_mesh.borrow_geometry()),
+					false => None,
+				};
+
+				let visible_instances = match sphere {
+					Some(sphere) => {
+						let mut matrix = Matrix4::create();
+						let mut instance_sphere = Sphere::create();
+						(0..count).filter(|index| {
+							Matrix4::multiply(&mut matrix, node.borrow_world_matrix(), &instanced_mesh.get_matrix_at(*index));
+							Sphere::copy(&mut instance_sphere, &sphere);
+							Sphere::apply_matrix4(&mut instance_sphere, &matrix);
+							Frustum::intersects_sphere(&frustum, &instance_sphere)
+						}).collect::<Vec<usize>>()
+					},
+					None => (0..count).collect::<Vec<usize>>(),
+				};
+
+				if visible_instances.is_empty() {
+					continue;
+				}
+
+				self.visible_instanced_meshes.push((*node_rid, *instanced_mesh_rid));
+
+				self.update_geometry(pools, geometry);
+				self.update_material(pools, material);
+				self.attributes.reserve_defaults(&self.device, count);
+
+				self.instances.update(
+					&self.device,
+					&self.queue,
+					node_rid,
+					instanced_mesh_rid,
+					instanced_mesh,
+					&visible_instances,
+				);
+
+				self.instanced_bindings.update(
+					&self.device,
+					&self.queue,
+					&self.textures,
+					&self.samplers,
+					pools,
+					node_rid,
+					instanced_mesh_rid,
+					0,
+					camera,
+					&camera_node_rid,
+					camera_node,
+					&globals,
+					material,
+				);
+
+				if let Err(error) = self.instanced_render_pipelines.update(
+					&self.device,
+					pools,
+					node_rid,
+					instanced_mesh_rid,
+					0,
+					material,
+					self.instanced_bindings.borrow(node_rid, instanced_mesh_rid, 0).unwrap().borrow_layout(),
+					self.sample_count,
+					match geometry.borrow_attribute("color") {
+						Some(rid) => attribute_pool.borrow(rid).map(get_vertex_format),
+						None => None,
+					},
+					geometry.borrow_attribute("tangent").is_some(),
+					geometry.borrow_topology(),
+					Some(&WGPUInstancing {
+						has_color: instanced_mesh.borrow_colors().is_some(),
+					}),
+				) {
+					if result.is_ok() {
+						result = Err(error);
+					}
+				}
 			}
+		}
+
+		result
+	}
+
+	// @TODO: Implement correctly
+	fn update_geometry(
+		&mut self,
+		pools: &ResourcePools,
+		geometry: &Geometry,
+	) {
+		if let Some(rid) = geometry.borrow_attribute("position") {
+			self.attributes.update(&self.device, pools, rid);
+		}
+		if let Some(rid) = geometry.borrow_attribute("normal") {
+			self.attributes.update(&self.device, pools, rid);
+		}
+		if let Some(rid) = geometry.borrow_attribute("uv") {
+			self.attributes.update(&self.device, pools, rid);
+		}
+		if let Some(rid) = geometry.borrow_attribute("color") {
+			self.attributes.update(&self.device, pools, rid);
+		}
+		if let Some(rid) = geometry.borrow_attribute("tangent") {
+			self.attributes.update(&self.device, pools, rid);
+		}
 
-			self.textures.update_from_material(
-				&self.device,
-				&self.queue,
-				pools,
-				material,
-			);
-
-			self.samplers.update_from_material(
-				&self.device,
-				pools,
-				material,
-			);
-
-			self.bindings.update(
-				&self.device,
-				&self.queue,
-				&self.textures,
-				&self.samplers,
-				pools,
-				node_rid,
-				camera,
-				camera_node,
-				material,
-			);
-
-			self.render_pipelines.update(
-				&self.device,
-				pools,
-				node_rid,
-				material,
-				&self.bindings.borrow(node_rid).unwrap().borrow_layout(),
-				self.sample_count,
-			);
+		if let Some(rid) = geometry.borrow_index() {
+			self.indices.update(&self.device, pools, rid);
 		}
 	}
 
+	fn update_material(
+		&mut self,
+		pools: &ResourcePools,
+		material: &Material,
+	) {
+		self.textures.update_from_material(
+			&self.device,
+			&self.queue,
+			pools,
+			material,
+		);
+
+		self.samplers.update_from_material(
+			&self.device,
+			pools,
+			material,
+		);
+	}
+
+	// In the geometry local space. None if the geometry has no positions.
+	fn get_bounding_sphere(
+		&mut self,
+		pools: &ResourcePools,
+		geometry_rid: &ResourceId<Geometry>,
+	) -> Option<[f32; 4]> {
+		let position_rid = *pools.borrow::<Geometry>().borrow(geometry_rid)?.borrow_attribute("position")?;
+		if let Some(sphere) = self.bounding_spheres.get(&position_rid) {
+			return Some(*sphere);
+		}
+		let sphere = GeometryProcessor::compute_bounding_sphere(pools, geometry_rid)?;
+		self.bounding_spheres.insert(position_rid, sphere);
+		Some(sphere)
+	}
+
 	fn render_internal(
 		&self,
 		pools: &ResourcePools,
@@ -263,6 +528,7 @@ impl WGPURenderer {
 		let attribute_pool = pools.borrow::<Attribute>();
 		let geometry_pool = pools.borrow::<Geometry>();
 		let index_pool = pools.borrow::<Index>();
+		let instanced_mesh_pool = pools.borrow::<InstancedMesh>();
 		let mesh_pool = pools.borrow::<Mesh>();
 
 		// @TODO: Error handling
@@ -320,12 +586,9 @@ impl WGPURenderer {
 				}),
 			});
 
-			for node_rid in scene.collect_nodes(pools).iter() {
-				let mesh = match scene.borrow_assigned_to::<Mesh>(node_rid) {
-					Some(rid) => match mesh_pool.borrow(rid) {
-						Some(mesh) => mesh,
-						None => continue,
-					},
+			for (node_rid, mesh_rid) in self.visible_meshes.iter() {
+				let mesh = match mesh_pool.borrow(mesh_rid) {
+					Some(mesh) => mesh,
 					None => continue,
 				};
 
@@ -334,45 +597,125 @@ impl WGPURenderer {
 					None => continue,
 				};
 
-				if let Some(pipeline) = self.render_pipelines.borrow(node_rid) {
-					pass.set_pipeline(&pipeline);
-				} else {
-					continue;
-				}
+				self.set_vertex_buffers(&mut pass, geometry);
+
+				let index_count = match geometry.borrow_index() {
+					Some(rid) => match (index_pool.borrow(rid), self.indices.borrow(rid)) {
+						(Some(indices), Some(buffer)) => {
+							pass.set_index_buffer(buffer.slice(..), wgpu::IndexFormat::Uint16);
+							Some(indices.get_count())
+						},
+						_ => continue,
+					},
+					None => None,
+				};
+
+				let count = match index_count {
+					Some(count) => count,
+					None => match geometry.borrow_attribute("position").and_then(|rid| attribute_pool.borrow(rid)) {
+						Some(positions) => positions.get_count(),
+						None => continue,
+					},
+				};
+
+				// A draw call per group, or the whole geometry with the first material
+				let ranges = match geometry.borrow_groups().is_empty() {
+					true => vec![(0, count, 0)],
+					false => geometry.borrow_groups().iter().map(|group| {
+						let start = group.start.min(count);
+						(start, (group.start + group.count).min(count), group.material_index)
+					}).collect::<Vec<(u32, u32, usize)>>(),
+				};
 
-				// @TODO: Should be programmable
-				if let Some(rid) = geometry.borrow_attribute("position") {
-					if let Some(buffer) = self.attributes.borrow(rid) {
-						pass.set_vertex_buffer(0, buffer.slice(..));
+				for (start, end, material_index) in ranges {
+					if start >= end {
+						continue;
 					}
-				}
-				if let Some(rid) = geometry.borrow_attribute("normal") {
-					if let Some(buffer) = self.attributes.borrow(rid) {
-						pass.set_vertex_buffer(1, buffer.slice(..));
+
+					if let Some(pipeline) = self.render_pipelines.borrow(node_rid, mesh_rid, material_index) {
+						pass.set_pipeline(pipeline);
+					} else {
+						continue;
 					}
+
+					let binding = self.bindings.borrow(node_rid, mesh_rid, material_index).unwrap();
+					pass.set_bind_group(0, binding.borrow_group(), &[]);
+
+					match index_count {
+						Some(_) => pass.draw_indexed(start..end, 0, 0..1),
+						None => pass.draw(start..end, 0..1),
+					};
 				}
-				if let Some(rid) = geometry.borrow_attribute("uv") {
-					if let Some(buffer) = self.attributes.borrow(rid) {
-						pass.set_vertex_buffer(2, buffer.slice(..));
-					}
+			}
+
+			for (node_rid, instanced_mesh_rid) in self.visible_instanced_meshes.iter() {
+				let instanced_mesh = match instanced_mesh_pool.borrow(instanced_mesh_rid) {
+					Some(instanced_mesh) => instanced_mesh,
+					None => continue,
+				};
+
+				let geometry = match geometry_pool.borrow(instanced_mesh.borrow_geometry()) {
+					Some(geometry) => geometry,
+					None => continue,
+				};
+
+				let instances = match self.instances.borrow(node_rid, instanced_mesh_rid) {
+					Some(instances) => instances,
+					None => continue,
+				};
+
+				if let Some(pipeline) = self.instanced_render_pipelines.borrow(node_rid, instanced_mesh_rid, 0) {
+					pass.set_pipeline(pipeline);
+				} else {
+					continue;
 				}
 
-				let binding = self.bindings.borrow(node_rid).unwrap();
-				pass.set_bind_group(0, &binding.borrow_group(), &[]);
+				self.set_vertex_buffers(&mut pass, geometry);
+				pass.set_vertex_buffer(5, instances.borrow_matrices().slice(..));
+				match instances.borrow_colors() {
+					Some(buffer) => pass.set_vertex_buffer(6, buffer.slice(..)),
+					None => pass.set_vertex_buffer(6, self.attributes.borrow_default_color().slice(..)),
+				};
+
+				let binding = self.instanced_bindings.borrow(node_rid, instanced_mesh_rid, 0).unwrap();
+				pass.set_bind_group(0, binding.borrow_group(), &[]);
 
-				if let Some(rid) = geometry.borrow_index() {
-					if let Some(indices) = index_pool.borrow(rid) {
-						if let Some(buffer) = self.indices.borrow(rid) {
+				let index_count = match geometry.borrow_index() {
+					Some(rid) => match (index_pool.borrow(rid), self.indices.borrow(rid)) {
+						(Some(indices), Some(buffer)) => {
 							pass.set_index_buffer(buffer.slice(..), wgpu::IndexFormat::Uint16);
-							pass.draw_indexed(0..indices.get_count(), 0, 0..1);
-						}
-					}
-				} else {
-					if let Some(rid) = geometry.borrow_attribute("position") {
-						if let Some(positions) = attribute_pool.borrow(rid) {
-							pass.draw(0..positions.get_count(), 0..1);
-						}
+							Some(indices.get_count())
+						},
+						_ => continue,
+					},
+					None => None,
+				};
+
+				let count = match index_count {
+					Some(count) => count,
+					None => match geometry.borrow_attribute("position").and_then(|rid| attribute_pool.borrow(rid)) {
+						Some(positions) => positions.get_count(),
+						None => continue,
+					},
+				};
+
+				// A draw call per group with the single material, or the whole geometry
+				let ranges = match geometry.borrow_groups().is_empty() {
+					true => vec![(0, count)],
+					false => geometry.borrow_groups().iter().map(|group| {
+						(group.start.min(count), (group.start + group.count).min(count))
+					}).collect::<Vec<(u32, u32)>>(),
+				};
+
+				for (start, end) in ranges {
+					if start >= end {
+						continue;
 					}
+
+					match index_count {
+						Some(_) => pass.draw_indexed(start..end, 0, 0..instances.get_count()),
+						None => pass.draw(start..end, 0..instances.get_count()),
+					};
 				}
 			}
 		}
@@ -381,14 +724,53 @@ impl WGPURenderer {
 		frame.present()
 	}
 
-	pub fn render(
+	// @TODO: Should be programmable
+	fn set_vertex_buffers<'a>(
+		&'a self,
+		pass: &mut wgpu::RenderPass<'a>,
+		geometry: &Geometry,
+	) {
+		if let Some(rid) = geometry.borrow_attribute("position") {
+			if let Some(buffer) = self.attributes.borrow(rid) {
+				pass.set_vertex_buffer(0, buffer.slice(..));
+			}
+		}
+		if let Some(rid) = geometry.borrow_attribute("normal") {
+			if let Some(buffer) = self.attributes.borrow(rid) {
+				pass.set_vertex_buffer(1, buffer.slice(..));
+			}
+		}
+		if let Some(rid) = geometry.borrow_attribute("uv") {
+			if let Some(buffer) = self.attributes.borrow(rid) {
+				pass.set_vertex_buffer(2, buffer.slice(..));
+			}
+		}
+		match geometry.borrow_attribute("color") {
+			Some(rid) => if let Some(buffer) = self.attributes.borrow(rid) {
+				pass.set_vertex_buffer(3, buffer.slice(..));
+			},
+			None => pass.set_vertex_buffer(3, self.attributes.borrow_default_color().slice(..)),
+		};
+		match geometry.borrow_attribute("tangent") {
+			Some(rid) => if let Some(buffer) = self.attributes.borrow(rid) {
+				pass.set_vertex_buffer(4, buffer.slice(..));
+			},
+			None => pass.set_vertex_buffer(4, self.attributes.borrow_default_tangent().slice(..)),
+		};
+	}
+
+	// Any camera type registered in ResourcePools and Scene links,
+	// PerspectiveCamera or OrthographicCamera. The meshes with invalid
+	// materials aren't drawn and the first material error is returned.
+	pub fn render<T: Camera + 'static>(
 		&mut self,
 		pools: &ResourcePools,
 		scene_rid: &ResourceId<Scene>,
-		camera_rid: &ResourceId<PerspectiveCamera>,
-	) {
-		self.update(pools, scene_rid, camera_rid);
+		camera_rid: &ResourceId<T>,
+	) -> Result<(), MaterialError> {
+		let result = self.update(pools, scene_rid, camera_rid);
 		self.render_internal(pools, scene_rid);
+		result
 	}
 
 	fn update_surface_configuration(&mut self) {
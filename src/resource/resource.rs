@@ -4,6 +4,7 @@ use std::{
 		TypeId,
 	},
 	collections::HashMap,
+	fmt,
 	hash::{
 		Hash,
 		Hasher,
@@ -23,7 +24,11 @@ use crate::{
 		node::node::MaterialNode,
 	},
 	scene::{
-		camera::PerspectiveCamera,
+		camera::{
+			OrthographicCamera,
+			PerspectiveCamera,
+		},
+		instanced_mesh::InstancedMesh,
 		mesh::Mesh,
 		node::Node,
 		scene::Scene,
@@ -114,10 +119,12 @@ impl ResourcePools {
 		Self::add::<Attribute>(&mut pools);
 		Self::add::<Geometry>(&mut pools);
 		Self::add::<Index>(&mut pools);
+		Self::add::<InstancedMesh>(&mut pools);
 		Self::add::<Material>(&mut pools);
 		Self::add::<Box<dyn MaterialNode>>(&mut pools);
 		Self::add::<Mesh>(&mut pools);
 		Self::add::<Node>(&mut pools);
+		Self::add::<OrthographicCamera>(&mut pools);
 		Self::add::<PerspectiveCamera>(&mut pools);
 		Self::add::<Scene>(&mut pools);
 		Self::add::<Sampler>(&mut pools);
@@ -186,6 +193,12 @@ impl<T> Clone for ResourceId<T> {
 	}
 }
 
+impl<T> fmt::Debug for ResourceId<T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "ResourceId({})", self.id)
+	}
+}
+
 impl<T> Hash for ResourceId<T> {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.id.hash(state);
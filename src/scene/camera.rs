@@ -1,9 +1,95 @@
-use crate::math::matrix4::Matrix4;
+use crate::{
+	math::{
+		matrix4::Matrix4,
+		ray::Ray,
+		vector3::Vector3,
+	},
+	scene::node::DEFAULT_LAYERS,
+};
+
+// Common interface of the camera types so that the renderer and
+// utilities can accept any of them.
+//
+// The conversion methods take the world matrix of the Node the camera is
+// assigned to. NDC x and y are in [-1.0, 1.0] with +Y up and z is the depth
+// in [0.0, 1.0]. Pixel coordinates have the origin at the top left.
+pub trait Camera {
+	fn borrow_projection_matrix(&self) -> &[f32; 16];
+	fn borrow_projection_matrix_inverse(&self) -> &[f32; 16];
+
+	// Bitmask of the layers the camera renders. Nodes sharing
+	// no layer with the camera aren't drawn.
+	fn get_layers(&self) -> u32;
+
+	fn is_perspective(&self) -> bool {
+		self.borrow_projection_matrix()[15] == 0.0
+	}
+
+	// World space to NDC
+	fn project<'a>(&self, v: &'a mut [f32; 3], world_matrix: &[f32; 16]) -> &'a mut [f32; 3] {
+		let mut view_matrix = *world_matrix;
+		Matrix4::invert(&mut view_matrix);
+		Vector3::apply_matrix4(v, &view_matrix);
+		Vector3::apply_matrix4(v, self.borrow_projection_matrix());
+		v
+	}
+
+	// NDC to world space
+	fn unproject<'a>(&self, v: &'a mut [f32; 3], world_matrix: &[f32; 16]) -> &'a mut [f32; 3] {
+		Vector3::apply_matrix4(v, self.borrow_projection_matrix_inverse());
+		Vector3::apply_matrix4(v, world_matrix);
+		v
+	}
+
+	// World space to pixel coordinates. The depth is ignored.
+	fn project_to_pixel(
+		&self,
+		v: &[f32; 3],
+		world_matrix: &[f32; 16],
+		width: f32,
+		height: f32,
+	) -> [f32; 2] {
+		let mut ndc = *v;
+		self.project(&mut ndc, world_matrix);
+		[
+			(ndc[0] + 1.0) * 0.5 * width,
+			(1.0 - ndc[1]) * 0.5 * height,
+		]
+	}
+
+	// World space ray through the pixel. The origin is the camera position
+	// for perspective cameras and on the near plane for orthographic ones.
+	fn get_ray_from_pixel<'a>(
+		&self,
+		ray: &'a mut [f32; 6],
+		x: f32,
+		y: f32,
+		width: f32,
+		height: f32,
+		world_matrix: &[f32; 16],
+	) -> &'a mut [f32; 6] {
+		let ndc_x = x / width * 2.0 - 1.0;
+		let ndc_y = 1.0 - y / height * 2.0;
+
+		let mut origin = [ndc_x, ndc_y, 0.0];
+		let mut target = [ndc_x, ndc_y, 1.0];
+		self.unproject(&mut origin, world_matrix);
+		self.unproject(&mut target, world_matrix);
+
+		if self.is_perspective() {
+			Vector3::set(&mut origin, world_matrix[12], world_matrix[13], world_matrix[14]);
+		}
+
+		Vector3::sub(&mut target, &origin);
+		Ray::set(ray, &origin, &target)
+	}
+}
 
 pub struct PerspectiveCamera {
 	aspect: f32,
 	far: f32,
 	fovy: f32,
+	layers: u32,
 	near: f32,
 	projection_matrix: [f32; 16],
 	projection_matrix_inverse: [f32; 16],
@@ -15,6 +101,7 @@ impl PerspectiveCamera {
 			aspect: aspect,
 			far: far,
 			fovy: fovy,
+			layers: DEFAULT_LAYERS,
 			near: near,
 			projection_matrix: Matrix4::create(),
 			projection_matrix_inverse: Matrix4::create(),
@@ -49,4 +136,155 @@ impl PerspectiveCamera {
 	pub fn borrow_projection_matrix_inverse(&self) -> &[f32; 16] {
 		&self.projection_matrix_inverse
 	}
-}
\ No newline at end of file
+
+	// Bit n is layer n
+	pub fn set_layers(&mut self, layers: u32) -> &mut Self {
+		self.layers = layers;
+		self
+	}
+
+	pub fn get_layers(&self) -> u32 {
+		self.layers
+	}
+
+	// Layer is 0 to 31
+	pub fn enable_layer(&mut self, layer: u32) -> &mut Self {
+		self.layers |= 1 << layer;
+		self
+	}
+
+	pub fn disable_layer(&mut self, layer: u32) -> &mut Self {
+		self.layers &= !(1 << layer);
+		self
+	}
+}
+
+impl Camera for PerspectiveCamera {
+	fn borrow_projection_matrix(&self) -> &[f32; 16] {
+		&self.projection_matrix
+	}
+
+	fn borrow_projection_matrix_inverse(&self) -> &[f32; 16] {
+		&self.projection_matrix_inverse
+	}
+
+	fn get_layers(&self) -> u32 {
+		self.layers
+	}
+}
+
+pub struct OrthographicCamera {
+	bottom: f32,
+	far: f32,
+	layers: u32,
+	left: f32,
+	near: f32,
+	projection_matrix: [f32; 16],
+	projection_matrix_inverse: [f32; 16],
+	right: f32,
+	top: f32,
+	zoom: f32,
+}
+
+impl OrthographicCamera {
+	pub fn new(left: f32, right: f32, top: f32, bottom: f32, near: f32, far: f32) -> Self {
+		let mut camera = OrthographicCamera {
+			bottom,
+			far,
+			layers: DEFAULT_LAYERS,
+			left,
+			near,
+			projection_matrix: Matrix4::create(),
+			projection_matrix_inverse: Matrix4::create(),
+			right,
+			top,
+			zoom: 1.0,
+		};
+		camera.update_projection_matrix();
+		camera
+	}
+
+	// Keeps the vertical extent and the center, and fits
+	// the horizontal extent to the aspect
+	pub fn set_aspect(&mut self, aspect: f32) -> &mut Self {
+		let center = (self.left + self.right) / 2.0;
+		let half_width = (self.top - self.bottom) / 2.0 * aspect;
+		self.left = center - half_width;
+		self.right = center + half_width;
+		self.update_projection_matrix();
+		self
+	}
+
+	pub fn set_zoom(&mut self, zoom: f32) -> &mut Self {
+		self.zoom = zoom;
+		self.update_projection_matrix();
+		self
+	}
+
+	pub fn get_zoom(&self) -> f32 {
+		self.zoom
+	}
+
+	// Zoom scales the view volume around its center
+	pub fn update_projection_matrix(&mut self) {
+		let center_x = (self.left + self.right) / 2.0;
+		let center_y = (self.top + self.bottom) / 2.0;
+		let half_width = (self.right - self.left) / (2.0 * self.zoom);
+		let half_height = (self.top - self.bottom) / (2.0 * self.zoom);
+		Matrix4::make_orthographic(
+			&mut self.projection_matrix,
+			center_x - half_width,
+			center_x + half_width,
+			center_y + half_height,
+			center_y - half_height,
+			self.near,
+			self.far,
+		);
+		Matrix4::invert(
+			Matrix4::copy(&mut self.projection_matrix_inverse, &self.projection_matrix)
+		);
+	}
+
+	pub fn borrow_projection_matrix(&self) -> &[f32; 16] {
+		&self.projection_matrix
+	}
+
+	pub fn borrow_projection_matrix_inverse(&self) -> &[f32; 16] {
+		&self.projection_matrix_inverse
+	}
+
+	// Bit n is layer n
+	pub fn set_layers(&mut self, layers: u32) -> &mut Self {
+		self.layers = layers;
+		self
+	}
+
+	pub fn get_layers(&self) -> u32 {
+		self.layers
+	}
+
+	// Layer is 0 to 31
+	pub fn enable_layer(&mut self, layer: u32) -> &mut Self {
+		self.layers |= 1 << layer;
+		self
+	}
+
+	pub fn disable_layer(&mut self, layer: u32) -> &mut Self {
+		self.layers &= !(1 << layer);
+		self
+	}
+}
+
+impl Camera for OrthographicCamera {
+	fn borrow_projection_matrix(&self) -> &[f32; 16] {
+		&self.projection_matrix
+	}
+
+	fn borrow_projection_matrix_inverse(&self) -> &[f32; 16] {
+		&self.projection_matrix_inverse
+	}
+
+	fn get_layers(&self) -> u32 {
+		self.layers
+	}
+}
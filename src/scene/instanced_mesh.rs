@@ -0,0 +1,116 @@
+use crate::{
+	geometry::geometry::Geometry,
+	material::material::Material,
+	math::matrix4::Matrix4,
+	resource::resource::ResourceId,
+};
+
+// Draws the geometry many times with a single draw call. Each instance
+// has its own transform, applied before the node world matrix, and
+// optionally its own color multiplied with the vertex color.
+// Geometry group ranges are drawn with the material, ignoring their
+// material indices.
+pub struct InstancedMesh {
+	// RGB per instance, None until any color is set
+	colors: Option<Vec<f32>>,
+	count: usize,
+	frustum_culled: bool,
+	geometry: ResourceId<Geometry>,
+	material: ResourceId<Material>,
+	// Column major 4x4 matrix per instance
+	matrices: Vec<f32>,
+	// Incremented whenever the instance matrices or colors change
+	version: u64,
+}
+
+impl InstancedMesh {
+	// The instance matrices are initialized with identity
+	pub fn new(
+		geometry: ResourceId<Geometry>,
+		material: ResourceId<Material>,
+		count: usize,
+	) -> Self {
+		let mut matrices = Vec::with_capacity(count * 16);
+		let identity = Matrix4::create();
+		for _ in 0..count {
+			matrices.extend_from_slice(&identity);
+		}
+
+		InstancedMesh {
+			colors: None,
+			count,
+			frustum_culled: true,
+			geometry,
+			material,
+			matrices,
+			version: 0,
+		}
+	}
+
+	pub fn borrow_geometry(&self) -> &ResourceId<Geometry> {
+		&self.geometry
+	}
+
+	pub fn borrow_material(&self) -> &ResourceId<Material> {
+		&self.material
+	}
+
+	pub fn get_count(&self) -> usize {
+		self.count
+	}
+
+	pub fn set_matrix_at(&mut self, index: usize, matrix: &[f32; 16]) -> &mut Self {
+		self.matrices[index * 16..index * 16 + 16].copy_from_slice(matrix);
+		self.version += 1;
+		self
+	}
+
+	pub fn get_matrix_at(&self, index: usize) -> [f32; 16] {
+		let mut matrix = Matrix4::create();
+		matrix.copy_from_slice(&self.matrices[index * 16..index * 16 + 16]);
+		matrix
+	}
+
+	pub fn borrow_matrices(&self) -> &Vec<f32> {
+		&self.matrices
+	}
+
+	// The other instances are white once any color is set
+	pub fn set_color_at(&mut self, index: usize, color: &[f32; 3]) -> &mut Self {
+		let count = self.count;
+		let colors = self.colors.get_or_insert_with(|| vec![1.0; count * 3]);
+		colors[index * 3..index * 3 + 3].copy_from_slice(color);
+		self.version += 1;
+		self
+	}
+
+	pub fn get_color_at(&self, index: usize) -> Option<[f32; 3]> {
+		self.colors.as_ref().map(|colors| [
+			colors[index * 3],
+			colors[index * 3 + 1],
+			colors[index * 3 + 2],
+		])
+	}
+
+	pub fn borrow_colors(&self) -> Option<&Vec<f32>> {
+		self.colors.as_ref()
+	}
+
+	// Changes every time the instance matrices or colors change, so the
+	// users can skip the work depending only on them
+	pub fn get_version(&self) -> u64 {
+		self.version
+	}
+
+	// Whether the renderer skips the instances outside of the camera view,
+	// tested one by one with the geometry bounding sphere. Disable it for
+	// materials moving the vertices in the shader.
+	pub fn set_frustum_culled(&mut self, frustum_culled: bool) -> &mut Self {
+		self.frustum_culled = frustum_culled;
+		self
+	}
+
+	pub fn is_frustum_culled(&self) -> bool {
+		self.frustum_culled
+	}
+}
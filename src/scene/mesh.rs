@@ -6,8 +6,9 @@ use crate::{
 
 // @TODO: Support shared geometry and material
 pub struct Mesh {
+	frustum_culled: bool,
 	geometry: ResourceId<Geometry>,
-	material: ResourceId<Material>,
+	materials: Vec<ResourceId<Material>>,
 }
 
 impl Mesh {
@@ -16,8 +17,26 @@ impl Mesh {
 		material: ResourceId<Material>,
 	) -> Self {
 		Mesh {
+			frustum_culled: true,
 			geometry: geometry,
-			material: material,
+			materials: vec![material],
+		}
+	}
+
+	// Geometry groups pick the materials with their material indices
+	pub fn new_with_materials(
+		geometry: ResourceId<Geometry>,
+		materials: Vec<ResourceId<Material>>,
+	) -> Self {
+		// @TODO: Proper error handling
+		if materials.is_empty() {
+			panic!("Mesh needs at least one material");
+		}
+
+		Mesh {
+			frustum_culled: true,
+			geometry,
+			materials,
 		}
 	}
 
@@ -25,7 +44,25 @@ impl Mesh {
 		&self.geometry
 	}
 
+	// The first material
 	pub fn borrow_material(&self) -> &ResourceId<Material> {
-		&self.material
+		&self.materials[0]
+	}
+
+	pub fn borrow_materials(&self) -> &Vec<ResourceId<Material>> {
+		&self.materials
+	}
+
+	// Whether the renderer skips the mesh outside of the camera view.
+	// Disable it for meshes whose vertices are moved in the shader,
+	// for example with a material position offset or skinning,
+	// because the bounding sphere is computed from the positions.
+	pub fn set_frustum_culled(&mut self, frustum_culled: bool) -> &mut Self {
+		self.frustum_culled = frustum_culled;
+		self
+	}
+
+	pub fn is_frustum_culled(&self) -> bool {
+		self.frustum_culled
 	}
 }
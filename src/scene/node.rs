@@ -11,28 +11,46 @@ use crate::{
 	},
 };
 
+// Layers the nodes and cameras are in by default
+pub const DEFAULT_LAYERS: u32 = 1;
+
 pub struct Node {
 	children: Vec<ResourceId<Node>>,
+	// Bitmask of the layers the node is in, not inherited by children
+	layers: u32,
 	matrix: [f32; 16],
+	// Position, rotation or scale changed since the matrix was composed
+	matrix_needs_update: bool,
 	parent: Option<ResourceId<Node>>,
 	position: [f32; 3],
 	quaternion: [f32; 4],
 	rotation: [f32; 3],
 	scale: [f32; 3],
+	// Invisible node hides its children too
+	visible: bool,
 	world_matrix: [f32; 16],
+	// Matrix or hierarchy changed since the world matrix was calculated
+	world_matrix_needs_update: bool,
+	// Incremented whenever the world matrix changes
+	world_matrix_version: u64,
 }
 
 impl Node {
 	pub fn new() -> Self {
 		Node {
 			children: Vec::new(),
+			layers: DEFAULT_LAYERS,
 			matrix: Matrix4::create(),
+			matrix_needs_update: false,
 			parent: None,
 			position: Vector3::create(),
 			quaternion: Quaternion::create(),
 			rotation: Euler::create(),
 			scale: *Vector3::set(&mut Vector3::create(), 1.0, 1.0, 1.0),
+			visible: true,
 			world_matrix: Matrix4::create(),
+			world_matrix_needs_update: true,
+			world_matrix_version: 0,
 		}
 	}
 
@@ -44,11 +62,43 @@ impl Node {
 		&self.children
 	}
 
+	pub fn set_visible(&mut self, visible: bool) -> &mut Self {
+		self.visible = visible;
+		self
+	}
+
+	pub fn is_visible(&self) -> bool {
+		self.visible
+	}
+
+	// Bit n is layer n
+	pub fn set_layers(&mut self, layers: u32) -> &mut Self {
+		self.layers = layers;
+		self
+	}
+
+	pub fn get_layers(&self) -> u32 {
+		self.layers
+	}
+
+	// Layer is 0 to 31
+	pub fn enable_layer(&mut self, layer: u32) -> &mut Self {
+		self.layers |= 1 << layer;
+		self
+	}
+
+	pub fn disable_layer(&mut self, layer: u32) -> &mut Self {
+		self.layers &= !(1 << layer);
+		self
+	}
+
 	pub fn borrow_position(&self) -> &[f32; 3] {
 		&self.position
 	}
 
+	// The _mut accessors mark the matrix to be updated
 	pub fn borrow_position_mut(&mut self) -> &mut [f32; 3] {
+		self.matrix_needs_update = true;
 		&mut self.position
 	}
 
@@ -57,6 +107,7 @@ impl Node {
 	}
 
 	pub fn borrow_rotation_mut(&mut self) -> &mut [f32; 3] {
+		self.matrix_needs_update = true;
 		&mut self.rotation
 	}
 
@@ -65,6 +116,7 @@ impl Node {
 	}
 
 	pub fn borrow_scale_mut(&mut self) -> &mut [f32; 3] {
+		self.matrix_needs_update = true;
 		&mut self.scale
 	}
 
@@ -80,58 +132,69 @@ impl Node {
 		Matrix4::copy(&mut self.matrix, matrix);
 		Matrix4::decompose(&mut self.position, &mut self.quaternion, &mut self.scale, &self.matrix);
 		Euler::set_from_quaternion(&mut self.rotation, &self.quaternion);
+		self.matrix_needs_update = false;
+		self.world_matrix_needs_update = true;
 		self
 	}
 
 	pub fn set_world_matrix(&mut self, matrix: &[f32; 16]) -> &mut Self {
 		Matrix4::copy(&mut self.world_matrix, matrix);
+		self.world_matrix_needs_update = false;
+		self.world_matrix_version += 1;
+		self
+	}
+
+	// Changes every time the world matrix changes, so the users can
+	// skip the work depending only on the world matrix
+	pub fn get_world_matrix_version(&self) -> u64 {
+		self.world_matrix_version
+	}
+
+	// Rotates so that -Z faces the target, as cameras look. The target is
+	// in the same space as the position, that is the parent space.
+	pub fn look_at(&mut self, target: &[f32; 3], up: &[f32; 3]) -> &mut Self {
+		let mut matrix = Matrix4::create();
+		Matrix4::make_look_at(&mut matrix, &self.position, target, up);
+		Quaternion::set_from_rotation_matrix(&mut self.quaternion, &matrix);
+		Euler::set_from_rotation_matrix(&mut self.rotation, &matrix);
+		self.matrix_needs_update = true;
 		self
 	}
 
 	pub fn update_matrix(&mut self) -> &mut Self {
 		Quaternion::set_from_euler(&mut self.quaternion, &self.rotation);
 		Matrix4::compose(&mut self.matrix, &self.position, &self.quaternion, &self.scale);
+		self.matrix_needs_update = false;
+		self.world_matrix_needs_update = true;
 		self
 	}
 
-	// @TODO: Optimize
+	// The world matrix is always recalculated because the parent
+	// can't be tracked from here, the descendants only if changed.
 	pub fn update_matrices(
 		&mut self,
 		pool: &mut ResourcePool<Node>,
 	) {
-		self.update_matrix();
+		if self.matrix_needs_update {
+			self.update_matrix();
+		}
 
+		let mut matrix = Matrix4::create();
 		if let Some(parent) = self.borrow_parent() {
 			let parent_matrix = pool.borrow(parent).unwrap().borrow_world_matrix();
-			Matrix4::multiply(&mut self.world_matrix, parent_matrix, &self.matrix);
+			Matrix4::multiply(&mut matrix, parent_matrix, &self.matrix);
 		} else {
-			Matrix4::copy(&mut self.world_matrix, &self.matrix);
+			Matrix4::copy(&mut matrix, &self.matrix);
 		}
+		self.set_world_matrix(&matrix);
 
 		let mut stack = Vec::new();
 
 		for child in self.children.iter() {
-			stack.push(*child);
+			stack.push((*child, Some(self.world_matrix)));
 		}
 
-		while let Some(rid) = stack.pop() {
-			let parent_matrix = {
-				let mut matrix = Matrix4::create();
-				let node = pool.borrow_mut(&rid).unwrap();
-				let parent = node.borrow_parent().cloned().unwrap();
-				Matrix4::copy(&mut matrix, pool.borrow(&parent).unwrap().borrow_world_matrix());
-				matrix
-			};
-
-			let node = pool.borrow_mut(&rid).unwrap();
-			let mut matrix = Matrix4::create();
-			Matrix4::multiply(&mut matrix, &parent_matrix, &node.borrow_matrix());
-			node.set_world_matrix(&matrix);
-
-			for child in node.children.iter() {
-				stack.push(*child);
-			}
-		}
+		NodeExecutor::update_world_matrices(pool, stack);
 	}
 }
 
@@ -139,33 +202,91 @@ pub struct NodeExecutor {
 }
 
 impl NodeExecutor {
+	// Detaches the child from its current parent if any. Add only root
+	// nodes to Scene, children are traversed from them.
+	pub fn add_child(
+		pool: &mut ResourcePool<Node>,
+		parent: &ResourceId<Node>,
+		child: &ResourceId<Node>,
+	) {
+		if let Some(old_parent) = pool.borrow(child).unwrap().borrow_parent().cloned() {
+			Self::remove_child(pool, &old_parent, child);
+		}
+		pool.borrow_mut(parent).unwrap().children.push(*child);
+		let node = pool.borrow_mut(child).unwrap();
+		node.parent = Some(*parent);
+		node.world_matrix_needs_update = true;
+	}
+
+	pub fn remove_child(
+		pool: &mut ResourcePool<Node>,
+		parent: &ResourceId<Node>,
+		child: &ResourceId<Node>,
+	) {
+		let children = &mut pool.borrow_mut(parent).unwrap().children;
+		if let Some(index) = children.iter().position(|rid| rid == child) {
+			children.remove(index);
+			let node = pool.borrow_mut(child).unwrap();
+			node.parent = None;
+			node.world_matrix_needs_update = true;
+		}
+	}
+
+	// Recalculates only the changed nodes and their descendants
 	pub fn update_matrices(
 		pool: &mut ResourcePool<Node>,
 		root: &ResourceId<Node>,
 	) {
-		let mut stack = Vec::new();
-		stack.push(*root);
+		let node = pool.borrow_mut(root).unwrap();
+		// The parent may have been updated outside of the root subtree
+		if node.borrow_parent().is_some() {
+			node.world_matrix_needs_update = true;
+		}
 
-		while let Some(rid) = stack.pop() {
+		Self::update_world_matrices(pool, vec![(*root, None)]);
+	}
+
+	// The stack holds the nodes with the parent world matrix if it changed
+	fn update_world_matrices(
+		pool: &mut ResourcePool<Node>,
+		mut stack: Vec<(ResourceId<Node>, Option<[f32; 16]>)>,
+	) {
+		while let Some((rid, parent_matrix)) = stack.pop() {
 			let node = pool.borrow_mut(&rid).unwrap();
-			node.update_matrix();
 
-			let parent_matrix = {
-				let mut matrix = Matrix4::create();
-				let node = pool.borrow_mut(&rid).unwrap();
-				if let Some(parent) = node.borrow_parent().cloned() {
-					Matrix4::copy(&mut matrix, pool.borrow(&parent).unwrap().borrow_world_matrix());
+			if node.matrix_needs_update {
+				node.update_matrix();
+			}
+
+			if !node.world_matrix_needs_update && parent_matrix.is_none() {
+				for child in node.children.iter() {
+					stack.push((*child, None));
 				}
-				matrix
+				continue;
+			}
+
+			// The parent is unchanged if the node is moved or reparented
+			let parent_matrix = match parent_matrix {
+				Some(matrix) => Some(matrix),
+				None => node.borrow_parent().cloned().map(|parent| {
+					*pool.borrow(&parent).unwrap().borrow_world_matrix()
+				}),
 			};
 
 			let node = pool.borrow_mut(&rid).unwrap();
 			let mut matrix = Matrix4::create();
-			Matrix4::multiply(&mut matrix, &parent_matrix, &node.borrow_matrix());
+			match parent_matrix {
+				Some(parent_matrix) => {
+					Matrix4::multiply(&mut matrix, &parent_matrix, node.borrow_matrix());
+				},
+				None => {
+					Matrix4::copy(&mut matrix, node.borrow_matrix());
+				},
+			};
 			node.set_world_matrix(&matrix);
 
 			for child in node.children.iter() {
-				stack.push(*child);
+				stack.push((*child, Some(matrix)));
 			}
 		}
 	}
@@ -187,4 +308,29 @@ impl NodeExecutor {
 			}
 		}
 	}
+
+	// Skips invisible nodes and their descendants
+	pub fn collect_visible_nodes(
+		pool: &ResourcePool<Node>,
+		root: &ResourceId<Node>,
+		nodes: &mut Vec<ResourceId<Node>>,
+	) {
+		if !pool.borrow(root).unwrap().is_visible() {
+			return;
+		}
+
+		let mut stack = Vec::new();
+		stack.push(*root);
+		nodes.push(*root);
+
+		while let Some(rid) = stack.pop() {
+			let node = pool.borrow(&rid).unwrap();
+			for child in node.children.iter() {
+				if pool.borrow(child).unwrap().is_visible() {
+					stack.push(*child);
+					nodes.push(*child);
+				}
+			}
+		}
+	}
 }
@@ -13,7 +13,11 @@ use crate::{
 		ResourcePools,
 	},
 	scene::{
-		camera::PerspectiveCamera,
+		camera::{
+			OrthographicCamera,
+			PerspectiveCamera,
+		},
+		instanced_mesh::InstancedMesh,
 		mesh::Mesh,
 		node::{
 			Node,
@@ -28,8 +32,10 @@ trait ResourceLinksTrait {
 	fn as_any_mut(&mut self) -> &mut dyn Any;
 }
 
+// A resource can be linked to multiple resources, for example
+// a node to multiple meshes
 pub struct ResourceLinks<T1, T2> {
-	links: HashMap<ResourceId<T1>, ResourceId<T2>>,
+	links: HashMap<ResourceId<T1>, Vec<ResourceId<T2>>>,
 }
 
 impl<T1: 'static, T2: 'static> ResourceLinksTrait for ResourceLinks<T1, T2> {
@@ -53,12 +59,34 @@ impl<T1: 'static, T2: 'static> ResourceLinks<T1, T2> {
 		self.links.contains_key(rid_from)
 	}
 
+	// Linking the same pair twice is ignored
 	pub fn add(&mut self, rid_from: &ResourceId<T1>, rid_to: &ResourceId<T2>) {
-		self.links.insert(*rid_from, *rid_to);
+		let links = self.links.entry(*rid_from).or_default();
+		if !links.contains(rid_to) {
+			links.push(*rid_to);
+		}
+	}
+
+	pub fn remove(&mut self, rid_from: &ResourceId<T1>, rid_to: &ResourceId<T2>) {
+		if let Some(links) = self.links.get_mut(rid_from) {
+			links.retain(|rid| rid != rid_to);
+			if links.is_empty() {
+				self.links.remove(rid_from);
+			}
+		}
 	}
 
+	// The first linked one
 	pub fn borrow(&self, rid_from: &ResourceId<T1>) -> Option<&ResourceId<T2>> {
-		self.links.get(rid_from)
+		self.links.get(rid_from).and_then(|links| links.first())
+	}
+
+	// In the linked order
+	pub fn borrow_all(&self, rid_from: &ResourceId<T1>) -> &[ResourceId<T2>] {
+		match self.links.get(rid_from) {
+			Some(links) => links,
+			None => &[],
+		}
 	}
 }
 
@@ -87,8 +115,12 @@ impl Scene {
 		let mut links = HashMap::new();
 		Self::add_links::<Node, Mesh>(&mut links);
 		Self::add_links::<Mesh, Node>(&mut links);
+		Self::add_links::<Node, InstancedMesh>(&mut links);
+		Self::add_links::<InstancedMesh, Node>(&mut links);
 		Self::add_links::<Node, PerspectiveCamera>(&mut links);
 		Self::add_links::<PerspectiveCamera, Node>(&mut links);
+		Self::add_links::<Node, OrthographicCamera>(&mut links);
+		Self::add_links::<OrthographicCamera, Node>(&mut links);
 
 		Scene {
 			background_color: *Color::set(&mut Color::create(), 1.0, 1.0, 1.0),
@@ -120,6 +152,11 @@ impl Scene {
 	}
 
 	// Where should be this method placed?
+	// A node can have multiple resources of the same type, for example
+	// meshes of a multi-material model. The renderer draws all the meshes.
+	// A resource can be assigned to multiple nodes, for example a mesh
+	// shared by several nodes. Moving a resource to another node is
+	// unassign() followed by assign().
 	pub fn assign<T: 'static>(
 		&mut self,
 		rid1: &ResourceId<Node>,
@@ -129,15 +166,34 @@ impl Scene {
 		self.borrow_links_mut::<T, Node>().add(rid2, rid1);
 	}
 
+	pub fn unassign<T: 'static>(
+		&mut self,
+		rid1: &ResourceId<Node>,
+		rid2: &ResourceId<T>,
+	) {
+		self.borrow_links_mut::<Node, T>().remove(rid1, rid2);
+		self.borrow_links_mut::<T, Node>().remove(rid2, rid1);
+	}
+
 	// @TODO: Rename?
+	// The last assigned one
 	pub fn borrow_assigned_from<T: 'static>(&self, rid: &ResourceId<T>) -> Option<&ResourceId<Node>> {
-		self.borrow_links::<T, Node>().borrow(rid)
+		self.borrow_links::<T, Node>().borrow_all(rid).last()
+	}
+
+	pub fn borrow_all_assigned_from<T: 'static>(&self, rid: &ResourceId<T>) -> &[ResourceId<Node>] {
+		self.borrow_links::<T, Node>().borrow_all(rid)
 	}
 
+	// The first assigned one
 	pub fn borrow_assigned_to<T: 'static>(&self, rid: &ResourceId<Node>) -> Option<&ResourceId<T>> {
 		self.borrow_links::<Node, T>().borrow(rid)
 	}
 
+	pub fn borrow_all_assigned_to<T: 'static>(&self, rid: &ResourceId<Node>) -> &[ResourceId<T>] {
+		self.borrow_links::<Node, T>().borrow_all(rid)
+	}
+
 	pub fn add_node(&mut self, rid: &ResourceId<Node>) {
 		self.nodes.push(*rid);
 	}
@@ -151,6 +207,16 @@ impl Scene {
 		nodes
 	}
 
+	// Skips invisible nodes and their descendants
+	pub fn collect_visible_nodes(&self, pools: &ResourcePools) -> Vec<ResourceId<Node>> {
+		let mut nodes = Vec::new();
+		let pool = pools.borrow::<Node>();
+		for node in self.nodes.iter() {
+			NodeExecutor::collect_visible_nodes(pool, node, &mut nodes);
+		}
+		nodes
+	}
+
 	pub fn borrow_background_color(&self) -> &[f32; 3] {
 		&self.background_color
 	}
@@ -168,3 +234,40 @@ impl Scene {
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		geometry::geometry::Geometry,
+		resource::resource::ResourcePools,
+		scene::{
+			mesh::Mesh,
+			node::Node,
+			scene::Scene,
+		},
+		utils::material_helper::MaterialHelper,
+	};
+
+	#[test]
+	fn assign_shares_resources_between_nodes() {
+		let mut pools = ResourcePools::new();
+		let mut scene = Scene::new();
+		let geometry = pools.borrow_mut::<Geometry>().add(Geometry::new());
+		let material = MaterialHelper::create_basic_material(&mut pools, &[1.0, 1.0, 1.0]);
+		let mesh = pools.borrow_mut::<Mesh>().add(Mesh::new(geometry, material));
+		let node1 = pools.borrow_mut::<Node>().add(Node::new());
+		let node2 = pools.borrow_mut::<Node>().add(Node::new());
+
+		scene.assign(&node1, &mesh);
+		scene.assign(&node2, &mesh);
+		assert_eq!(scene.borrow_all_assigned_to::<Mesh>(&node1), &[mesh]);
+		assert_eq!(scene.borrow_all_assigned_to::<Mesh>(&node2), &[mesh]);
+		assert_eq!(scene.borrow_all_assigned_from::<Mesh>(&mesh), &[node1, node2]);
+		assert_eq!(scene.borrow_assigned_from::<Mesh>(&mesh), Some(&node2));
+
+		// Moving is explicit
+		scene.unassign(&node1, &mesh);
+		assert!(scene.borrow_all_assigned_to::<Mesh>(&node1).is_empty());
+		assert_eq!(scene.borrow_all_assigned_from::<Mesh>(&mesh), &[node2]);
+	}
+}
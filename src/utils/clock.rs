@@ -0,0 +1,52 @@
+// Measures time in seconds for animations and frame-rate independent updates
+pub struct Clock {
+	previous_time: f64,
+	start_time: f64,
+}
+
+impl Default for Clock {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl Clock {
+	pub fn new() -> Self {
+		let time = now();
+		Clock {
+			previous_time: time,
+			start_time: time,
+		}
+	}
+
+	// Since the clock is created
+	pub fn get_elapsed_time(&self) -> f64 {
+		now() - self.start_time
+	}
+
+	// Since the previous call, or since the clock is created for the first call
+	pub fn get_delta(&mut self) -> f64 {
+		let time = now();
+		let delta = time - self.previous_time;
+		self.previous_time = time;
+		delta
+	}
+}
+
+// Non-Wasm
+
+#[cfg(not(target_arch = "wasm32"))]
+fn now() -> f64 {
+	use std::time::{
+		SystemTime,
+		UNIX_EPOCH,
+	};
+	SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs_f64()
+}
+
+// Wasm
+
+#[cfg(target_arch = "wasm32")]
+fn now() -> f64 {
+	js_sys::Date::now() / 1000.0
+}
@@ -0,0 +1,377 @@
+use std::collections::HashSet;
+use winit::{
+	event::{
+		DeviceEvent,
+		ElementState,
+		MouseButton,
+		VirtualKeyCode,
+		WindowEvent,
+	},
+	window::Window,
+};
+use crate::{
+	math::{
+		euler::Euler,
+		matrix4::Matrix4,
+		quaternion::Quaternion,
+		vector3::Vector3,
+	},
+	resource::resource::{
+		ResourceId,
+		ResourcePools,
+	},
+	scene::node::Node,
+};
+
+const EPSILON: f32 = 0.01;
+
+// Keyboard and mouse state shared by FlyControls and FirstPersonControls.
+// W/A/S/D or the arrow keys move, R and F move up and down. The mouse
+// looks around while the pointer is locked or while the left button is
+// pressed.
+struct MovementInput {
+	dragging: bool,
+	keys: HashSet<VirtualKeyCode>,
+	look_delta: [f32; 2],
+	pointer_locked: bool,
+}
+
+impl MovementInput {
+	fn new() -> Self {
+		MovementInput {
+			dragging: false,
+			keys: HashSet::new(),
+			look_delta: [0.0; 2],
+			pointer_locked: false,
+		}
+	}
+
+	fn handle_event(&mut self, event: &WindowEvent) {
+		match event {
+			WindowEvent::KeyboardInput { input, .. } => {
+				if let Some(key) = input.virtual_keycode {
+					match input.state {
+						ElementState::Pressed => self.keys.insert(key),
+						ElementState::Released => self.keys.remove(&key),
+					};
+				}
+			},
+			WindowEvent::MouseInput { state, button: MouseButton::Left, .. } => {
+				self.dragging = *state == ElementState::Pressed;
+			},
+			// Release events are not delivered while the window is unfocused
+			WindowEvent::Focused(false) => {
+				self.keys.clear();
+				self.dragging = false;
+			},
+			_ => {},
+		};
+	}
+
+	// Raw mouse motion keeps coming while the pointer is locked,
+	// unlike CursorMoved
+	fn handle_device_event(&mut self, event: &DeviceEvent) {
+		if let DeviceEvent::MouseMotion { delta } = event {
+			if self.pointer_locked || self.dragging {
+				self.look_delta[0] += delta.0 as f32;
+				self.look_delta[1] += delta.1 as f32;
+			}
+		}
+	}
+
+	fn get_axis(&self, positive: &[VirtualKeyCode], negative: &[VirtualKeyCode]) -> f32 {
+		let is_pressed = |keys: &[VirtualKeyCode]| keys.iter().any(|key| self.keys.contains(key));
+		(is_pressed(positive) as i32 - is_pressed(negative) as i32) as f32
+	}
+
+	// In the camera local space, -Z is forward
+	fn get_direction(&self) -> [f32; 3] {
+		let mut direction = [
+			self.get_axis(&[VirtualKeyCode::D, VirtualKeyCode::Right], &[VirtualKeyCode::A, VirtualKeyCode::Left]),
+			self.get_axis(&[VirtualKeyCode::R], &[VirtualKeyCode::F]),
+			self.get_axis(&[VirtualKeyCode::S, VirtualKeyCode::Down], &[VirtualKeyCode::W, VirtualKeyCode::Up]),
+		];
+		if Vector3::length(&direction) > 0.0 {
+			Vector3::normalize(&mut direction);
+		}
+		direction
+	}
+
+	fn take_look_delta(&mut self) -> [f32; 2] {
+		let delta = self.look_delta;
+		self.look_delta = [0.0; 2];
+		delta
+	}
+
+	fn set_pointer_lock(&mut self, window: &Window, lock: bool) {
+		set_pointer_lock(window, lock);
+		self.pointer_locked = lock;
+	}
+}
+
+// Moves the velocity toward the target velocity. The result depends only on
+// the elapsed time, not on the frame rate. Zero acceleration means instant.
+fn accelerate(velocity: &mut [f32; 3], target: &[f32; 3], acceleration: f32, delta: f32) {
+	let t = match acceleration > 0.0 {
+		true => 1.0 - (-acceleration * delta).exp(),
+		false => 1.0,
+	};
+	for i in 0..3 {
+		velocity[i] += (target[i] - velocity[i]) * t;
+	}
+}
+
+fn get_rotation_matrix(m: &mut [f32; 16], rotation: &[f32; 3]) {
+	let mut quaternion = Quaternion::create();
+	Quaternion::set_from_euler(&mut quaternion, rotation);
+	Matrix4::compose(m, &Vector3::create(), &quaternion, &[1.0; 3]);
+}
+
+// Moves a camera Node in its local space with six degrees of freedom,
+// like a spaceship. Q and E roll in addition to MovementInput keys.
+// The camera Node is assumed to have no transformed parent.
+//
+// Pass the window and device events to handle_event() and
+// handle_device_event(), and call update() with the delta time
+// in seconds, for example from Clock::get_delta(), once per frame.
+pub struct FlyControls {
+	acceleration: f32,
+	camera: ResourceId<Node>,
+	input: MovementInput,
+	look_speed: f32,
+	movement_speed: f32,
+	roll_speed: f32,
+	velocity: [f32; 3],
+}
+
+impl FlyControls {
+	pub fn new(camera: &ResourceId<Node>) -> Self {
+		FlyControls {
+			acceleration: 10.0,
+			camera: *camera,
+			input: MovementInput::new(),
+			look_speed: 0.002,
+			movement_speed: 1.0,
+			roll_speed: 1.0,
+			velocity: Vector3::create(),
+		}
+	}
+
+	// Per second. Zero means the velocity changes instantly
+	pub fn set_acceleration(&mut self, acceleration: f32) -> &mut Self {
+		self.acceleration = acceleration;
+		self
+	}
+
+	// Radians per pixel of the mouse movement
+	pub fn set_look_speed(&mut self, look_speed: f32) -> &mut Self {
+		self.look_speed = look_speed;
+		self
+	}
+
+	// Units per second
+	pub fn set_movement_speed(&mut self, movement_speed: f32) -> &mut Self {
+		self.movement_speed = movement_speed;
+		self
+	}
+
+	// Radians per second
+	pub fn set_roll_speed(&mut self, roll_speed: f32) -> &mut Self {
+		self.roll_speed = roll_speed;
+		self
+	}
+
+	// On the web the lock needs to be requested from a user input event
+	// handler, for example a mouse click, and the browser releases it with
+	// Escape by itself. Call set_pointer_lock(window, false) on Escape to
+	// keep in sync.
+	pub fn set_pointer_lock(&mut self, window: &Window, lock: bool) -> &mut Self {
+		self.input.set_pointer_lock(window, lock);
+		self
+	}
+
+	pub fn is_pointer_locked(&self) -> bool {
+		self.input.pointer_locked
+	}
+
+	pub fn handle_event(&mut self, event: &WindowEvent) {
+		self.input.handle_event(event);
+	}
+
+	pub fn handle_device_event(&mut self, event: &DeviceEvent) {
+		self.input.handle_device_event(event);
+	}
+
+	pub fn update(&mut self, pools: &mut ResourcePools, delta: f64) {
+		let delta = delta as f32;
+		let look = self.input.take_look_delta();
+		let roll = self.input.get_axis(&[VirtualKeyCode::Q], &[VirtualKeyCode::E]) * self.roll_speed * delta;
+
+		let mut target = self.input.get_direction();
+		Vector3::scale(&mut target, self.movement_speed);
+		accelerate(&mut self.velocity, &target, self.acceleration, delta);
+
+		let node = pools.borrow_mut::<Node>().borrow_mut(&self.camera).unwrap();
+
+		// Rotates in the camera local space
+		let mut rotation = Matrix4::create();
+		let mut local_rotation = Matrix4::create();
+		let mut m = Matrix4::create();
+		get_rotation_matrix(&mut rotation, node.borrow_rotation());
+		Matrix4::make_rotation_y(&mut m, -look[0] * self.look_speed);
+		Matrix4::multiply(&mut local_rotation, &rotation, &m);
+		Matrix4::make_rotation_x(&mut m, -look[1] * self.look_speed);
+		Matrix4::multiply(&mut rotation, &local_rotation, &m);
+		Matrix4::make_rotation_z(&mut m, roll);
+		Matrix4::multiply(&mut local_rotation, &rotation, &m);
+
+		let position = node.borrow_position_mut();
+		for i in 0..3 {
+			position[i] += (
+				local_rotation[i] * self.velocity[0] +
+				local_rotation[i + 4] * self.velocity[1] +
+				local_rotation[i + 8] * self.velocity[2]
+			) * delta;
+		}
+		Euler::set_from_rotation_matrix(node.borrow_rotation_mut(), &local_rotation);
+	}
+}
+
+// Walks a camera Node on the horizontal plane and looks around with yaw
+// and pitch, keeping the camera upright. R and F move along the world
+// up axis. The camera Node is assumed to have no transformed parent.
+//
+// Pass the window and device events to handle_event() and
+// handle_device_event(), and call update() with the delta time
+// in seconds, for example from Clock::get_delta(), once per frame.
+pub struct FirstPersonControls {
+	acceleration: f32,
+	camera: ResourceId<Node>,
+	input: MovementInput,
+	look_speed: f32,
+	max_pitch: f32,
+	min_pitch: f32,
+	movement_speed: f32,
+	velocity: [f32; 3],
+}
+
+impl FirstPersonControls {
+	pub fn new(camera: &ResourceId<Node>) -> Self {
+		FirstPersonControls {
+			acceleration: 10.0,
+			camera: *camera,
+			input: MovementInput::new(),
+			look_speed: 0.002,
+			max_pitch: std::f32::consts::FRAC_PI_2 - EPSILON,
+			min_pitch: -std::f32::consts::FRAC_PI_2 + EPSILON,
+			movement_speed: 1.0,
+			velocity: Vector3::create(),
+		}
+	}
+
+	// Per second. Zero means the velocity changes instantly
+	pub fn set_acceleration(&mut self, acceleration: f32) -> &mut Self {
+		self.acceleration = acceleration;
+		self
+	}
+
+	// Radians per pixel of the mouse movement
+	pub fn set_look_speed(&mut self, look_speed: f32) -> &mut Self {
+		self.look_speed = look_speed;
+		self
+	}
+
+	// Units per second
+	pub fn set_movement_speed(&mut self, movement_speed: f32) -> &mut Self {
+		self.movement_speed = movement_speed;
+		self
+	}
+
+	// Radians from the horizon, up is positive.
+	// Clamped to slightly inside -PI/2 to PI/2.
+	pub fn set_pitch_range(&mut self, min: f32, max: f32) -> &mut Self {
+		self.min_pitch = min.max(-std::f32::consts::FRAC_PI_2 + EPSILON);
+		self.max_pitch = max.min(std::f32::consts::FRAC_PI_2 - EPSILON);
+		self
+	}
+
+	// On the web the lock needs to be requested from a user input event
+	// handler, for example a mouse click, and the browser releases it with
+	// Escape by itself. Call set_pointer_lock(window, false) on Escape to
+	// keep in sync.
+	pub fn set_pointer_lock(&mut self, window: &Window, lock: bool) -> &mut Self {
+		self.input.set_pointer_lock(window, lock);
+		self
+	}
+
+	pub fn is_pointer_locked(&self) -> bool {
+		self.input.pointer_locked
+	}
+
+	pub fn handle_event(&mut self, event: &WindowEvent) {
+		self.input.handle_event(event);
+	}
+
+	pub fn handle_device_event(&mut self, event: &DeviceEvent) {
+		self.input.handle_device_event(event);
+	}
+
+	pub fn update(&mut self, pools: &mut ResourcePools, delta: f64) {
+		let delta = delta as f32;
+		let look = self.input.take_look_delta();
+
+		let mut target = self.input.get_direction();
+		Vector3::scale(&mut target, self.movement_speed);
+		accelerate(&mut self.velocity, &target, self.acceleration, delta);
+
+		let node = pools.borrow_mut::<Node>().borrow_mut(&self.camera).unwrap();
+
+		// Yaw and pitch from the current forward direction, so that
+		// the rotation can also be changed outside of the controls
+		let mut rotation = Matrix4::create();
+		get_rotation_matrix(&mut rotation, node.borrow_rotation());
+		let mut yaw = rotation[8].atan2(rotation[10]);
+		let mut pitch = (-rotation[9]).clamp(-1.0, 1.0).asin();
+
+		yaw -= look[0] * self.look_speed;
+		pitch = (pitch - look[1] * self.look_speed).clamp(self.min_pitch, self.max_pitch);
+
+		let mut pitch_rotation = Matrix4::create();
+		let mut yaw_rotation = Matrix4::create();
+		Matrix4::make_rotation_x(&mut pitch_rotation, pitch);
+		Matrix4::make_rotation_y(&mut yaw_rotation, yaw);
+		Matrix4::multiply(&mut rotation, &yaw_rotation, &pitch_rotation);
+
+		// Moves along the yaw rotated axes and the world up axis
+		let position = node.borrow_position_mut();
+		for i in 0..3 {
+			position[i] += (
+				yaw_rotation[i] * self.velocity[0] +
+				yaw_rotation[i + 4] * self.velocity[1] +
+				yaw_rotation[i + 8] * self.velocity[2]
+			) * delta;
+		}
+		Euler::set_from_rotation_matrix(node.borrow_rotation_mut(), &rotation);
+	}
+}
+
+// Non-Wasm
+
+#[cfg(not(target_arch = "wasm32"))]
+fn set_pointer_lock(window: &Window, lock: bool) {
+	// Some platforms don't support grabbing, the cursor is hidden anyway
+	let _ = window.set_cursor_grab(lock);
+	window.set_cursor_visible(!lock);
+}
+
+// Wasm
+
+// winit doesn't support cursor grab on the web, so uses Pointer Lock API
+#[cfg(target_arch = "wasm32")]
+fn set_pointer_lock(window: &Window, lock: bool) {
+	use winit::platform::web::WindowExtWebSys;
+	match lock {
+		true => window.canvas().request_pointer_lock(),
+		false => web_sys::window().unwrap().document().unwrap().exit_pointer_lock(),
+	};
+}
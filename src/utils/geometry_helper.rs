@@ -1,3 +1,5 @@
+use std::f32::consts::PI;
+
 use crate::{
 	math::vector3::Vector3,
 	geometry::{
@@ -9,6 +11,7 @@ use crate::{
 		ResourceId,
 		ResourcePools,
 	},
+	utils::geometry_processor::GeometryProcessor,
 };
 
 pub struct GeometryHelper {
@@ -43,12 +46,7 @@ impl GeometryHelper {
 			0, 1, 2,
 		].to_vec();
 
-		let mut geometry = Geometry::new();
-		geometry.set_attribute("position", pools.borrow_mut::<Attribute>().add(Attribute::new(positions, 3)));
-		geometry.set_attribute("normal", pools.borrow_mut::<Attribute>().add(Attribute::new(normals, 3)));
-		geometry.set_attribute("uv", pools.borrow_mut::<Attribute>().add(Attribute::new(uvs, 2)));
-		geometry.set_index(pools.borrow_mut::<Index>().add(Index::new(indices)));
-		pools.borrow_mut::<Geometry>().add(geometry)
+		create_geometry(pools, positions, normals, uvs, Some(indices))
 	}
 
 	pub fn create_plane(
@@ -56,50 +54,24 @@ impl GeometryHelper {
 		width: f32,
 		height: f32,
 	) -> ResourceId<Geometry> {
-		let positions = [
-			// top-left
-			-0.5 * width, 0.5 * height, 0.0,
-			// top-right
-			0.5 * width, 0.5 * height, 0.0,
-			// bottom-left
-			-0.5 * width, -0.5 * height, 0.0,
-			// bottom-right
-			0.5 * width, -0.5 * height, 0.0,
-		].to_vec();
-
-		let normals = [
-			// top-left
-			0.0, 0.0, 1.0,
-			// top-right
-			0.0, 0.0, 1.0,
-			// bottom-left
-			0.0, 0.0, 1.0,
-			// bottom-right
-			0.0, 0.0, 1.0,
-		].to_vec();
-
-		let uvs = [
-			// top-left
-			0.0, 0.0,
-			// top-right
-			1.0, 0.0,
-			// bottom-left
-			0.0, 1.0,
-			// bottom-right
-			1.0, 1.0,
-		].to_vec();
-
-		let indices = [
-			0, 2, 1,
-			1, 2, 3,
-		].to_vec();
+		Self::create_segmented_plane(pools, width, height, 1, 1)
+	}
 
-		let mut geometry = Geometry::new();
-		geometry.set_attribute("position", pools.borrow_mut::<Attribute>().add(Attribute::new(positions, 3)));
-		geometry.set_attribute("normal", pools.borrow_mut::<Attribute>().add(Attribute::new(normals, 3)));
-		geometry.set_attribute("uv", pools.borrow_mut::<Attribute>().add(Attribute::new(uvs, 2)));
-		geometry.set_index(pools.borrow_mut::<Index>().add(Index::new(indices)));
-		pools.borrow_mut::<Geometry>().add(geometry)
+	// On XY plane facing +Z
+	pub fn create_segmented_plane(
+		pools: &mut ResourcePools,
+		width: f32,
+		height: f32,
+		width_segments: u32,
+		height_segments: u32,
+	) -> ResourceId<Geometry> {
+		let mut buffers = GeometryBuffers::new();
+		buffers.push_plane(
+			[0, 1, 2], [1.0, -1.0],
+			[width, height, 0.0],
+			width_segments.max(1), height_segments.max(1),
+		);
+		buffers.create_geometry(pools)
 	}
 
 	pub fn create_box(
@@ -108,106 +80,494 @@ impl GeometryHelper {
 		height: f32,
 		depth: f32,
 	) -> ResourceId<Geometry> {
-		let mut positions = Vec::new();
-		let mut normals = Vec::new();
-		let mut uvs = Vec::new();
-		let mut indices = Vec::new();
-
-		let mut position_vec = Vector3::create();
-
-		// @TODO: Clean up
-		for face in 0..6 {
-			let (x, y, z, nx, ny, nz, dx, dy, dz) = match face {
-				// front
-				0 => (
-					-0.5, 0.5, 0.5,
-					0.0, 0.0, 1.0,
-					[0.0, 1.0, 0.0, 1.0],
-					[0.0, 0.0, -1.0, -1.0],
-					[0.0, 0.0, 0.0, 0.0],
-				),
-				// right
-				1 => (
-					0.5, 0.5, 0.5,
-					1.0, 0.0, 0.0,
-					[0.0, 0.0, 0.0, 0.0],
-					[0.0, 0.0, -1.0, -1.0],
-					[0.0, -1.0, 0.0, -1.0],
-				),
-				// back
-				2 => (
-					0.5, 0.5, -0.5,
-					0.0, 0.0, -1.0,
-					[0.0, -1.0, 0.0, -1.0],
-					[0.0, 0.0, -1.0, -1.0],
-					[0.0, 0.0, 0.0, 0.0],
-				),
-				// left
-				3 => (
-					-0.5, 0.5, -0.5,
-					-1.0, 0.0, 0.0,
-					[0.0, 0.0, 0.0, 0.0],
-					[0.0, 0.0, -1.0, -1.0],
-					[0.0, 1.0, 0.0, 1.0],
-				),
-				// top
-				4 => (
-					-0.5, 0.5, -0.5,
-					0.0, 1.0, 0.0,
-					[0.0, 1.0, 0.0, 1.0],
-					[0.0, 0.0, 0.0, 0.0],
-					[0.0, 0.0, 1.0, 1.0],
-				),
-				// bottom
-				_ => (
-					-0.5, -0.5, 0.5,
-					0.0, -1.0, 0.0,
-					[0.0, 1.0, 0.0, 1.0],
-					[0.0, 0.0, 0.0, 0.0],
-					[0.0, 0.0, -1.0, -1.0],
-				),
+		Self::create_segmented_box(pools, width, height, depth, 1, 1, 1)
+	}
+
+	pub fn create_segmented_box(
+		pools: &mut ResourcePools,
+		width: f32,
+		height: f32,
+		depth: f32,
+		width_segments: u32,
+		height_segments: u32,
+		depth_segments: u32,
+	) -> ResourceId<Geometry> {
+		let width_segments = width_segments.max(1);
+		let height_segments = height_segments.max(1);
+		let depth_segments = depth_segments.max(1);
+
+		let mut buffers = GeometryBuffers::new();
+		// front
+		buffers.push_plane([0, 1, 2], [1.0, -1.0], [width, height, depth], width_segments, height_segments);
+		// right
+		buffers.push_plane([2, 1, 0], [-1.0, -1.0], [depth, height, width], depth_segments, height_segments);
+		// back
+		buffers.push_plane([0, 1, 2], [-1.0, -1.0], [width, height, -depth], width_segments, height_segments);
+		// left
+		buffers.push_plane([2, 1, 0], [1.0, -1.0], [depth, height, -width], depth_segments, height_segments);
+		// top
+		buffers.push_plane([0, 2, 1], [1.0, 1.0], [width, depth, height], width_segments, depth_segments);
+		// bottom
+		buffers.push_plane([0, 2, 1], [1.0, -1.0], [width, depth, -height], width_segments, depth_segments);
+		buffers.create_geometry(pools)
+	}
+
+	// UV sphere. The texture is mapped with equirectangular projection.
+	pub fn create_sphere(
+		pools: &mut ResourcePools,
+		radius: f32,
+		width_segments: u32,
+		height_segments: u32,
+	) -> ResourceId<Geometry> {
+		let width_segments = width_segments.max(3);
+		let height_segments = height_segments.max(2);
+
+		let mut buffers = GeometryBuffers::new();
+		for y in 0..=height_segments {
+			let v = y as f32 / height_segments as f32;
+			let theta = v * PI;
+
+			// Shifts the pole uvs to the middle of the segments
+			let u_offset = match y {
+				0 => 0.5 / width_segments as f32,
+				_ if y == height_segments => -0.5 / width_segments as f32,
+				_ => 0.0,
 			};
 
-			for i in 0..4 {
-				position_vec[0] = (x + dx[i]) * width;
-				position_vec[1] = (y + dy[i]) * height;
-				position_vec[2] = (z + dz[i]) * depth;
+			for x in 0..=width_segments {
+				let u = x as f32 / width_segments as f32;
+				let phi = u * PI * 2.0;
+				let normal = [
+					-phi.cos() * theta.sin(),
+					theta.cos(),
+					phi.sin() * theta.sin(),
+				];
+				buffers.push_vertex(
+					[normal[0] * radius, normal[1] * radius, normal[2] * radius],
+					normal,
+					[u + u_offset, v],
+				);
+			}
+		}
+
+		// No triangles of zero area at the poles
+		buffers.push_grid_indices(0, width_segments, height_segments, true, true);
+		buffers.create_geometry(pools)
+	}
+
+	// Subdivided icosahedron, evenly distributed vertices unlike UV sphere.
+	// Each subdivision splits every triangle into four.
+	pub fn create_icosphere(
+		pools: &mut ResourcePools,
+		radius: f32,
+		subdivisions: u32,
+	) -> ResourceId<Geometry> {
+		let t = (1.0 + 5.0_f32.sqrt()) / 2.0;
+		let vertices = [
+			[-1.0, t, 0.0], [1.0, t, 0.0], [-1.0, -t, 0.0], [1.0, -t, 0.0],
+			[0.0, -1.0, t], [0.0, 1.0, t], [0.0, -1.0, -t], [0.0, 1.0, -t],
+			[t, 0.0, -1.0], [t, 0.0, 1.0], [-t, 0.0, -1.0], [-t, 0.0, 1.0],
+		];
+
+		let faces = [
+			[0, 11, 5], [0, 5, 1], [0, 1, 7], [0, 7, 10], [0, 10, 11],
+			[1, 5, 9], [5, 11, 4], [11, 10, 2], [10, 7, 6], [7, 1, 8],
+			[3, 9, 4], [3, 4, 2], [3, 2, 6], [3, 6, 8], [3, 8, 9],
+			[4, 9, 5], [2, 4, 11], [6, 2, 10], [8, 6, 7], [9, 8, 1],
+		];
+
+		let mut triangles = faces.iter().map(|face| {
+			let mut triangle = [[0.0; 3]; 3];
+			for i in 0..3 {
+				Vector3::copy(&mut triangle[i], &vertices[face[i]]);
+				Vector3::normalize(&mut triangle[i]);
+			}
+			triangle
+		}).collect::<Vec<[[f32; 3]; 3]>>();
+
+		for _ in 0..subdivisions {
+			let mut subdivided = Vec::with_capacity(triangles.len() * 4);
+			for [a, b, c] in triangles.iter() {
+				let midpoint = |v: &[f32; 3], v2: &[f32; 3]| {
+					let mut m = [v[0] + v2[0], v[1] + v2[1], v[2] + v2[2]];
+					Vector3::normalize(&mut m);
+					m
+				};
+				let ab = midpoint(a, b);
+				let bc = midpoint(b, c);
+				let ca = midpoint(c, a);
+				subdivided.push([*a, ab, ca]);
+				subdivided.push([ab, *b, bc]);
+				subdivided.push([ca, bc, *c]);
+				subdivided.push([ab, bc, ca]);
+			}
+			triangles = subdivided;
+		}
+
+		let mut buffers = GeometryBuffers::new();
+		for triangle in triangles.iter() {
+			let mut uvs = [[0.0; 2]; 3];
+			for i in 0..3 {
+				let n = &triangle[i];
+				uvs[i] = [n[2].atan2(-n[0]) / (PI * 2.0) + 0.5, n[1].clamp(-1.0, 1.0).acos() / PI];
+			}
+
+			// Triangles crossing the seam wrap the small u around
+			let max_u = uvs.iter().map(|uv| uv[0]).fold(0.0, f32::max);
+			let min_u = uvs.iter().map(|uv| uv[0]).fold(1.0, f32::min);
+			if max_u > 0.9 && min_u < 0.1 {
+				for uv in uvs.iter_mut() {
+					if uv[0] < 0.2 {
+						uv[0] += 1.0;
+					}
+				}
+			}
 
-				for j in 0..3 {
-					positions.push(position_vec[j]);
+			// u of a pole vertex is undefined so it takes
+			// the average of the other vertices in the triangle
+			for i in 0..3 {
+				if triangle[i][1].abs() > 0.99999 {
+					uvs[i][0] = (uvs[(i + 1) % 3][0] + uvs[(i + 2) % 3][0]) / 2.0;
 				}
+			}
+
+			for i in 0..3 {
+				let n = triangle[i];
+				buffers.push_vertex([n[0] * radius, n[1] * radius, n[2] * radius], n, uvs[i]);
+			}
+		}
+
+		let geometry = buffers.create_non_indexed_geometry(pools);
+		// Shares the vertices except the seam ones. Stays non-indexed
+		// if too many vertices for u16 index.
+		GeometryProcessor::merge_vertices(pools, &geometry, 0.0);
+		geometry
+	}
+
+	// Along Y axis. Cone if either radius is zero.
+	pub fn create_cylinder(
+		pools: &mut ResourcePools,
+		radius_top: f32,
+		radius_bottom: f32,
+		height: f32,
+		radial_segments: u32,
+		height_segments: u32,
+		open_ended: bool,
+	) -> ResourceId<Geometry> {
+		let radial_segments = radial_segments.max(3);
+		let height_segments = height_segments.max(1);
+		let half_height = height / 2.0;
+		let slope = (radius_bottom - radius_top) / height;
+
+		let mut buffers = GeometryBuffers::new();
+
+		// torso
+		for y in 0..=height_segments {
+			let v = y as f32 / height_segments as f32;
+			let radius = v * (radius_bottom - radius_top) + radius_top;
+			for x in 0..=radial_segments {
+				let u = x as f32 / radial_segments as f32;
+				let theta = u * PI * 2.0;
+				let mut normal = [theta.sin(), slope, theta.cos()];
+				Vector3::normalize(&mut normal);
+				buffers.push_vertex(
+					[radius * theta.sin(), -v * height + half_height, radius * theta.cos()],
+					normal,
+					[u, v],
+				);
+			}
+		}
+		buffers.push_grid_indices(
+			0, radial_segments, height_segments,
+			radius_top == 0.0, radius_bottom == 0.0,
+		);
 
-				normals.push(nx);
-				normals.push(ny);
-				normals.push(nz);
+		if !open_ended {
+			if radius_top > 0.0 {
+				buffers.push_cap(radius_top, half_height, radial_segments, true);
+			}
+			if radius_bottom > 0.0 {
+				buffers.push_cap(radius_bottom, -half_height, radial_segments, false);
 			}
+		}
 
-			uvs.push(0.0);
-			uvs.push(0.0);
+		buffers.create_geometry(pools)
+	}
+
+	// Along Y axis, the tip at the top
+	pub fn create_cone(
+		pools: &mut ResourcePools,
+		radius: f32,
+		height: f32,
+		radial_segments: u32,
+		height_segments: u32,
+		open_ended: bool,
+	) -> ResourceId<Geometry> {
+		Self::create_cylinder(pools, 0.0, radius, height, radial_segments, height_segments, open_ended)
+	}
 
-			uvs.push(1.0);
-			uvs.push(0.0);
+	// On XY plane around Z axis. radius is from the center to
+	// the center of the tube.
+	pub fn create_torus(
+		pools: &mut ResourcePools,
+		radius: f32,
+		tube: f32,
+		radial_segments: u32,
+		tubular_segments: u32,
+	) -> ResourceId<Geometry> {
+		let radial_segments = radial_segments.max(3);
+		let tubular_segments = tubular_segments.max(3);
 
-			uvs.push(0.0);
-			uvs.push(1.0);
+		let mut buffers = GeometryBuffers::new();
+		for j in 0..=radial_segments {
+			let v = j as f32 / radial_segments as f32;
+			let phi = v * PI * 2.0;
+			for i in 0..=tubular_segments {
+				let u = i as f32 / tubular_segments as f32;
+				let theta = u * PI * 2.0;
+				let position = [
+					(radius + tube * phi.cos()) * theta.cos(),
+					(radius + tube * phi.cos()) * theta.sin(),
+					tube * phi.sin(),
+				];
+				let mut normal = position;
+				Vector3::sub(&mut normal, &[radius * theta.cos(), radius * theta.sin(), 0.0]);
+				Vector3::normalize(&mut normal);
+				buffers.push_vertex(position, normal, [u, 1.0 - v]);
+			}
+		}
 
-			uvs.push(1.0);
-			uvs.push(1.0);
+		let row = tubular_segments + 1;
+		for j in 1..=radial_segments {
+			for i in 1..=tubular_segments {
+				let a = row * j + i - 1;
+				let b = row * (j - 1) + i - 1;
+				let c = row * (j - 1) + i;
+				let d = row * j + i;
+				buffers.push_triangle(a, b, d);
+				buffers.push_triangle(b, c, d);
+			}
+		}
 
-			indices.push(face * 4 + 0);
-			indices.push(face * 4 + 2);
-			indices.push(face * 4 + 1);
+		buffers.create_geometry(pools)
+	}
 
-			indices.push(face * 4 + 1);
-			indices.push(face * 4 + 2);
-			indices.push(face * 4 + 3);
+	// Cylinder with hemisphere ends along Y axis. length is of the
+	// cylinder part so the total height is length + radius * 2.
+	pub fn create_capsule(
+		pools: &mut ResourcePools,
+		radius: f32,
+		length: f32,
+		cap_segments: u32,
+		radial_segments: u32,
+	) -> ResourceId<Geometry> {
+		let cap_segments = cap_segments.max(1);
+		let radial_segments = radial_segments.max(3);
+		let half_length = length / 2.0;
+
+		// Profile from the top pole to the bottom pole,
+		// (radius, y, normal radius, normal y)
+		let mut profile = Vec::new();
+		for k in 0..=cap_segments {
+			let angle = k as f32 / cap_segments as f32 * PI / 2.0;
+			profile.push([radius * angle.sin(), half_length + radius * angle.cos(), angle.sin(), angle.cos()]);
+		}
+		for k in 0..=cap_segments {
+			let angle = PI / 2.0 + k as f32 / cap_segments as f32 * PI / 2.0;
+			profile.push([radius * angle.sin(), -half_length + radius * angle.cos(), angle.sin(), angle.cos()]);
+		}
+
+		// v is proportional to the distance along the profile
+		let mut distances = vec![0.0];
+		for k in 1..profile.len() {
+			let dr = profile[k][0] - profile[k - 1][0];
+			let dy = profile[k][1] - profile[k - 1][1];
+			distances.push(distances[k - 1] + (dr * dr + dy * dy).sqrt());
+		}
+		let total_distance = distances[distances.len() - 1];
+
+		let mut buffers = GeometryBuffers::new();
+		for (k, [r, y, nr, ny]) in profile.iter().enumerate() {
+			let v = distances[k] / total_distance;
+			let u_offset = match k {
+				0 => 0.5 / radial_segments as f32,
+				_ if k == profile.len() - 1 => -0.5 / radial_segments as f32,
+				_ => 0.0,
+			};
+			for x in 0..=radial_segments {
+				let u = x as f32 / radial_segments as f32;
+				let theta = u * PI * 2.0;
+				buffers.push_vertex(
+					[r * theta.sin(), *y, r * theta.cos()],
+					[nr * theta.sin(), *ny, nr * theta.cos()],
+					[u + u_offset, v],
+				);
+			}
 		}
+		buffers.push_grid_indices(0, radial_segments, profile.len() as u32 - 1, true, true);
+
+		buffers.create_geometry(pools)
+	}
+}
+
+// Vertex data under construction shared by the generators
+struct GeometryBuffers {
+	indices: Vec<u16>,
+	normals: Vec<f32>,
+	positions: Vec<f32>,
+	uvs: Vec<f32>,
+}
+
+impl GeometryBuffers {
+	fn new() -> Self {
+		GeometryBuffers {
+			indices: Vec::new(),
+			normals: Vec::new(),
+			positions: Vec::new(),
+			uvs: Vec::new(),
+		}
+	}
+
+	fn get_vertex_count(&self) -> u32 {
+		self.positions.len() as u32 / 3
+	}
+
+	fn push_vertex(&mut self, position: [f32; 3], normal: [f32; 3], uv: [f32; 2]) {
+		self.positions.extend_from_slice(&position);
+		self.normals.extend_from_slice(&normal);
+		self.uvs.extend_from_slice(&uv);
+	}
+
+	// @TODO: Proper error handling
+	fn push_triangle(&mut self, a: u32, b: u32, c: u32) {
+		if a.max(b).max(c) > u16::MAX as u32 {
+			panic!("Too many vertices for u16 index");
+		}
+		self.indices.push(a as u16);
+		self.indices.push(b as u16);
+		self.indices.push(c as u16);
+	}
+
+	// Triangulates a grid of (columns + 1) x (rows + 1) vertices pushed row by
+	// row from the top, starting at the offset. skip_top and skip_bottom skip
+	// the zero area triangles if the first or the last row is collapsed to
+	// a single point.
+	fn push_grid_indices(
+		&mut self,
+		offset: u32,
+		columns: u32,
+		rows: u32,
+		skip_top: bool,
+		skip_bottom: bool,
+	) {
+		let row = columns + 1;
+		for y in 0..rows {
+			for x in 0..columns {
+				let a = offset + row * y + x;
+				let b = offset + row * (y + 1) + x;
+				let c = offset + row * (y + 1) + x + 1;
+				let d = offset + row * y + x + 1;
+				if !skip_top || y != 0 {
+					self.push_triangle(a, b, d);
+				}
+				if !skip_bottom || y != rows - 1 {
+					self.push_triangle(b, c, d);
+				}
+			}
+		}
+	}
+
+	// Grid on the plane spanned by the u and v axes of the axes, facing
+	// the w axis at size[2] / 2 distance. Negative size[2] faces the
+	// opposite direction. directions flip u and v.
+	fn push_plane(
+		&mut self,
+		axes: [usize; 3],
+		directions: [f32; 2],
+		size: [f32; 3],
+		columns: u32,
+		rows: u32,
+	) {
+		let offset = self.get_vertex_count();
+		let [u_axis, v_axis, w_axis] = axes;
+
+		let mut normal = [0.0; 3];
+		normal[w_axis] = match size[2] >= 0.0 {
+			true => 1.0,
+			false => -1.0,
+		};
+
+		for y in 0..=rows {
+			for x in 0..=columns {
+				let u = x as f32 / columns as f32;
+				let v = y as f32 / rows as f32;
+				let mut position = [0.0; 3];
+				position[u_axis] = (u - 0.5) * size[0] * directions[0];
+				position[v_axis] = (v - 0.5) * size[1] * directions[1];
+				position[w_axis] = size[2] / 2.0;
+				self.push_vertex(position, normal, [u, v]);
+			}
+		}
+
+		self.push_grid_indices(offset, columns, rows, false, false);
+	}
+
+	// Disk on XZ plane at y, facing +Y if top or -Y otherwise
+	fn push_cap(&mut self, radius: f32, y: f32, segments: u32, top: bool) {
+		let sign = match top {
+			true => 1.0,
+			false => -1.0,
+		};
+
+		// A center vertex per segment for the uvs
+		let center_offset = self.get_vertex_count();
+		for _ in 0..segments {
+			self.push_vertex([0.0, y, 0.0], [0.0, sign, 0.0], [0.5, 0.5]);
+		}
+
+		let ring_offset = self.get_vertex_count();
+		for x in 0..=segments {
+			let theta = x as f32 / segments as f32 * PI * 2.0;
+			self.push_vertex(
+				[radius * theta.sin(), y, radius * theta.cos()],
+				[0.0, sign, 0.0],
+				[theta.cos() * 0.5 + 0.5, 0.5 - theta.sin() * 0.5 * sign],
+			);
+		}
+
+		for x in 0..segments {
+			let center = center_offset + x;
+			let ring = ring_offset + x;
+			match top {
+				true => self.push_triangle(ring, ring + 1, center),
+				false => self.push_triangle(ring + 1, ring, center),
+			};
+		}
+	}
+
+	fn create_geometry(self, pools: &mut ResourcePools) -> ResourceId<Geometry> {
+		let indices = self.indices;
+		create_geometry(pools, self.positions, self.normals, self.uvs, Some(indices))
+	}
+
+	fn create_non_indexed_geometry(self, pools: &mut ResourcePools) -> ResourceId<Geometry> {
+		create_geometry(pools, self.positions, self.normals, self.uvs, None)
+	}
+}
 
-		let mut geometry = Geometry::new();
-		geometry.set_attribute("position", pools.borrow_mut::<Attribute>().add(Attribute::new(positions, 3)));
-		geometry.set_attribute("normal", pools.borrow_mut::<Attribute>().add(Attribute::new(normals, 3)));
-		geometry.set_attribute("uv", pools.borrow_mut::<Attribute>().add(Attribute::new(uvs, 2)));
+fn create_geometry(
+	pools: &mut ResourcePools,
+	positions: Vec<f32>,
+	normals: Vec<f32>,
+	uvs: Vec<f32>,
+	indices: Option<Vec<u16>>,
+) -> ResourceId<Geometry> {
+	let mut geometry = Geometry::new();
+	geometry.set_attribute("position", pools.borrow_mut::<Attribute>().add(Attribute::new(positions, 3)));
+	geometry.set_attribute("normal", pools.borrow_mut::<Attribute>().add(Attribute::new(normals, 3)));
+	geometry.set_attribute("uv", pools.borrow_mut::<Attribute>().add(Attribute::new(uvs, 2)));
+	if let Some(indices) = indices {
 		geometry.set_index(pools.borrow_mut::<Index>().add(Index::new(indices)));
-		pools.borrow_mut::<Geometry>().add(geometry)
 	}
+	pools.borrow_mut::<Geometry>().add(geometry)
 }
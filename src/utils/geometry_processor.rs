@@ -0,0 +1,999 @@
+use std::collections::{
+	HashMap,
+	VecDeque,
+};
+
+use crate::{
+	geometry::{
+		attribute::{
+			Attribute,
+			AttributeData,
+		},
+		geometry::{
+			Geometry,
+			GeometryGroup,
+		},
+		index::Index,
+	},
+	math::{
+		box3::Box3,
+		matrix3::Matrix3,
+		sphere::Sphere,
+		vector3::Vector3,
+	},
+	resource::resource::{
+		ResourceId,
+		ResourcePools,
+	},
+};
+
+// Index is u16 so an indexed geometry can have 65536 vertices at most
+const MAX_INDEXED_VERTEX_COUNT: usize = u16::MAX as usize + 1;
+
+// Parameters of Tom Forsyth's linear-speed vertex cache optimization
+const CACHE_SIZE: usize = 32;
+const CACHE_DECAY_POWER: f32 = 1.5;
+const LAST_TRIANGLE_SCORE: f32 = 0.75;
+const VALENCE_BOOST_SCALE: f32 = 2.0;
+const VALENCE_BOOST_POWER: f32 = 0.5;
+
+// Post-transform cache size simulated to split triangles into clusters
+// for the overdraw optimization
+const OVERDRAW_CACHE_SIZE: usize = 16;
+
+// Geometry processing utilities. They replace the geometry attributes
+// and index with newly added resources rather than editing the existing
+// ones in place because the renderer caches the GPU buffers per resource.
+// The old resources are left in the pools.
+pub struct GeometryProcessor {
+}
+
+impl GeometryProcessor {
+	// Computes smooth vertex normals by accumulating area weighted face
+	// normals of the faces sharing a vertex, and sets them as "normal"
+	// attribute. Returns false if the geometry has no position attribute.
+	pub fn compute_vertex_normals(
+		pools: &mut ResourcePools,
+		geometry_rid: &ResourceId<Geometry>,
+	) -> bool {
+		let normals = {
+			let geometry = match pools.borrow::<Geometry>().borrow(geometry_rid) {
+				Some(geometry) => geometry,
+				None => return false,
+			};
+
+			let positions = match borrow_positions(pools, geometry) {
+				Some(positions) => positions,
+				None => return false,
+			};
+
+			let indices = match get_indices(pools, geometry) {
+				Some(indices) => indices,
+				None => return false,
+			};
+
+			let mut normals = vec![0.0; positions.len()];
+			let mut face_normal = Vector3::create();
+			for face in indices.chunks(3) {
+				if face.len() < 3 {
+					break;
+				}
+				get_face_normal(&mut face_normal, positions, face);
+				for index in face.iter() {
+					let offset = *index as usize * 3;
+					for i in 0..3 {
+						normals[offset + i] += face_normal[i];
+					}
+				}
+			}
+
+			let mut normal = Vector3::create();
+			for item in normals.chunks_mut(3) {
+				Vector3::set(&mut normal, item[0], item[1], item[2]);
+				Vector3::normalize(&mut normal);
+				item.copy_from_slice(&normal);
+			}
+
+			normals
+		};
+
+		let normal = pools.borrow_mut::<Attribute>().add(Attribute::new(normals, 3));
+		pools.borrow_mut::<Geometry>().borrow_mut(geometry_rid).unwrap().set_attribute("normal", normal);
+		true
+	}
+
+	// Computes faceted normals. Vertices can't be shared between faces
+	// with different normals so the geometry is de-indexed first.
+	pub fn compute_flat_normals(
+		pools: &mut ResourcePools,
+		geometry_rid: &ResourceId<Geometry>,
+	) -> bool {
+		Self::to_non_indexed(pools, geometry_rid) &&
+			Self::compute_vertex_normals(pools, geometry_rid)
+	}
+
+	// Returns the axis aligned bounding box of the positions in
+	// [min_x, min_y, min_z, max_x, max_y, max_z] format
+	pub fn compute_bounding_box(
+		pools: &ResourcePools,
+		geometry_rid: &ResourceId<Geometry>,
+	) -> Option<[f32; 6]> {
+		let geometry = pools.borrow::<Geometry>().borrow(geometry_rid)?;
+		let positions = borrow_positions(pools, geometry)?;
+
+		let mut bounding_box = Box3::create();
+		let mut position = Vector3::create();
+		for item in positions.chunks(3) {
+			Vector3::set(&mut position, item[0], item[1], item[2]);
+			Box3::expand_by_point(&mut bounding_box, &position);
+		}
+		Some(bounding_box)
+	}
+
+	// Returns the bounding sphere of the positions in
+	// [center_x, center_y, center_z, radius] format. The center is
+	// the bounding box center.
+	pub fn compute_bounding_sphere(
+		pools: &ResourcePools,
+		geometry_rid: &ResourceId<Geometry>,
+	) -> Option<[f32; 4]> {
+		let bounding_box = Self::compute_bounding_box(pools, geometry_rid)?;
+		let mut sphere = Sphere::create();
+		if Box3::is_empty(&bounding_box) {
+			return Some(sphere);
+		}
+
+		let geometry = pools.borrow::<Geometry>().borrow(geometry_rid)?;
+		let positions = borrow_positions(pools, geometry)?;
+
+		let mut center = Vector3::create();
+		Box3::get_center(&mut center, &bounding_box);
+
+		let mut radius: f32 = 0.0;
+		let mut position = Vector3::create();
+		for item in positions.chunks(3) {
+			Vector3::set(&mut position, item[0], item[1], item[2]);
+			radius = radius.max(Vector3::distance(&center, &position));
+		}
+
+		Sphere::set(&mut sphere, &center, radius);
+		Some(sphere)
+	}
+
+	// Transforms positions by the matrix, and normals and tangents by
+	// its rotation and scale part. Other attributes are kept as they are.
+	pub fn apply_matrix4(
+		pools: &mut ResourcePools,
+		geometry_rid: &ResourceId<Geometry>,
+		matrix: &[f32; 16],
+	) -> bool {
+		let (positions, normals, tangents) = {
+			let geometry = match pools.borrow::<Geometry>().borrow(geometry_rid) {
+				Some(geometry) => geometry,
+				None => return false,
+			};
+
+			let mut positions = match borrow_positions(pools, geometry) {
+				Some(positions) => positions.clone(),
+				None => return false,
+			};
+
+			let mut position = Vector3::create();
+			for item in positions.chunks_mut(3) {
+				Vector3::set(&mut position, item[0], item[1], item[2]);
+				Vector3::apply_matrix4(&mut position, matrix);
+				item.copy_from_slice(&position);
+			}
+
+			let attribute_pool = pools.borrow::<Attribute>();
+			let borrow_float32 = |name, item_size| {
+				geometry.borrow_attribute(name).and_then(|rid| attribute_pool.borrow(rid)).and_then(|attribute| {
+					match attribute.borrow_attribute_data() {
+						AttributeData::Float32(data) if attribute.get_item_size() == item_size => Some(data),
+						_ => None,
+					}
+				})
+			};
+
+			let mut normal_matrix = Matrix3::create();
+			Matrix3::make_normal_from_matrix4(&mut normal_matrix, matrix);
+
+			let normals = borrow_float32("normal", 3).map(|normals| {
+				let mut normals = normals.clone();
+				let mut normal = Vector3::create();
+				for item in normals.chunks_mut(3) {
+					Vector3::set(&mut normal, item[0], item[1], item[2]);
+					Vector3::apply_matrix3(&mut normal, &normal_matrix);
+					Vector3::normalize(&mut normal);
+					item.copy_from_slice(&normal);
+				}
+				normals
+			});
+
+			// w, the bitangent sign, is kept
+			let tangents = borrow_float32("tangent", 4).map(|tangents| {
+				let mut tangents = tangents.clone();
+				let mut tangent = Vector3::create();
+				for item in tangents.chunks_mut(4) {
+					Vector3::set(&mut tangent, item[0], item[1], item[2]);
+					Vector3::transform_direction(&mut tangent, matrix);
+					item[0..3].copy_from_slice(&tangent);
+				}
+				tangents
+			});
+
+			(positions, normals, tangents)
+		};
+
+		let mut attributes = vec![("position", Attribute::new(positions, 3))];
+		if let Some(normals) = normals {
+			attributes.push(("normal", Attribute::new(normals, 3)));
+		}
+		if let Some(tangents) = tangents {
+			attributes.push(("tangent", Attribute::new(tangents, 4)));
+		}
+
+		set_attributes(pools, geometry_rid, attributes);
+		true
+	}
+
+	// Expands the indexed attributes so that every face has its own
+	// vertices, and removes the index. Does nothing to a non-indexed one.
+	pub fn to_non_indexed(
+		pools: &mut ResourcePools,
+		geometry_rid: &ResourceId<Geometry>,
+	) -> bool {
+		let attributes = {
+			let geometry = match pools.borrow::<Geometry>().borrow(geometry_rid) {
+				Some(geometry) => geometry,
+				None => return false,
+			};
+
+			if geometry.borrow_index().is_none() {
+				return true;
+			}
+
+			let indices = match get_indices(pools, geometry) {
+				Some(indices) => indices,
+				None => return false,
+			};
+
+			let attribute_pool = pools.borrow::<Attribute>();
+			let mut attributes = Vec::new();
+			for (name, rid) in geometry.borrow_attributes().iter() {
+				if let Some(attribute) = attribute_pool.borrow(rid) {
+					attributes.push((*name, gather_attribute(attribute, &indices)));
+				}
+			}
+			attributes
+		};
+
+		set_attributes(pools, geometry_rid, attributes);
+		pools.borrow_mut::<Geometry>().borrow_mut(geometry_rid).unwrap().remove_index();
+		true
+	}
+
+	// Welds vertices whose all attribute values are equal within the
+	// tolerance, and makes the geometry indexed. Zero tolerance welds
+	// only exactly equal vertices. Returns false if the welded geometry
+	// still has too many vertices for u16 index.
+	pub fn merge_vertices(
+		pools: &mut ResourcePools,
+		geometry_rid: &ResourceId<Geometry>,
+		tolerance: f32,
+	) -> bool {
+		let (attributes, indices) = {
+			let geometry = match pools.borrow::<Geometry>().borrow(geometry_rid) {
+				Some(geometry) => geometry,
+				None => return false,
+			};
+
+			let indices = match get_indices(pools, geometry) {
+				Some(indices) => indices,
+				None => return false,
+			};
+
+			let attribute_pool = pools.borrow::<Attribute>();
+			let mut names = geometry.borrow_attributes().keys().copied().collect::<Vec<&'static str>>();
+			// For the deterministic vertex keys
+			names.sort();
+
+			let mut attributes = Vec::new();
+			for name in names.iter() {
+				if let Some(attribute) = attribute_pool.borrow(geometry.borrow_attribute(name).unwrap()) {
+					attributes.push((*name, attribute));
+				}
+			}
+
+			let vertex_count = attributes.iter().map(|(_, attribute)| {
+				attribute.get_count() as usize
+			}).min().unwrap_or(0);
+
+			let mut unique_vertices = Vec::new();
+			let mut remap = Vec::with_capacity(vertex_count);
+			let mut vertex_map = HashMap::new();
+			let mut key = Vec::new();
+			for vertex in 0..vertex_count {
+				key.clear();
+				for (_, attribute) in attributes.iter() {
+					push_vertex_key(&mut key, attribute, vertex, tolerance);
+				}
+				let new_index = *vertex_map.entry(key.clone()).or_insert_with(|| {
+					unique_vertices.push(vertex as u32);
+					unique_vertices.len() as u32 - 1
+				});
+				remap.push(new_index);
+			}
+
+			if unique_vertices.len() > MAX_INDEXED_VERTEX_COUNT {
+				return false;
+			}
+
+			let attributes = attributes.iter().map(|(name, attribute)| {
+				(*name, gather_attribute(attribute, &unique_vertices))
+			}).collect::<Vec<(&'static str, Attribute)>>();
+
+			let indices = indices.iter().map(|index| {
+				remap[*index as usize] as u16
+			}).collect::<Vec<u16>>();
+
+			(attributes, indices)
+		};
+
+		set_attributes(pools, geometry_rid, attributes);
+		let index = pools.borrow_mut::<Index>().add(Index::new(indices));
+		pools.borrow_mut::<Geometry>().borrow_mut(geometry_rid).unwrap().set_index(index);
+		true
+	}
+
+	// Merges the geometries into a new geometry. They must have the same
+	// topology and the same set of attributes in the same formats. The result is indexed if any
+	// of them is indexed and the total vertex count fits in u16 index,
+	// otherwise they are de-indexed and concatenated. Groups are kept with
+	// their ranges offset, and if any of them has groups the ones without
+	// get a group of the whole geometry with material index 0.
+	pub fn merge_geometries(
+		pools: &mut ResourcePools,
+		geometry_rids: &[ResourceId<Geometry>],
+	) -> Option<ResourceId<Geometry>> {
+		let (attributes, indices, groups, topology) = {
+			let geometry_pool = pools.borrow::<Geometry>();
+			let attribute_pool = pools.borrow::<Attribute>();
+
+			let mut geometries = Vec::new();
+			for rid in geometry_rids.iter() {
+				geometries.push(geometry_pool.borrow(rid)?);
+			}
+
+			let mut names = geometries.first()?.borrow_attributes().keys().copied().collect::<Vec<&'static str>>();
+			names.sort();
+			let topology = *geometries.first()?.borrow_topology();
+
+			let mut vertex_counts = Vec::new();
+			let mut geometry_indices = Vec::new();
+			for geometry in geometries.iter() {
+				if geometry.borrow_attributes().len() != names.len() ||
+					*geometry.borrow_topology() != topology {
+					return None;
+				}
+				vertex_counts.push(attribute_pool.borrow(geometry.borrow_attribute("position")?)?.get_count());
+				geometry_indices.push(get_indices(pools, geometry)?);
+			}
+
+			let indexed = geometries.iter().any(|geometry| geometry.borrow_index().is_some()) &&
+				vertex_counts.iter().sum::<u32>() as usize <= MAX_INDEXED_VERTEX_COUNT;
+
+			let mut attributes = Vec::new();
+			for name in names.iter() {
+				let mut sources = Vec::new();
+				for geometry in geometries.iter() {
+					sources.push(attribute_pool.borrow(geometry.borrow_attribute(name)?)?);
+				}
+
+				let attribute = match indexed {
+					true => concat_attributes(&sources)?,
+					false => {
+						let gathered = sources.iter().zip(geometry_indices.iter()).map(|(attribute, indices)| {
+							gather_attribute(attribute, indices)
+						}).collect::<Vec<Attribute>>();
+						concat_attributes(&gathered.iter().collect::<Vec<&Attribute>>())?
+					},
+				};
+				attributes.push((*name, attribute));
+			}
+
+			let indices = match indexed {
+				true => {
+					let mut indices = Vec::new();
+					let mut offset = 0;
+					for (geometry_index, vertex_count) in geometry_indices.iter().zip(vertex_counts.iter()) {
+						for index in geometry_index.iter() {
+							indices.push((index + offset) as u16);
+						}
+						offset += vertex_count;
+					}
+					Some(indices)
+				},
+				false => None,
+			};
+
+			// Index count and de-indexed vertex count are the same
+			let mut groups = Vec::new();
+			if geometries.iter().any(|geometry| !geometry.borrow_groups().is_empty()) {
+				let mut offset = 0;
+				for (geometry, indices) in geometries.iter().zip(geometry_indices.iter()) {
+					match geometry.borrow_groups().is_empty() {
+						true => groups.push(GeometryGroup {
+							count: indices.len() as u32,
+							material_index: 0,
+							start: offset,
+						}),
+						false => for group in geometry.borrow_groups().iter() {
+							groups.push(GeometryGroup {
+								start: group.start + offset,
+								..*group
+							});
+						},
+					};
+					offset += indices.len() as u32;
+				}
+			}
+
+			(attributes, indices, groups, topology)
+		};
+
+		let mut geometry = Geometry::new();
+		geometry.set_topology(topology);
+		for group in groups {
+			geometry.add_group(group.start, group.count, group.material_index);
+		}
+		for (name, attribute) in attributes {
+			geometry.set_attribute(name, pools.borrow_mut::<Attribute>().add(attribute));
+		}
+		if let Some(indices) = indices {
+			geometry.set_index(pools.borrow_mut::<Index>().add(Index::new(indices)));
+		}
+		Some(pools.borrow_mut::<Geometry>().add(geometry))
+	}
+
+	// Reorders the triangles of an indexed geometry to make the best use
+	// of the GPU post-transform vertex cache with Tom Forsyth's algorithm.
+	// Returns false if the geometry isn't indexed.
+	pub fn optimize_vertex_cache(
+		pools: &mut ResourcePools,
+		geometry_rid: &ResourceId<Geometry>,
+	) -> bool {
+		let indices = {
+			let geometry = match pools.borrow::<Geometry>().borrow(geometry_rid) {
+				Some(geometry) => geometry,
+				None => return false,
+			};
+
+			let indices = match geometry.borrow_index().and_then(|rid| pools.borrow::<Index>().borrow(rid)) {
+				Some(index) => index.borrow_data(),
+				None => return false,
+			};
+
+			let vertex_count = indices.iter().map(|index| *index as usize + 1).max().unwrap_or(0);
+			reorder_in_groups(geometry, indices, |indices| {
+				optimize_vertex_cache_indices(indices, vertex_count)
+			})
+		};
+
+		let index = pools.borrow_mut::<Index>().add(Index::new(indices));
+		pools.borrow_mut::<Geometry>().borrow_mut(geometry_rid).unwrap().set_index(index);
+		true
+	}
+
+	// Reorders clusters of triangles so that the ones facing outward from
+	// the geometry center are drawn first and occlude the others, which
+	// reduces overdraw. The clusters are split where the simulated vertex
+	// cache misses a whole triangle so that the vertex cache efficiency is
+	// mostly kept. Call optimize_vertex_cache() before this. Triangles are
+	// reordered within each group. Returns false if the geometry isn't
+	// indexed.
+	pub fn optimize_overdraw(
+		pools: &mut ResourcePools,
+		geometry_rid: &ResourceId<Geometry>,
+	) -> bool {
+		let indices = {
+			let geometry = match pools.borrow::<Geometry>().borrow(geometry_rid) {
+				Some(geometry) => geometry,
+				None => return false,
+			};
+
+			let indices = match geometry.borrow_index().and_then(|rid| pools.borrow::<Index>().borrow(rid)) {
+				Some(index) => index.borrow_data(),
+				None => return false,
+			};
+
+			let positions = match borrow_positions(pools, geometry) {
+				Some(positions) => positions,
+				None => return false,
+			};
+
+			reorder_in_groups(geometry, indices, |indices| {
+				optimize_overdraw_indices(indices, positions)
+			})
+		};
+
+		let index = pools.borrow_mut::<Index>().add(Index::new(indices));
+		pools.borrow_mut::<Geometry>().borrow_mut(geometry_rid).unwrap().set_index(index);
+		true
+	}
+}
+
+fn borrow_positions<'a>(
+	pools: &'a ResourcePools,
+	geometry: &Geometry,
+) -> Option<&'a Vec<f32>> {
+	let attribute = pools.borrow::<Attribute>().borrow(geometry.borrow_attribute("position")?)?;
+	match attribute.borrow_attribute_data() {
+		AttributeData::Float32(data) if attribute.get_item_size() == 3 => Some(data),
+		_ => None,
+	}
+}
+
+// Index data, or sequential indices of the position attribute
+// if the geometry isn't indexed
+fn get_indices(
+	pools: &ResourcePools,
+	geometry: &Geometry,
+) -> Option<Vec<u32>> {
+	match geometry.borrow_index() {
+		Some(rid) => {
+			let index = pools.borrow::<Index>().borrow(rid)?;
+			Some(index.borrow_data().iter().map(|index| *index as u32).collect())
+		},
+		None => {
+			let attribute = pools.borrow::<Attribute>().borrow(geometry.borrow_attribute("position")?)?;
+			Some((0..attribute.get_count()).collect())
+		},
+	}
+}
+
+// Applies the reordering to each group range so that triangles stay in
+// the groups of their materials, or to the whole index without groups
+fn reorder_in_groups<F: Fn(&[u16]) -> Vec<u16>>(
+	geometry: &Geometry,
+	indices: &[u16],
+	reorder: F,
+) -> Vec<u16> {
+	if geometry.borrow_groups().is_empty() {
+		return reorder(indices);
+	}
+
+	let mut result = indices.to_vec();
+	for group in geometry.borrow_groups().iter() {
+		let start = (group.start as usize).min(indices.len());
+		let end = (group.start as usize + group.count as usize).min(indices.len());
+		let reordered = reorder(&indices[start..end]);
+		result[start..start + reordered.len()].copy_from_slice(&reordered);
+	}
+	result
+}
+
+fn set_attributes(
+	pools: &mut ResourcePools,
+	geometry_rid: &ResourceId<Geometry>,
+	attributes: Vec<(&'static str, Attribute)>,
+) {
+	for (name, attribute) in attributes {
+		let rid = pools.borrow_mut::<Attribute>().add(attribute);
+		pools.borrow_mut::<Geometry>().borrow_mut(geometry_rid).unwrap().set_attribute(name, rid);
+	}
+}
+
+// Area weighted, not normalized
+fn get_face_normal<'a>(
+	normal: &'a mut [f32; 3],
+	positions: &[f32],
+	face: &[u32],
+) -> &'a mut [f32; 3] {
+	let get_position = |index: u32| {
+		let offset = index as usize * 3;
+		[positions[offset], positions[offset + 1], positions[offset + 2]]
+	};
+
+	let a = get_position(face[0]);
+	let mut ab = get_position(face[1]);
+	let mut ac = get_position(face[2]);
+	Vector3::sub(&mut ab, &a);
+	Vector3::sub(&mut ac, &a);
+	Vector3::cross(&mut ab, &ac);
+	Vector3::scale(&mut ab, 0.5);
+	normal.copy_from_slice(&ab);
+	normal
+}
+
+fn create_attribute(data: AttributeData, item_size: u32) -> Attribute {
+	match data {
+		AttributeData::Float32(data) => Attribute::new(data, item_size),
+		AttributeData::Unorm8(data) => Attribute::new_unorm8(data, item_size),
+		AttributeData::Unorm16(data) => Attribute::new_unorm16(data, item_size),
+	}
+}
+
+fn gather<T: Copy>(data: &[T], item_size: usize, indices: &[u32]) -> Vec<T> {
+	let mut gathered = Vec::with_capacity(indices.len() * item_size);
+	for index in indices.iter() {
+		let offset = *index as usize * item_size;
+		gathered.extend_from_slice(&data[offset..offset + item_size]);
+	}
+	gathered
+}
+
+fn gather_attribute(attribute: &Attribute, indices: &[u32]) -> Attribute {
+	let item_size = attribute.get_item_size();
+	let size = item_size as usize;
+	create_attribute(match attribute.borrow_attribute_data() {
+		AttributeData::Float32(data) => AttributeData::Float32(gather(data, size, indices)),
+		AttributeData::Unorm8(data) => AttributeData::Unorm8(gather(data, size, indices)),
+		AttributeData::Unorm16(data) => AttributeData::Unorm16(gather(data, size, indices)),
+	}, item_size)
+}
+
+// Returns None if the attributes have different formats
+fn concat_attributes(attributes: &[&Attribute]) -> Option<Attribute> {
+	let first = attributes.first()?;
+	let item_size = first.get_item_size();
+	let mut data = match first.borrow_attribute_data() {
+		AttributeData::Float32(_) => AttributeData::Float32(Vec::new()),
+		AttributeData::Unorm8(_) => AttributeData::Unorm8(Vec::new()),
+		AttributeData::Unorm16(_) => AttributeData::Unorm16(Vec::new()),
+	};
+
+	for attribute in attributes.iter() {
+		if attribute.get_item_size() != item_size {
+			return None;
+		}
+		match (&mut data, attribute.borrow_attribute_data()) {
+			(AttributeData::Float32(dst), AttributeData::Float32(src)) => dst.extend_from_slice(src),
+			(AttributeData::Unorm8(dst), AttributeData::Unorm8(src)) => dst.extend_from_slice(src),
+			(AttributeData::Unorm16(dst), AttributeData::Unorm16(src)) => dst.extend_from_slice(src),
+			_ => return None,
+		};
+	}
+
+	Some(create_attribute(data, item_size))
+}
+
+// Float values are quantized by the tolerance, or compared
+// by their bits if the tolerance is zero
+fn push_vertex_key(key: &mut Vec<i64>, attribute: &Attribute, vertex: usize, tolerance: f32) {
+	let item_size = attribute.get_item_size() as usize;
+	let range = vertex * item_size..(vertex + 1) * item_size;
+	match attribute.borrow_attribute_data() {
+		AttributeData::Float32(data) => {
+			for value in data[range].iter() {
+				key.push(match tolerance > 0.0 {
+					true => (*value / tolerance).round() as i64,
+					// Adding zero turns -0.0 into 0.0
+					false => (*value + 0.0).to_bits() as i64,
+				});
+			}
+		},
+		AttributeData::Unorm8(data) => key.extend(data[range].iter().map(|value| *value as i64)),
+		AttributeData::Unorm16(data) => key.extend(data[range].iter().map(|value| *value as i64)),
+	};
+}
+
+fn get_vertex_score(cache_position: Option<usize>, valence: u32) -> f32 {
+	// No triangles left to use the vertex
+	if valence == 0 {
+		return -1.0;
+	}
+
+	let cache_score = match cache_position {
+		// The vertices of the last triangle get a fixed score so that
+		// the next triangle doesn't strongly prefer any of them
+		Some(position) if position < 3 => LAST_TRIANGLE_SCORE,
+		Some(position) => {
+			let scale = 1.0 / (CACHE_SIZE - 3) as f32;
+			(1.0 - (position - 3) as f32 * scale).powf(CACHE_DECAY_POWER)
+		},
+		None => 0.0,
+	};
+
+	// Vertices with fewer triangles left get higher score so that
+	// lone triangles aren't left behind
+	cache_score + VALENCE_BOOST_SCALE * (valence as f32).powf(-VALENCE_BOOST_POWER)
+}
+
+fn optimize_vertex_cache_indices(indices: &[u16], vertex_count: usize) -> Vec<u16> {
+	let triangle_count = indices.len() / 3;
+
+	let mut vertex_triangles = vec![Vec::new(); vertex_count];
+	for triangle in 0..triangle_count {
+		for i in 0..3 {
+			vertex_triangles[indices[triangle * 3 + i] as usize].push(triangle);
+		}
+	}
+
+	let mut cache_positions = vec![None; vertex_count];
+	let mut vertex_scores = vertex_triangles.iter().map(|triangles| {
+		get_vertex_score(None, triangles.len() as u32)
+	}).collect::<Vec<f32>>();
+
+	let get_triangle_score = |vertex_scores: &[f32], triangle: usize| {
+		(0..3).map(|i| vertex_scores[indices[triangle * 3 + i] as usize]).sum::<f32>()
+	};
+
+	let mut emitted = vec![false; triangle_count];
+	let mut cache: Vec<usize> = Vec::with_capacity(CACHE_SIZE + 3);
+	let mut result = Vec::with_capacity(triangle_count * 3);
+
+	let mut best_triangle = (0..triangle_count).max_by(|a, b| {
+		get_triangle_score(&vertex_scores, *a).partial_cmp(&get_triangle_score(&vertex_scores, *b)).unwrap()
+	});
+	// Fallback to the first remaining triangle when no triangles
+	// share the cached vertices
+	let mut next_triangle = 0;
+
+	while let Some(triangle) = best_triangle {
+		emitted[triangle] = true;
+		let vertices = &indices[triangle * 3..triangle * 3 + 3];
+		result.extend_from_slice(vertices);
+
+		// Moves the triangle vertices to the front of the LRU cache
+		let mut new_cache = Vec::with_capacity(CACHE_SIZE + 3);
+		for vertex in vertices.iter() {
+			let vertex = *vertex as usize;
+			vertex_triangles[vertex].retain(|t| *t != triangle);
+			if !new_cache.contains(&vertex) {
+				new_cache.push(vertex);
+			}
+		}
+		for vertex in cache.iter() {
+			if !new_cache.contains(vertex) {
+				new_cache.push(*vertex);
+			}
+		}
+
+		for (position, vertex) in new_cache.iter().enumerate() {
+			cache_positions[*vertex] = match position < CACHE_SIZE {
+				true => Some(position),
+				false => None,
+			};
+			vertex_scores[*vertex] = get_vertex_score(cache_positions[*vertex], vertex_triangles[*vertex].len() as u32);
+		}
+
+		best_triangle = None;
+		let mut best_score = -1.0;
+		for vertex in new_cache.iter() {
+			for t in vertex_triangles[*vertex].iter() {
+				let score = get_triangle_score(&vertex_scores, *t);
+				if score > best_score {
+					best_score = score;
+					best_triangle = Some(*t);
+				}
+			}
+		}
+
+		new_cache.truncate(CACHE_SIZE);
+		cache = new_cache;
+
+		if best_triangle.is_none() {
+			while next_triangle < triangle_count && emitted[next_triangle] {
+				next_triangle += 1;
+			}
+			if next_triangle < triangle_count {
+				best_triangle = Some(next_triangle);
+			}
+		}
+	}
+
+	result
+}
+
+fn optimize_overdraw_indices(indices: &[u16], positions: &[f32]) -> Vec<u16> {
+	let triangle_count = indices.len() / 3;
+	if triangle_count == 0 {
+		return indices.to_vec();
+	}
+
+	// Splits triangles into clusters where a triangle misses
+	// all of its vertices in the simulated FIFO cache
+	let mut cluster_starts = Vec::new();
+	let mut cache = VecDeque::with_capacity(OVERDRAW_CACHE_SIZE + 3);
+	for triangle in 0..triangle_count {
+		let mut misses = 0;
+		for i in 0..3 {
+			let vertex = indices[triangle * 3 + i];
+			if !cache.contains(&vertex) {
+				misses += 1;
+				cache.push_back(vertex);
+				if cache.len() > OVERDRAW_CACHE_SIZE {
+					cache.pop_front();
+				}
+			}
+		}
+		if triangle == 0 || misses == 3 {
+			cluster_starts.push(triangle);
+		}
+	}
+
+	let get_position = |index: u16| {
+		let offset = index as usize * 3;
+		[positions[offset], positions[offset + 1], positions[offset + 2]]
+	};
+
+	let mut mesh_centroid = Vector3::create();
+	for index in indices.iter() {
+		Vector3::add(&mut mesh_centroid, &get_position(*index));
+	}
+	Vector3::scale(&mut mesh_centroid, 1.0 / indices.len() as f32);
+
+	let mut clusters = Vec::with_capacity(cluster_starts.len());
+	let mut face = [0; 3];
+	let mut face_normal = Vector3::create();
+	for (i, start) in cluster_starts.iter().enumerate() {
+		let end = match cluster_starts.get(i + 1) {
+			Some(end) => *end,
+			None => triangle_count,
+		};
+
+		let mut centroid = Vector3::create();
+		let mut normal = Vector3::create();
+		for triangle in *start..end {
+			for j in 0..3 {
+				face[j] = indices[triangle * 3 + j] as u32;
+				Vector3::add(&mut centroid, &get_position(indices[triangle * 3 + j]));
+			}
+			get_face_normal(&mut face_normal, positions, &face);
+			Vector3::add(&mut normal, &face_normal);
+		}
+		Vector3::scale(&mut centroid, 1.0 / ((end - start) * 3) as f32);
+		Vector3::normalize(&mut normal);
+
+		// Positive if the cluster faces outward from the geometry center
+		Vector3::sub(&mut centroid, &mesh_centroid);
+		clusters.push((Vector3::dot(&centroid, &normal), *start, end));
+	}
+
+	// Stable sort keeps the vertex cache friendly order for even keys
+	clusters.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+
+	let mut result = Vec::with_capacity(indices.len());
+	for (_, start, end) in clusters.iter() {
+		result.extend_from_slice(&indices[start * 3..end * 3]);
+	}
+	result
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		geometry::{
+			attribute::Attribute,
+			geometry::{
+				Geometry,
+				GeometryGroup,
+			},
+			index::Index,
+		},
+		resource::resource::{
+			ResourceId,
+			ResourcePools,
+		},
+	};
+	use super::GeometryProcessor;
+
+	fn create_geometry(
+		pools: &mut ResourcePools,
+		positions: Vec<f32>,
+		indices: Option<Vec<u16>>,
+	) -> ResourceId<Geometry> {
+		let mut geometry = Geometry::new();
+		geometry.set_attribute("position", pools.borrow_mut::<Attribute>().add(Attribute::new(positions, 3)));
+		if let Some(indices) = indices {
+			geometry.set_index(pools.borrow_mut::<Index>().add(Index::new(indices)));
+		}
+		pools.borrow_mut::<Geometry>().add(geometry)
+	}
+
+	fn borrow_indices<'a>(pools: &'a ResourcePools, geometry_rid: &ResourceId<Geometry>) -> &'a Vec<u16> {
+		let geometry = pools.borrow::<Geometry>().borrow(geometry_rid).unwrap();
+		pools.borrow::<Index>().borrow(geometry.borrow_index().unwrap()).unwrap().borrow_data()
+	}
+
+	fn borrow_positions<'a>(pools: &'a ResourcePools, geometry_rid: &ResourceId<Geometry>) -> &'a Vec<f32> {
+		let geometry = pools.borrow::<Geometry>().borrow(geometry_rid).unwrap();
+		pools.borrow::<Attribute>().borrow(geometry.borrow_attribute("position").unwrap()).unwrap().borrow_data()
+	}
+
+	// Triangles with their vertex indices rotated to start from the smallest
+	// one so that the winding is kept, sorted for the comparison
+	fn get_triangles(indices: &[u16]) -> Vec<[u16; 3]> {
+		let mut triangles = indices.chunks(3).map(|t| {
+			match t.iter().enumerate().min_by_key(|(_, index)| **index).unwrap().0 {
+				0 => [t[0], t[1], t[2]],
+				1 => [t[1], t[2], t[0]],
+				_ => [t[2], t[0], t[1]],
+			}
+		}).collect::<Vec<[u16; 3]>>();
+		triangles.sort();
+		triangles
+	}
+
+	#[test]
+	fn merge_vertices_welds_quad() {
+		let mut pools = ResourcePools::new();
+		let geometry = create_geometry(&mut pools, vec![
+			0.0, 0.0, 0.0,
+			1.0, 0.0, 0.0,
+			1.0, 1.0, 0.0,
+			0.0, 0.0, 0.0,
+			1.0, 1.0, 0.0,
+			0.0, 1.0, 0.0,
+		], None);
+
+		assert!(GeometryProcessor::merge_vertices(&mut pools, &geometry, 0.0));
+
+		let positions = borrow_positions(&pools, &geometry);
+		let indices = borrow_indices(&pools, &geometry);
+		assert_eq!(positions.len(), 4 * 3);
+		assert_eq!(indices.len(), 6);
+		assert_eq!(indices, &vec![0, 1, 2, 0, 2, 3]);
+	}
+
+	#[test]
+	fn merge_geometries_offsets_indices_and_groups() {
+		let mut pools = ResourcePools::new();
+		let geometry1 = create_geometry(&mut pools, vec![
+			0.0, 0.0, 0.0,
+			1.0, 0.0, 0.0,
+			1.0, 1.0, 0.0,
+			0.0, 1.0, 0.0,
+		], Some(vec![0, 1, 2, 0, 2, 3]));
+		pools.borrow_mut::<Geometry>().borrow_mut(&geometry1).unwrap()
+			.add_group(0, 3, 1)
+			.add_group(3, 3, 2);
+		let geometry2 = create_geometry(&mut pools, vec![
+			0.0, 0.0, 1.0,
+			1.0, 0.0, 1.0,
+			1.0, 1.0, 1.0,
+		], Some(vec![0, 2, 1]));
+
+		let merged = GeometryProcessor::merge_geometries(&mut pools, &[geometry1, geometry2]).unwrap();
+
+		assert_eq!(borrow_positions(&pools, &merged).len(), 7 * 3);
+		assert_eq!(borrow_indices(&pools, &merged), &vec![0, 1, 2, 0, 2, 3, 4, 6, 5]);
+		assert_eq!(pools.borrow::<Geometry>().borrow(&merged).unwrap().borrow_groups(), &vec![
+			GeometryGroup {count: 3, material_index: 1, start: 0},
+			GeometryGroup {count: 3, material_index: 2, start: 3},
+			GeometryGroup {count: 3, material_index: 0, start: 6},
+		]);
+	}
+
+	#[test]
+	fn optimize_vertex_cache_keeps_triangles() {
+		let mut pools = ResourcePools::new();
+		let segments = 8;
+		let mut positions = Vec::new();
+		for y in 0..=segments {
+			for x in 0..=segments {
+				positions.push(x as f32);
+				positions.push(y as f32);
+				positions.push(0.0);
+			}
+		}
+		let mut indices = Vec::new();
+		for y in 0..segments {
+			for x in 0..segments {
+				let a = y * (segments + 1) + x;
+				let b = a + 1;
+				let c = a + segments + 1;
+				let d = c + 1;
+				indices.extend_from_slice(&[a, b, d, a, d, c]);
+			}
+		}
+		let geometry = create_geometry(&mut pools, positions, Some(indices.clone()));
+
+		assert!(GeometryProcessor::optimize_vertex_cache(&mut pools, &geometry));
+
+		let optimized = borrow_indices(&pools, &geometry);
+		assert_eq!(optimized.len(), indices.len());
+		assert_eq!(get_triangles(optimized), get_triangles(&indices));
+	}
+}
@@ -26,6 +26,7 @@ use crate::{
 			tangent_to_object_normal::TangentToObjectNormalNode,
 			texture::TextureNode,
 			vector3::Vector3Node,
+			vertex_color::VertexColorNode,
 			xyz::XYZNode,
 			y::YNode,
 			z::ZNode,
@@ -59,6 +60,7 @@ use crate::{
 	},
 	utils::{
 		file_loader::FileLoader,
+		tangent_generator::TangentGenerator,
 		texture_loader::TextureLoader,
 	},
 };
@@ -71,55 +73,94 @@ async fn parse_attribute(
 	let (semantic, accessor) = primitive;
 	use gltf::mesh::Semantic;
 	if let Some(view) = accessor.view() {
-		let offset = view.offset();
-		let length = view.length();
+		let offset = view.offset() + accessor.offset();
+		let count = accessor.count();
+		let element_size = accessor.size();
+		let stride = view.stride().unwrap_or(element_size);
+		let length = match count {
+			0 => 0,
+			_ => (count - 1) * stride + element_size,
+		};
 		let buffer = view.buffer();
 
 		use gltf::buffer::Source;
 		use std::io::{Read, Seek, SeekFrom};
-		let data = match buffer.source() {
+		let bytes = match buffer.source() {
 			Source::Bin => {
 				panic!("Bin is not supported yet");
 			},
 			Source::Uri(uri) => {
-				let mut buf = [0_u8; 4];
-				let mut data = Vec::<f32>::new();
+				let mut bytes = vec![0_u8; length];
 				let mut file = FileLoader::open(
 					&(path.to_owned() + uri),
 				).await;
-				for i in 0..(length / 4) {
-					file.seek(SeekFrom::Start((offset + i * 4) as u64)).unwrap();
-					file.read_exact(&mut buf).unwrap();
-					data.push(f32::from_le_bytes(buf));
-				}
-				data
+				file.seek(SeekFrom::Start(offset as u64)).unwrap();
+				file.read_exact(&mut bytes).unwrap();
+				bytes
 			}
 		};
 
-		let (name, attribute) = match semantic {
-			Semantic::Normals => {(
-				"normal",
-				Attribute::new(data, 3),
-			)},
-			Semantic::Positions => {(
-				"position",
-				Attribute::new(data, 3),
-			)},
-			Semantic::TexCoords(_) => {(
-				"uv",
-				Attribute::new(data, 2),
-			)},
+		// Removes the gaps between interleaved elements
+		let mut data = Vec::with_capacity(count * element_size);
+		for i in 0..count {
+			data.extend_from_slice(&bytes[i * stride..i * stride + element_size]);
+		}
+
+		let item_size = accessor.dimensions().multiplicity() as u32;
+
+		let name = match semantic {
+			Semantic::Colors(0) => "color",
+			Semantic::Normals => "normal",
+			Semantic::Positions => "position",
+			Semantic::Tangents => "tangent",
+			Semantic::TexCoords(_) => "uv",
 			_ => {
 				panic!("Unsupport accessor semantic.");
 			},
 		};
 
+		use gltf::accessor::DataType;
+		let attribute = match (accessor.data_type(), name) {
+			(DataType::F32, _) => Attribute::new(
+				data.chunks(4).map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])).collect(),
+				item_size,
+			),
+			// Normalized integer colors are passed to the GPU as they are
+			(DataType::U8, "color") => Attribute::new_unorm8(data, item_size),
+			(DataType::U16, "color") => Attribute::new_unorm16(
+				data.chunks(2).map(|b| u16::from_le_bytes([b[0], b[1]])).collect(),
+				item_size,
+			),
+			// The other attributes have float vertex layouts,
+			// e.g. normalized integer uvs
+			(DataType::U8, _) => Attribute::new(
+				data.iter().map(|b| to_float(*b as f32, u8::MAX as f32, accessor.normalized())).collect(),
+				item_size,
+			),
+			(DataType::U16, _) => Attribute::new(
+				data.chunks(2).map(|b| {
+					to_float(u16::from_le_bytes([b[0], b[1]]) as f32, u16::MAX as f32, accessor.normalized())
+				}).collect(),
+				item_size,
+			),
+			_ => {
+				panic!("Unsupported accessor data type.");
+			},
+		};
+
 		(name, pools.borrow_mut::<Attribute>().add(attribute))
 	} else {
 		panic!("Sparse accessor is not supported yet.");
 	}
 }
 
+fn to_float(value: f32, max: f32, normalized: bool) -> f32 {
+	match normalized {
+		true => value / max,
+		false => value,
+	}
+}
+
 async fn parse_geometry(
 	pools: &mut ResourcePools,
 	path: &str,
@@ -144,6 +185,7 @@ async fn parse_material(
 	pools: &mut ResourcePools,
 	path: &str,
 	material_def: &gltf::Material<'_>,
+	vertex_color: bool,
 ) -> ResourceId<Material> {
 	let pbr_metallic_roughness = material_def.pbr_metallic_roughness();
 
@@ -175,6 +217,24 @@ async fn parse_material(
 		base_color
 	};
 
+	// Vertex color, multiplied into base color
+
+	let base_color = if vertex_color {
+		let color = pools.borrow_mut::<Box<dyn MaterialNode>>().add(
+			Box::new(VertexColorNode::new()),
+		);
+
+		let color_rgb = pools.borrow_mut::<Box<dyn MaterialNode>>().add(
+			Box::new(XYZNode::new(color)),
+		);
+
+		pools.borrow_mut::<Box<dyn MaterialNode>>().add(
+			Box::new(MultiplyNode::new(base_color, color_rgb))
+		)
+	} else {
+		base_color
+	};
+
 	// Metallic/Roughness
 
 	let metallic_factor = pbr_metallic_roughness.metallic_factor();
@@ -330,8 +390,9 @@ async fn parse_node(
 		},
 	};
 
-	let node = pools.borrow_mut::<Node>().add(Node::new());
+	let node = pools.borrow_mut::<Node>().add(node);
 
+	// A mesh per primitive, all of them assigned to the node
 	if let Some(mesh_def) = node_def.mesh() {
 		for primitive_def in mesh_def.primitives() {
 			let (geometry, material) = parse_primitive(pools, path, &primitive_def).await;
@@ -393,9 +454,23 @@ async fn parse_primitive(
 	path: &str,
 	primitive_def: &gltf::Primitive<'_>,
 ) -> (ResourceId<Geometry>, ResourceId<Material>) {
+	let geometry = parse_geometry(pools, path, primitive_def).await;
+
+	// The spec requires MikkTSpace tangents if normal map is used
+	// without tangents
+	if primitive_def.material().normal_texture().is_some() &&
+		primitive_def.get(&gltf::mesh::Semantic::Tangents).is_none() {
+		TangentGenerator::generate(pools, &geometry);
+	}
+
 	(
-		parse_geometry(pools, path, primitive_def).await,
-		parse_material(pools, path, &primitive_def.material()).await
+		geometry,
+		parse_material(
+			pools,
+			path,
+			&primitive_def.material(),
+			primitive_def.get(&gltf::mesh::Semantic::Colors(0)).is_some(),
+		).await
 	)
 }
 
@@ -1,6 +1,15 @@
+pub mod clock;
 pub mod file_loader;
+pub mod fly_controls;
 pub mod log;
 pub mod geometry_helper;
+pub mod geometry_processor;
 pub mod gltf_loader;
 pub mod material_helper;
+pub mod obj_loader;
+pub mod orbit_controls;
+pub mod ply_loader;
+pub mod raycaster;
+pub mod stl_loader;
+pub mod tangent_generator;
 pub mod texture_loader;
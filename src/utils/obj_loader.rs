@@ -0,0 +1,876 @@
+use std::{
+	collections::HashMap,
+	io::{
+		Error,
+		ErrorKind,
+		Read,
+		Result,
+	},
+};
+
+use crate::{
+	geometry::{
+		attribute::Attribute,
+		geometry::Geometry,
+		index::Index,
+	},
+	material::{
+		material::{
+			Material,
+			Side,
+		},
+		node::{
+			add::AddNode,
+			brdf::{
+				BRDFNode,
+				BRDFNodeDescriptor,
+			},
+			const_float::ConstFloatNode,
+			float::FloatNode,
+			multiply::MultiplyNode,
+			node::MaterialNode,
+			normal::NormalNode,
+			sub::SubNode,
+			tangent_to_object_normal::TangentToObjectNormalNode,
+			texture::TextureNode,
+			vector3::Vector3Node,
+			xyz::XYZNode,
+		},
+	},
+	math::vector3::Vector3,
+	resource::resource::{
+		ResourceId,
+		ResourcePools,
+	},
+	scene::{
+		mesh::Mesh,
+		node::Node,
+		scene::Scene,
+	},
+	texture::{
+		sampler::{
+			Sampler,
+			SamplerDescriptor,
+			WrapMode,
+		},
+		texture::{
+			Texture,
+			TextureFormat,
+		},
+	},
+	utils::{
+		file_loader::FileLoader,
+		tangent_generator::TangentGenerator,
+		texture_loader::TextureLoader,
+	},
+};
+
+// Position, uv and normal indices of a face vertex, zero based
+type ObjVertex = (usize, Option<usize>, Option<usize>);
+
+struct ObjFace {
+	// Zero is off
+	smoothing_group: u32,
+	vertices: Vec<ObjVertex>,
+}
+
+// Faces of the same material in a group
+struct ObjChunk {
+	faces: Vec<ObjFace>,
+	material: Option<String>,
+}
+
+// Faces of the same o or g group, a chunk per material
+struct ObjObject {
+	chunks: Vec<ObjChunk>,
+}
+
+struct ObjData {
+	material_libraries: Vec<String>,
+	normals: Vec<[f32; 3]>,
+	objects: Vec<ObjObject>,
+	positions: Vec<[f32; 3]>,
+	uvs: Vec<[f32; 2]>,
+}
+
+struct MtlMaterial {
+	bump_map: Option<String>,
+	diffuse: [f32; 3],
+	diffuse_map: Option<String>,
+	emissive: [f32; 3],
+	// Parsed from d or Tr so that the data is kept, but not applied yet
+	// @TODO: Apply once Material supports transparency
+	#[allow(dead_code)]
+	opacity: f32,
+	shininess: f32,
+	specular: [f32; 3],
+}
+
+impl Default for MtlMaterial {
+	fn default() -> Self {
+		MtlMaterial {
+			bump_map: None,
+			diffuse: [0.8, 0.8, 0.8],
+			diffuse_map: None,
+			emissive: [0.0, 0.0, 0.0],
+			opacity: 1.0,
+			shininess: 0.0,
+			specular: [0.0, 0.0, 0.0],
+		}
+	}
+}
+
+fn invalid_data(message: String) -> Error {
+	Error::new(ErrorKind::InvalidData, message)
+}
+
+// Missing values are zero
+fn parse_floats<const N: usize>(tokens: &[&str]) -> Result<[f32; N]> {
+	let mut values = [0.0; N];
+	for (value, token) in values.iter_mut().zip(tokens.iter()) {
+		*value = token.parse().map_err(|_| invalid_data(format!("Invalid number {}", token)))?;
+	}
+	Ok(values)
+}
+
+// OBJ indices are one based, and negative ones are relative to the end.
+// count is the number of the items defined so far. None if the token
+// is empty.
+fn parse_index(token: &str, count: usize) -> Result<Option<usize>> {
+	if token.is_empty() {
+		return Ok(None);
+	}
+	let index = token.parse::<i64>().map_err(|_| invalid_data(format!("Invalid index {}", token)))?;
+	let index = match index < 0 {
+		true => count as i64 + index,
+		false => index - 1,
+	};
+	match index >= 0 && index < count as i64 {
+		true => Ok(Some(index as usize)),
+		false => Err(invalid_data(format!("Index {} is out of range", token))),
+	}
+}
+
+fn parse_obj(text: &str) -> Result<ObjData> {
+	let mut data = ObjData {
+		material_libraries: Vec::new(),
+		normals: Vec::new(),
+		objects: Vec::new(),
+		positions: Vec::new(),
+		uvs: Vec::new(),
+	};
+
+	let mut chunks = Vec::new();
+	let mut faces = Vec::new();
+	let mut material = None;
+	let mut smoothing_group = 0;
+
+	// Starts a new chunk if the current one has faces
+	let flush_chunk = |chunks: &mut Vec<ObjChunk>, faces: &mut Vec<ObjFace>, material: &Option<String>| {
+		if !faces.is_empty() {
+			chunks.push(ObjChunk {
+				faces: std::mem::take(faces),
+				material: material.clone(),
+			});
+		}
+	};
+
+	// Starts a new object if the current one has chunks
+	let flush_object = |data: &mut ObjData, chunks: &mut Vec<ObjChunk>| {
+		if !chunks.is_empty() {
+			data.objects.push(ObjObject {
+				chunks: std::mem::take(chunks),
+			});
+		}
+	};
+
+	// Lines ending with backslash continue to the next line
+	let text = text.replace("\\\r\n", " ").replace("\\\n", " ");
+
+	for line in text.lines() {
+		let line = match line.find('#') {
+			Some(position) => &line[..position],
+			None => line,
+		};
+		let tokens = line.split_whitespace().collect::<Vec<&str>>();
+		if tokens.is_empty() {
+			continue;
+		}
+
+		match tokens[0] {
+			"v" => data.positions.push(parse_floats(&tokens[1..])?),
+			"vn" => data.normals.push(parse_floats(&tokens[1..])?),
+			"vt" => {
+				// OBJ v is upward, the renderer v is downward
+				let uv: [f32; 2] = parse_floats(&tokens[1..])?;
+				data.uvs.push([uv[0], 1.0 - uv[1]]);
+			},
+			"f" => {
+				let mut vertices = Vec::with_capacity(tokens.len() - 1);
+				for token in tokens[1..].iter() {
+					let mut indices = token.split('/');
+					let position = match parse_index(indices.next().unwrap_or(""), data.positions.len())? {
+						Some(position) => position,
+						None => return Err(invalid_data(format!("Face vertex {} has no position", token))),
+					};
+					let uv = match indices.next() {
+						Some(index) => parse_index(index, data.uvs.len())?,
+						None => None,
+					};
+					let normal = match indices.next() {
+						Some(index) => parse_index(index, data.normals.len())?,
+						None => None,
+					};
+					vertices.push((position, uv, normal));
+				}
+
+				if vertices.len() >= 3 {
+					faces.push(ObjFace {
+						smoothing_group,
+						vertices,
+					});
+				}
+			},
+			"o" | "g" => {
+				flush_chunk(&mut chunks, &mut faces, &material);
+				flush_object(&mut data, &mut chunks);
+			},
+			"s" => {
+				smoothing_group = match tokens.get(1) {
+					Some(&"off") | None => 0,
+					Some(group) => group.parse().unwrap_or(1),
+				};
+			},
+			"usemtl" => {
+				flush_chunk(&mut chunks, &mut faces, &material);
+				material = match tokens.len() > 1 {
+					true => Some(tokens[1..].join(" ")),
+					false => None,
+				};
+			},
+			"mtllib" => {
+				data.material_libraries.push(tokens[1..].join(" "));
+			},
+			// Lines, points, free-form geometries and so on
+			_ => {},
+		};
+	}
+
+	flush_chunk(&mut chunks, &mut faces, &material);
+	flush_object(&mut data, &mut chunks);
+	Ok(data)
+}
+
+// The texture file name follows the options
+fn parse_map_file_name(tokens: &[&str]) -> Option<String> {
+	let mut i = 0;
+	while i < tokens.len() && tokens[i].starts_with('-') {
+		i += match tokens[i] {
+			"-o" | "-s" | "-t" => 4,
+			"-mm" => 3,
+			_ => 2,
+		};
+	}
+	match i < tokens.len() {
+		true => Some(tokens[i..].join(" ").replace('\\', "/")),
+		false => None,
+	}
+}
+
+// Tr is transparency, the complement of d. -halo option of d is ignored.
+fn parse_mtl(text: &str) -> Result<HashMap<String, MtlMaterial>> {
+	let mut materials = HashMap::new();
+	let mut name = None;
+	let mut material = MtlMaterial::default();
+
+	for line in text.lines() {
+		let line = match line.find('#') {
+			Some(position) => &line[..position],
+			None => line,
+		};
+		let tokens = line.split_whitespace().collect::<Vec<&str>>();
+		if tokens.is_empty() {
+			continue;
+		}
+
+		match tokens[0] {
+			"newmtl" => {
+				if let Some(name) = name.take() {
+					materials.insert(name, std::mem::take(&mut material));
+				}
+				name = Some(tokens[1..].join(" "));
+			},
+			"Kd" => material.diffuse = parse_floats(&tokens[1..])?,
+			"Ks" => material.specular = parse_floats(&tokens[1..])?,
+			"Ke" => material.emissive = parse_floats(&tokens[1..])?,
+			"Ns" => material.shininess = parse_floats::<1>(&tokens[1..])?[0],
+			"d" => {
+				let tokens = match tokens.get(1) {
+					Some(&"-halo") => &tokens[2..],
+					_ => &tokens[1..],
+				};
+				material.opacity = parse_floats::<1>(tokens)?[0];
+			},
+			"Tr" => material.opacity = 1.0 - parse_floats::<1>(&tokens[1..])?[0],
+			"map_Kd" => material.diffuse_map = parse_map_file_name(&tokens[1..]),
+			"map_Bump" | "map_bump" | "bump" | "norm" => material.bump_map = parse_map_file_name(&tokens[1..]),
+			_ => {},
+		};
+	}
+
+	if let Some(name) = name {
+		materials.insert(name, material);
+	}
+
+	Ok(materials)
+}
+
+// Ear clipping on the plane of the polygon, so that concave polygons
+// are handled. Returns the triangles as the indices of the polygon vertices.
+fn triangulate(positions: &[[f32; 3]], polygon: &[usize]) -> Vec<[usize; 3]> {
+	let count = polygon.len();
+	if count == 3 {
+		return vec![[0, 1, 2]];
+	}
+
+	// Newell's method
+	let mut normal = Vector3::create();
+	for i in 0..count {
+		let current = &positions[polygon[i]];
+		let next = &positions[polygon[(i + 1) % count]];
+		normal[0] += (current[1] - next[1]) * (current[2] + next[2]);
+		normal[1] += (current[2] - next[2]) * (current[0] + next[0]);
+		normal[2] += (current[0] - next[0]) * (current[1] + next[1]);
+	}
+
+	// Projects to the axis plane closest to the polygon plane,
+	// keeping counter-clockwise polygons counter-clockwise
+	let axis = (0..3).max_by(|a, b| normal[*a].abs().total_cmp(&normal[*b].abs())).unwrap();
+	let (u_axis, v_axis) = match normal[axis] >= 0.0 {
+		true => ((axis + 1) % 3, (axis + 2) % 3),
+		false => ((axis + 2) % 3, (axis + 1) % 3),
+	};
+	let points = polygon.iter().map(|index| {
+		[positions[*index][u_axis], positions[*index][v_axis]]
+	}).collect::<Vec<[f32; 2]>>();
+
+	let cross = |a: &[f32; 2], b: &[f32; 2], c: &[f32; 2]| {
+		(b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])
+	};
+
+	let mut triangles = Vec::with_capacity(count - 2);
+	let mut remaining = (0..count).collect::<Vec<usize>>();
+	while remaining.len() > 3 {
+		let length = remaining.len();
+		let ear = (0..length).find(|i| {
+			let prev = remaining[(i + length - 1) % length];
+			let current = remaining[*i];
+			let next = remaining[(i + 1) % length];
+			let (a, b, c) = (&points[prev], &points[current], &points[next]);
+			cross(a, b, c) > 0.0 && remaining.iter().all(|index| {
+				*index == prev || *index == current || *index == next ||
+					cross(a, b, &points[*index]) < 0.0 ||
+					cross(b, c, &points[*index]) < 0.0 ||
+					cross(c, a, &points[*index]) < 0.0
+			})
+		});
+
+		match ear {
+			Some(i) => {
+				triangles.push([
+					remaining[(i + length - 1) % length],
+					remaining[i],
+					remaining[(i + 1) % length],
+				]);
+				remaining.remove(i);
+			},
+			// Degenerate or self-intersecting polygon, falls back to fan
+			None => {
+				for i in 1..length - 1 {
+					triangles.push([remaining[0], remaining[i], remaining[i + 1]]);
+				}
+				return triangles;
+			},
+		};
+	}
+
+	triangles.push([remaining[0], remaining[1], remaining[2]]);
+	triangles
+}
+
+// Faces without normals get area weighted face normals, shared between
+// faces in the same smoothing group, or flat if smoothing is off.
+// The chunks are drawn with geometry groups whose material indices are
+// the chunk indices. Returns the geometry and whether each chunk has uvs.
+fn build_geometry(
+	pools: &mut ResourcePools,
+	data: &ObjData,
+	object: &ObjObject,
+) -> (ResourceId<Geometry>, Vec<bool>) {
+	// Position, uv, normal, smoothing group and face index for flat faces
+	let mut vertex_map = HashMap::<(usize, Option<usize>, Option<usize>, u32, usize), u32>::new();
+	let mut positions = Vec::new();
+	let mut normals = Vec::new();
+	let mut uvs = Vec::new();
+	let mut has_normals = Vec::new();
+	let mut indices = Vec::<u32>::new();
+	let mut groups = Vec::new();
+	let mut chunk_has_uvs = Vec::new();
+	let mut face_index = 0;
+
+	for chunk in object.chunks.iter() {
+		let group_start = indices.len();
+		let mut has_uvs = false;
+
+		for face in chunk.faces.iter() {
+			let mut face_indices = Vec::with_capacity(face.vertices.len());
+			for (position, uv, normal) in face.vertices.iter() {
+				let key = match (normal, face.smoothing_group) {
+					(Some(_), _) => (*position, *uv, *normal, 0, 0),
+					(None, 0) => (*position, *uv, None, 0, face_index),
+					(None, group) => (*position, *uv, None, group, 0),
+				};
+				let index = *vertex_map.entry(key).or_insert_with(|| {
+					positions.extend_from_slice(&data.positions[*position]);
+					normals.extend_from_slice(&match normal {
+						Some(normal) => data.normals[*normal],
+						None => [0.0; 3],
+					});
+					uvs.extend_from_slice(&match uv {
+						Some(uv) => data.uvs[*uv],
+						None => [0.0; 2],
+					});
+					has_normals.push(normal.is_some());
+					has_normals.len() as u32 - 1
+				});
+				has_uvs = has_uvs || uv.is_some();
+				face_indices.push(index);
+			}
+
+			let polygon = face.vertices.iter().map(|vertex| vertex.0).collect::<Vec<usize>>();
+			for triangle in triangulate(&data.positions, &polygon) {
+				for i in triangle.iter() {
+					indices.push(face_indices[*i]);
+				}
+			}
+			face_index += 1;
+		}
+
+		groups.push((group_start, indices.len() - group_start));
+		chunk_has_uvs.push(has_uvs);
+	}
+
+	// Computes the missing normals
+	if has_normals.iter().any(|has_normal| !has_normal) {
+		let mut face_normal = Vector3::create();
+		let mut edge = Vector3::create();
+		for triangle in indices.chunks(3) {
+			let position = |index: u32| {
+				let offset = index as usize * 3;
+				[positions[offset], positions[offset + 1], positions[offset + 2]]
+			};
+			let a = position(triangle[0]);
+			Vector3::copy(&mut face_normal, &position(triangle[1]));
+			Vector3::sub(&mut face_normal, &a);
+			Vector3::copy(&mut edge, &position(triangle[2]));
+			Vector3::sub(&mut edge, &a);
+			Vector3::cross(&mut face_normal, &edge);
+			for index in triangle.iter() {
+				if !has_normals[*index as usize] {
+					let offset = *index as usize * 3;
+					for i in 0..3 {
+						normals[offset + i] += face_normal[i];
+					}
+				}
+			}
+		}
+
+		let mut normal = Vector3::create();
+		for (index, has_normal) in has_normals.iter().enumerate() {
+			if !has_normal {
+				let offset = index * 3;
+				Vector3::set(&mut normal, normals[offset], normals[offset + 1], normals[offset + 2]);
+				Vector3::normalize(&mut normal);
+				normals[offset..offset + 3].copy_from_slice(&normal);
+			}
+		}
+	}
+
+	// Index is u16 so too many vertices are expanded to non-indexed
+	let index = match has_normals.len() <= u16::MAX as usize + 1 {
+		true => Some(indices.iter().map(|index| *index as u16).collect::<Vec<u16>>()),
+		false => {
+			let gather = |data: &Vec<f32>, item_size: usize| {
+				let mut gathered = Vec::with_capacity(indices.len() * item_size);
+				for index in indices.iter() {
+					let offset = *index as usize * item_size;
+					gathered.extend_from_slice(&data[offset..offset + item_size]);
+				}
+				gathered
+			};
+			positions = gather(&positions, 3);
+			normals = gather(&normals, 3);
+			uvs = gather(&uvs, 2);
+			None
+		},
+	};
+
+	let mut geometry = Geometry::new();
+	geometry.set_attribute("position", pools.borrow_mut::<Attribute>().add(Attribute::new(positions, 3)));
+	geometry.set_attribute("normal", pools.borrow_mut::<Attribute>().add(Attribute::new(normals, 3)));
+	geometry.set_attribute("uv", pools.borrow_mut::<Attribute>().add(Attribute::new(uvs, 2)));
+	if let Some(index) = index {
+		geometry.set_index(pools.borrow_mut::<Index>().add(Index::new(index)));
+	}
+	// De-indexed vertices are in the index order so the ranges are the same
+	for (material_index, (start, count)) in groups.iter().enumerate() {
+		geometry.add_group(*start as u32, *count as u32, material_index);
+	}
+	(pools.borrow_mut::<Geometry>().add(geometry), chunk_has_uvs)
+}
+
+async fn load_texture(
+	pools: &mut ResourcePools,
+	textures: &mut HashMap<(String, bool), ResourceId<Texture>>,
+	path: &str,
+	file_name: &str,
+	format: TextureFormat,
+) -> ResourceId<Texture> {
+	// The same file can be loaded as sRGB and linear
+	let key = (file_name.to_string(), matches!(format, TextureFormat::Uint8Srgb));
+	if let Some(texture) = textures.get(&key) {
+		return *texture;
+	}
+	let texture = TextureLoader::load_with_filepath(
+		pools,
+		&(path.to_owned() + file_name),
+		format,
+	).await;
+	textures.insert(key, texture);
+	texture
+}
+
+// Converts the Phong-like MTL parameters to the BRDF node inputs.
+// Ns maps to roughness, and zero Ks makes it fully rough.
+// @TODO: Apply opacity once Material supports transparency
+async fn build_material(
+	pools: &mut ResourcePools,
+	textures: &mut HashMap<(String, bool), ResourceId<Texture>>,
+	sampler: &ResourceId<Sampler>,
+	path: &str,
+	material_def: &MtlMaterial,
+	has_uvs: bool,
+) -> ResourceId<Material> {
+	// Base color
+
+	let base_color = pools.borrow_mut::<Box<dyn MaterialNode>>().add(Box::new(
+		Vector3Node::new(material_def.diffuse),
+	));
+
+	let base_color = match (&material_def.diffuse_map, has_uvs) {
+		(Some(file_name), true) => {
+			let texture = load_texture(pools, textures, path, file_name, TextureFormat::Uint8Srgb).await;
+
+			let texture_node = pools.borrow_mut::<Box<dyn MaterialNode>>().add(
+				Box::new(TextureNode::new(texture, *sampler)),
+			);
+
+			let texture_rgb = pools.borrow_mut::<Box<dyn MaterialNode>>().add(
+				Box::new(XYZNode::new(texture_node)),
+			);
+
+			pools.borrow_mut::<Box<dyn MaterialNode>>().add(
+				Box::new(MultiplyNode::new(base_color, texture_rgb))
+			)
+		},
+		_ => base_color,
+	};
+
+	// Metallic/Roughness
+
+	let roughness = match material_def.specular.iter().any(|value| *value > 0.0) {
+		true => 1.0 - (material_def.shininess.clamp(0.0, 1000.0) / 1000.0).sqrt(),
+		false => 1.0,
+	};
+
+	let metallic = pools.borrow_mut::<Box<dyn MaterialNode>>().add(
+		Box::new(FloatNode::new(0.0)),
+	);
+
+	let roughness = pools.borrow_mut::<Box<dyn MaterialNode>>().add(
+		Box::new(FloatNode::new(roughness)),
+	);
+
+	// Normal, bump map is regarded as tangent space normal map
+	// as most of the assets do
+
+	let normal = match (&material_def.bump_map, has_uvs) {
+		(Some(file_name), true) => {
+			let texture = load_texture(pools, textures, path, file_name, TextureFormat::default()).await;
+
+			let texture_node = pools.borrow_mut::<Box<dyn MaterialNode>>().add(
+				Box::new(TextureNode::new(texture, *sampler)),
+			);
+
+			let texture_rgb = pools.borrow_mut::<Box<dyn MaterialNode>>().add(
+				Box::new(XYZNode::new(texture_node)),
+			);
+
+			let const_2 = pools.borrow_mut::<Box<dyn MaterialNode>>().add(
+				Box::new(ConstFloatNode::new(2.0)),
+			);
+
+			let const_1 = pools.borrow_mut::<Box<dyn MaterialNode>>().add(
+				Box::new(ConstFloatNode::new(1.0)),
+			);
+
+			let multiply = pools.borrow_mut::<Box<dyn MaterialNode>>().add(
+				Box::new(MultiplyNode::new(texture_rgb, const_2)),
+			);
+
+			let sub = pools.borrow_mut::<Box<dyn MaterialNode>>().add(
+				Box::new(SubNode::new(multiply, const_1)),
+			);
+
+			pools.borrow_mut::<Box<dyn MaterialNode>>().add(
+				Box::new(TangentToObjectNormalNode::new(sub)),
+			)
+		},
+		_ => pools.borrow_mut::<Box<dyn MaterialNode>>().add(Box::new(
+			NormalNode::new()
+		)),
+	};
+
+	// BRDF
+
+	let brdf = pools.borrow_mut::<Box<dyn MaterialNode>>().add(Box::new(
+		BRDFNode::new(BRDFNodeDescriptor {
+			base_color,
+			metallic,
+			normal,
+			roughness,
+		}),
+	));
+
+	// Emissive
+
+	let color = match material_def.emissive.iter().any(|value| *value > 0.0) {
+		true => {
+			let emissive = pools.borrow_mut::<Box<dyn MaterialNode>>().add(Box::new(
+				Vector3Node::new(material_def.emissive),
+			));
+
+			pools.borrow_mut::<Box<dyn MaterialNode>>().add(Box::new(
+				AddNode::new(
+					brdf,
+					emissive,
+				),
+			))
+		},
+		false => brdf,
+	};
+
+	pools.borrow_mut::<Material>().add(Material::new(color, Side::default()))
+}
+
+async fn load_text(file_path: &str) -> Result<String> {
+	let mut text = String::new();
+	FileLoader::open(file_path).await.read_to_string(&mut text)?;
+	Ok(text)
+}
+
+pub struct ObjLoader {
+}
+
+impl ObjLoader {
+	// Loads OBJ and the referred MTL files. Returns a node with a mesh
+	// per group, drawing the faces of each material with a geometry
+	// group. Faces refer to materials not in the MTL files get the
+	// default material. Malformed files, e.g. with out of range face
+	// indices, return InvalidData error.
+	pub async fn load_obj(
+		pools: &mut ResourcePools,
+		scene: &ResourceId<Scene>,
+		path: &str,
+		filename: &str,
+	) -> Result<Vec<ResourceId<Node>>> {
+		let data = parse_obj(&load_text(&(path.to_owned() + filename)).await?)?;
+
+		let mut material_defs = HashMap::new();
+		for library in data.material_libraries.iter() {
+			let library = library.replace('\\', "/");
+			material_defs.extend(parse_mtl(&load_text(&(path.to_owned() + &library)).await?)?);
+		}
+
+		let sampler = pools.borrow_mut::<Sampler>().add(Sampler::new(
+			SamplerDescriptor {
+				wrap_u: WrapMode::Repeat,
+				wrap_v: WrapMode::Repeat,
+				wrap_w: WrapMode::Repeat,
+				..SamplerDescriptor::default()
+			},
+		));
+
+		let default_material_def = MtlMaterial::default();
+		let mut materials = HashMap::new();
+		let mut textures = HashMap::new();
+		let mut nodes = Vec::new();
+
+		for object in data.objects.iter() {
+			let (geometry, chunk_has_uvs) = build_geometry(pools, &data, object);
+
+			let material_defs = object.chunks.iter().map(|chunk| {
+				chunk.material.as_ref().and_then(|name| {
+					material_defs.get(name)
+				}).unwrap_or(&default_material_def)
+			}).collect::<Vec<&MtlMaterial>>();
+
+			// Normal map requires tangents
+			if material_defs.iter().zip(chunk_has_uvs.iter()).any(|(material_def, has_uvs)| {
+				material_def.bump_map.is_some() && *has_uvs
+			}) {
+				TangentGenerator::generate(pools, &geometry);
+			}
+
+			let mut mesh_materials = Vec::new();
+			for ((chunk, material_def), has_uvs) in object.chunks.iter().zip(material_defs.iter()).zip(chunk_has_uvs.iter()) {
+				// Materials are shared between chunks with and without uvs separately
+				let key = (chunk.material.clone(), *has_uvs);
+				let material = match materials.get(&key) {
+					Some(material) => *material,
+					None => {
+						let material = build_material(
+							pools,
+							&mut textures,
+							&sampler,
+							path,
+							material_def,
+							*has_uvs,
+						).await;
+						materials.insert(key, material);
+						material
+					},
+				};
+				mesh_materials.push(material);
+			}
+
+			let node = pools.borrow_mut::<Node>().add(Node::new());
+			let mesh = pools.borrow_mut::<Mesh>().add(Mesh::new_with_materials(geometry, mesh_materials));
+			pools.borrow_mut::<Scene>().borrow_mut(scene).unwrap().assign(&node, &mesh);
+			nodes.push(node);
+		}
+
+		Ok(nodes)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{
+		geometry::geometry::{
+			Geometry,
+			GeometryGroup,
+		},
+		resource::resource::ResourcePools,
+	};
+	use super::{
+		build_geometry,
+		parse_mtl,
+		parse_obj,
+		triangulate,
+	};
+
+	#[test]
+	fn parse_obj_resolves_negative_indices() {
+		let data = parse_obj("
+v 0 0 0
+v 1 0 0
+v 1 1 0
+vt 0 0
+vt 1 0
+vt 1 1
+f -3/-3 -2/-2 -1/-1
+v 0 1 0
+f 1 -2 -1
+").unwrap();
+
+		let faces = &data.objects[0].chunks[0].faces;
+		assert_eq!(faces[0].vertices, vec![(0, Some(0), None), (1, Some(1), None), (2, Some(2), None)]);
+		assert_eq!(faces[1].vertices, vec![(0, None, None), (2, None, None), (3, None, None)]);
+		assert!(parse_obj("v 0 0 0\nf -2 1 1\n").is_err());
+	}
+
+	#[test]
+	fn triangulate_concave_quad() {
+		// Chevron with the reflex vertex at (1, 1). Fan triangulation
+		// from the first vertex would make a clockwise triangle.
+		let positions = [
+			[0.0, 0.0, 0.0],
+			[2.0, 1.0, 0.0],
+			[0.0, 2.0, 0.0],
+			[1.0, 1.0, 0.0],
+		];
+		let triangles = triangulate(&positions, &[0, 1, 2, 3]);
+		assert_eq!(triangles.len(), 2);
+
+		let mut total_area = 0.0;
+		for triangle in triangles.iter() {
+			let [a, b, c] = triangle.map(|i| positions[i]);
+			let area = ((b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])) * 0.5;
+			assert!(area > 0.0);
+			total_area += area;
+		}
+		assert_eq!(total_area, 1.0);
+	}
+
+	#[test]
+	fn usemtl_splits_faces_into_groups() {
+		let data = parse_obj("
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+usemtl a
+f 1 2 3
+usemtl b
+f 1 3 4
+usemtl a
+f 2 3 4
+").unwrap();
+
+		assert_eq!(data.objects.len(), 1);
+		let materials = data.objects[0].chunks.iter().map(|chunk| {
+			chunk.material.as_deref()
+		}).collect::<Vec<Option<&str>>>();
+		assert_eq!(materials, vec![Some("a"), Some("b"), Some("a")]);
+
+		let mut pools = ResourcePools::new();
+		let (geometry, chunk_has_uvs) = build_geometry(&mut pools, &data, &data.objects[0]);
+		assert_eq!(chunk_has_uvs, vec![false; 3]);
+		assert_eq!(pools.borrow::<Geometry>().borrow(&geometry).unwrap().borrow_groups(), &vec![
+			GeometryGroup {count: 3, material_index: 0, start: 0},
+			GeometryGroup {count: 3, material_index: 1, start: 3},
+			GeometryGroup {count: 3, material_index: 2, start: 6},
+		]);
+	}
+
+	#[test]
+	fn parse_mtl_reads_opacity() {
+		let materials = parse_mtl("
+newmtl opaque
+Kd 1 0 0
+newmtl dissolve
+d 0.25
+newmtl halo
+d -halo 0.5
+newmtl transparency
+Tr 0.25
+").unwrap();
+
+		assert_eq!(materials["opaque"].diffuse, [1.0, 0.0, 0.0]);
+		assert_eq!(materials["opaque"].opacity, 1.0);
+		assert_eq!(materials["dissolve"].opacity, 0.25);
+		assert_eq!(materials["halo"].opacity, 0.5);
+		assert_eq!(materials["transparency"].opacity, 0.75);
+	}
+}
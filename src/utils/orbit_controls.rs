@@ -0,0 +1,341 @@
+use std::collections::HashMap;
+use winit::{
+	event::{
+		ElementState,
+		ModifiersState,
+		MouseButton,
+		MouseScrollDelta,
+		TouchPhase,
+		WindowEvent,
+	},
+	window::Window,
+};
+use crate::{
+	math::{
+		euler::Euler,
+		matrix4::Matrix4,
+		vector3::Vector3,
+	},
+	resource::resource::{
+		ResourceId,
+		ResourcePools,
+	},
+	scene::node::Node,
+};
+
+const EPSILON: f32 = 0.000001;
+
+// Roughly the pixel delta of a mouse wheel notch
+const PIXELS_PER_LINE: f32 = 100.0;
+
+#[derive(Clone, Copy, PartialEq)]
+enum State {
+	None,
+	Pan,
+	Rotate,
+}
+
+// Orbits a camera Node around a target. Left drag or one finger rotates,
+// right or middle drag, Shift + left drag or two fingers pan, and wheel or
+// pinch zooms. The camera Node position and rotation are assumed to be in
+// world space, that is the Node has no transformed parent.
+//
+// Pass the window events to handle_event() and call update() once per frame
+// before updating the scene matrices. The same window events are delivered
+// on the web build so it works there as well.
+pub struct OrbitControls {
+	camera: ResourceId<Node>,
+	damping_factor: f32,
+	enable_damping: bool,
+	height: f32,
+	max_distance: f32,
+	max_polar_angle: f32,
+	min_distance: f32,
+	min_polar_angle: f32,
+	modifiers: ModifiersState,
+	// The camera is placed even without input on the first update()
+	// and after the target or the ranges change
+	needs_update: bool,
+	pan_delta: [f32; 2],
+	pan_speed: f32,
+	pointer: Option<[f32; 2]>,
+	rotate_speed: f32,
+	scale: f32,
+	spherical_delta: [f32; 2], // theta, phi
+	state: State,
+	target: [f32; 3],
+	touches: HashMap<u64, [f32; 2]>,
+	zoom_speed: f32,
+}
+
+impl OrbitControls {
+	pub fn new(window: &Window, camera: &ResourceId<Node>) -> Self {
+		OrbitControls {
+			camera: *camera,
+			damping_factor: 0.05,
+			enable_damping: false,
+			height: window.inner_size().height as f32,
+			max_distance: f32::INFINITY,
+			max_polar_angle: std::f32::consts::PI,
+			min_distance: 0.0,
+			min_polar_angle: 0.0,
+			modifiers: ModifiersState::empty(),
+			needs_update: true,
+			pan_delta: [0.0; 2],
+			pan_speed: 1.0,
+			pointer: None,
+			rotate_speed: 1.0,
+			scale: 1.0,
+			spherical_delta: [0.0; 2],
+			state: State::None,
+			target: Vector3::create(),
+			touches: HashMap::new(),
+			zoom_speed: 1.0,
+		}
+	}
+
+	pub fn borrow_target(&self) -> &[f32; 3] {
+		&self.target
+	}
+
+	pub fn set_target(&mut self, target: &[f32; 3]) -> &mut Self {
+		Vector3::copy(&mut self.target, target);
+		self.needs_update = true;
+		self
+	}
+
+	// With damping update() must be called every frame for the inertia
+	pub fn set_enable_damping(&mut self, enable_damping: bool) -> &mut Self {
+		self.enable_damping = enable_damping;
+		self
+	}
+
+	pub fn set_damping_factor(&mut self, damping_factor: f32) -> &mut Self {
+		self.damping_factor = damping_factor;
+		self
+	}
+
+	pub fn set_distance_range(&mut self, min: f32, max: f32) -> &mut Self {
+		self.min_distance = min;
+		self.max_distance = max;
+		self.needs_update = true;
+		self
+	}
+
+	// Angles from the +Y axis in radians, 0.0 to PI
+	pub fn set_polar_angle_range(&mut self, min: f32, max: f32) -> &mut Self {
+		self.min_polar_angle = min;
+		self.max_polar_angle = max;
+		self.needs_update = true;
+		self
+	}
+
+	pub fn set_pan_speed(&mut self, pan_speed: f32) -> &mut Self {
+		self.pan_speed = pan_speed;
+		self
+	}
+
+	pub fn set_rotate_speed(&mut self, rotate_speed: f32) -> &mut Self {
+		self.rotate_speed = rotate_speed;
+		self
+	}
+
+	pub fn set_zoom_speed(&mut self, zoom_speed: f32) -> &mut Self {
+		self.zoom_speed = zoom_speed;
+		self
+	}
+
+	pub fn handle_event(&mut self, event: &WindowEvent) {
+		match event {
+			WindowEvent::Resized(size) => {
+				self.height = size.height as f32;
+			},
+			WindowEvent::ModifiersChanged(modifiers) => {
+				self.modifiers = *modifiers;
+			},
+			WindowEvent::MouseInput { state, button, .. } => {
+				self.state = match (state, button) {
+					(ElementState::Pressed, MouseButton::Left) => match self.modifiers.shift() {
+						true => State::Pan,
+						false => State::Rotate,
+					},
+					(ElementState::Pressed, MouseButton::Middle) |
+					(ElementState::Pressed, MouseButton::Right) => State::Pan,
+					_ => State::None,
+				};
+			},
+			WindowEvent::CursorMoved { position, .. } => {
+				let position = [position.x as f32, position.y as f32];
+				if let Some(pointer) = self.pointer {
+					self.handle_drag(position[0] - pointer[0], position[1] - pointer[1]);
+				}
+				self.pointer = Some(position);
+			},
+			WindowEvent::CursorLeft { .. } => {
+				self.pointer = None;
+				self.state = State::None;
+			},
+			WindowEvent::MouseWheel { delta, .. } => {
+				let lines = match delta {
+					MouseScrollDelta::LineDelta(_, y) => *y,
+					MouseScrollDelta::PixelDelta(position) => position.y as f32 / PIXELS_PER_LINE,
+				};
+				self.zoom(0.95_f32.powf(self.zoom_speed * lines));
+			},
+			WindowEvent::Touch(touch) => {
+				let position = [touch.location.x as f32, touch.location.y as f32];
+				match touch.phase {
+					TouchPhase::Started => {
+						self.touches.insert(touch.id, position);
+					},
+					TouchPhase::Moved => {
+						self.handle_touch_move(touch.id, position);
+					},
+					TouchPhase::Ended | TouchPhase::Cancelled => {
+						self.touches.remove(&touch.id);
+					},
+				};
+			},
+			_ => {},
+		};
+	}
+
+	// Doesn't touch the camera Node while idle so that its world matrix
+	// and the object uniforms depending on it are not updated every frame
+	pub fn update(&mut self, pools: &mut ResourcePools) {
+		if !self.needs_update && self.is_idle() {
+			return;
+		}
+
+		let node = pools.borrow_mut::<Node>().borrow_mut(&self.camera).unwrap();
+
+		let mut offset = Vector3::create();
+		Vector3::copy(&mut offset, node.borrow_position());
+		Vector3::sub(&mut offset, &self.target);
+
+		let radius = Vector3::length(&offset);
+		let mut theta = offset[0].atan2(offset[2]);
+		let mut phi = match radius > 0.0 {
+			true => (offset[1] / radius).clamp(-1.0, 1.0).acos(),
+			false => std::f32::consts::FRAC_PI_2,
+		};
+
+		let factor = match self.enable_damping {
+			true => self.damping_factor,
+			false => 1.0,
+		};
+
+		theta += self.spherical_delta[0] * factor;
+		phi += self.spherical_delta[1] * factor;
+		phi = phi.clamp(
+			self.min_polar_angle.max(EPSILON),
+			self.max_polar_angle.min(std::f32::consts::PI - EPSILON),
+		);
+		let radius = (radius * self.scale).clamp(self.min_distance, self.max_distance);
+
+		// Looks at the target from the spherical coordinate
+		let mut rotation = Matrix4::create();
+		let mut pitch = Matrix4::create();
+		Matrix4::make_rotation_y(&mut rotation, theta);
+		Matrix4::make_rotation_x(&mut pitch, phi - std::f32::consts::FRAC_PI_2);
+		let yaw = rotation;
+		Matrix4::multiply(&mut rotation, &yaw, &pitch);
+
+		// Pans along the camera x and y axes, proportionally to the distance
+		// so that the target roughly follows the pointer
+		let pan_scale = radius * self.pan_speed * factor;
+		let mut pan = [rotation[0], rotation[1], rotation[2]];
+		Vector3::scale(&mut pan, -self.pan_delta[0] * pan_scale);
+		Vector3::add(&mut self.target, &pan);
+		let mut pan = [rotation[4], rotation[5], rotation[6]];
+		Vector3::scale(&mut pan, self.pan_delta[1] * pan_scale);
+		Vector3::add(&mut self.target, &pan);
+
+		Vector3::set(
+			&mut offset,
+			radius * phi.sin() * theta.sin(),
+			radius * phi.cos(),
+			radius * phi.sin() * theta.cos(),
+		);
+		Vector3::add(Vector3::copy(node.borrow_position_mut(), &self.target), &offset);
+		Euler::set_from_rotation_matrix(node.borrow_rotation_mut(), &rotation);
+
+		match self.enable_damping {
+			true => {
+				// Snapped to zero once the inertia is imperceptible
+				for delta in self.spherical_delta.iter_mut().chain(self.pan_delta.iter_mut()) {
+					*delta *= 1.0 - self.damping_factor;
+					if delta.abs() < EPSILON {
+						*delta = 0.0;
+					}
+				}
+			},
+			false => {
+				self.spherical_delta = [0.0; 2];
+				self.pan_delta = [0.0; 2];
+			},
+		};
+		self.scale = 1.0;
+		self.needs_update = false;
+	}
+
+	fn is_idle(&self) -> bool {
+		self.scale == 1.0 &&
+			self.spherical_delta.iter().all(|delta| *delta == 0.0) &&
+			self.pan_delta.iter().all(|delta| *delta == 0.0)
+	}
+
+	fn handle_drag(&mut self, dx: f32, dy: f32) {
+		match self.state {
+			State::Rotate => self.rotate(dx, dy),
+			State::Pan => self.pan(dx, dy),
+			State::None => {},
+		};
+	}
+
+	fn handle_touch_move(&mut self, id: u64, position: [f32; 2]) {
+		let previous = match self.touches.insert(id, position) {
+			Some(previous) => previous,
+			None => return,
+		};
+
+		match self.touches.len() {
+			1 => self.rotate(position[0] - previous[0], position[1] - previous[1]),
+			2 => {
+				// Pinch zooms and the midpoint movement pans
+				let other = *self.touches.iter().find(|(key, _)| **key != id).unwrap().1;
+				let previous_distance = distance(&previous, &other);
+				let current_distance = distance(&position, &other);
+				if current_distance > 0.0 {
+					self.zoom(previous_distance / current_distance);
+				}
+				self.pan(
+					(position[0] - previous[0]) * 0.5,
+					(position[1] - previous[1]) * 0.5,
+				);
+			},
+			_ => {},
+		};
+	}
+
+	fn rotate(&mut self, dx: f32, dy: f32) {
+		let height = self.height.max(1.0);
+		self.spherical_delta[0] -= 2.0 * std::f32::consts::PI * dx / height * self.rotate_speed;
+		self.spherical_delta[1] -= 2.0 * std::f32::consts::PI * dy / height * self.rotate_speed;
+	}
+
+	fn pan(&mut self, dx: f32, dy: f32) {
+		let height = self.height.max(1.0);
+		self.pan_delta[0] += dx / height;
+		self.pan_delta[1] += dy / height;
+	}
+
+	// Less than 1.0 zooms in
+	fn zoom(&mut self, scale: f32) {
+		self.scale *= scale;
+	}
+}
+
+fn distance(a: &[f32; 2], b: &[f32; 2]) -> f32 {
+	((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2)).sqrt()
+}
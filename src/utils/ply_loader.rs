@@ -0,0 +1,545 @@
+use std::io::{
+	Error,
+	ErrorKind,
+	Result,
+};
+
+use crate::{
+	geometry::{
+		attribute::Attribute,
+		geometry::{
+			Geometry,
+			PrimitiveTopology,
+		},
+		index::Index,
+	},
+	resource::resource::{
+		ResourceId,
+		ResourcePools,
+	},
+	utils::{
+		file_loader::FileLoader,
+		geometry_processor::GeometryProcessor,
+	},
+};
+
+enum PlyFormat {
+	Ascii,
+	BinaryBigEndian,
+	BinaryLittleEndian,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum PlyType {
+	Float32,
+	Float64,
+	Int8,
+	Int16,
+	Int32,
+	Uint8,
+	Uint16,
+	Uint32,
+}
+
+impl PlyType {
+	fn from_name(name: &str) -> Result<Self> {
+		Ok(match name {
+			"char" | "int8" => PlyType::Int8,
+			"uchar" | "uint8" => PlyType::Uint8,
+			"short" | "int16" => PlyType::Int16,
+			"ushort" | "uint16" => PlyType::Uint16,
+			"int" | "int32" => PlyType::Int32,
+			"uint" | "uint32" => PlyType::Uint32,
+			"float" | "float32" => PlyType::Float32,
+			"double" | "float64" => PlyType::Float64,
+			_ => return Err(invalid_data(&format!("Unknown PLY property type {}", name))),
+		})
+	}
+
+	fn get_size(&self) -> usize {
+		match self {
+			PlyType::Int8 | PlyType::Uint8 => 1,
+			PlyType::Int16 | PlyType::Uint16 => 2,
+			PlyType::Float32 | PlyType::Int32 | PlyType::Uint32 => 4,
+			PlyType::Float64 => 8,
+		}
+	}
+
+	// For normalizing integer colors to [0.0, 1.0]
+	fn get_max_value(&self) -> f64 {
+		match self {
+			PlyType::Int8 => i8::MAX as f64,
+			PlyType::Uint8 => u8::MAX as f64,
+			PlyType::Int16 => i16::MAX as f64,
+			PlyType::Uint16 => u16::MAX as f64,
+			PlyType::Int32 => i32::MAX as f64,
+			PlyType::Uint32 => u32::MAX as f64,
+			PlyType::Float32 | PlyType::Float64 => 1.0,
+		}
+	}
+}
+
+struct PlyProperty {
+	// Count type if list
+	list_type: Option<PlyType>,
+	name: String,
+	value_type: PlyType,
+}
+
+struct PlyElement {
+	count: usize,
+	name: String,
+	properties: Vec<PlyProperty>,
+}
+
+struct PlyBodyReader<'a> {
+	bytes: &'a [u8],
+	format: PlyFormat,
+	position: usize,
+}
+
+impl<'a> PlyBodyReader<'a> {
+	fn read(&mut self, value_type: PlyType) -> Result<f64> {
+		Ok(match self.format {
+			PlyFormat::Ascii => {
+				while self.position < self.bytes.len() && self.bytes[self.position].is_ascii_whitespace() {
+					self.position += 1;
+				}
+				let start = self.position;
+				while self.position < self.bytes.len() && !self.bytes[self.position].is_ascii_whitespace() {
+					self.position += 1;
+				}
+				if start == self.position {
+					return Err(invalid_data("PLY file is truncated"));
+				}
+				std::str::from_utf8(&self.bytes[start..self.position]).ok()
+					.and_then(|token| token.parse().ok())
+					.ok_or_else(|| invalid_data("PLY has an invalid number"))?
+			},
+			_ => {
+				let size = value_type.get_size();
+				if self.position + size > self.bytes.len() {
+					return Err(invalid_data("PLY file is truncated"));
+				}
+				let mut buf = [0_u8; 8];
+				buf[..size].copy_from_slice(&self.bytes[self.position..self.position + size]);
+				if let PlyFormat::BinaryBigEndian = self.format {
+					buf[..size].reverse();
+				}
+				self.position += size;
+				match value_type {
+					PlyType::Int8 => buf[0] as i8 as f64,
+					PlyType::Uint8 => buf[0] as f64,
+					PlyType::Int16 => i16::from_le_bytes([buf[0], buf[1]]) as f64,
+					PlyType::Uint16 => u16::from_le_bytes([buf[0], buf[1]]) as f64,
+					PlyType::Int32 => i32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as f64,
+					PlyType::Uint32 => u32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as f64,
+					PlyType::Float32 => f32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as f64,
+					PlyType::Float64 => f64::from_le_bytes(buf),
+				}
+			},
+		})
+	}
+
+	// Face indices must be integers referring to the vertices
+	fn read_index(&mut self, value_type: PlyType, vertex_count: usize) -> Result<u32> {
+		let value = self.read(value_type)?;
+		if value < 0.0 || value.fract() != 0.0 || value >= vertex_count as f64 {
+			return Err(invalid_data("PLY face has an invalid vertex index"));
+		}
+		Ok(value as u32)
+	}
+}
+
+fn invalid_data(message: &str) -> Error {
+	Error::new(ErrorKind::InvalidData, message)
+}
+
+// Returns the format, the elements and the body start position
+fn parse_header(bytes: &[u8]) -> Result<(PlyFormat, Vec<PlyElement>, usize)> {
+	if !bytes.starts_with(b"ply") {
+		return Err(invalid_data("PLY magic number is not found"));
+	}
+
+	let end = match bytes.windows(10).position(|window| window == b"end_header") {
+		Some(position) => position,
+		None => return Err(invalid_data("PLY end_header is not found")),
+	};
+
+	// The body starts after the end_header line break
+	let mut body_start = end + 10;
+	while body_start < bytes.len() && bytes[body_start] != b'\n' {
+		body_start += 1;
+	}
+	body_start += 1;
+
+	let header = String::from_utf8_lossy(&bytes[..end]);
+	let mut format = PlyFormat::Ascii;
+	let mut elements = Vec::<PlyElement>::new();
+
+	for line in header.lines() {
+		let tokens = line.split_whitespace().collect::<Vec<&str>>();
+		let invalid_line = || invalid_data(&format!("PLY header has an invalid line, {}", line));
+
+		match tokens.as_slice() {
+			["format", name, ..] => {
+				format = match *name {
+					"ascii" => PlyFormat::Ascii,
+					"binary_little_endian" => PlyFormat::BinaryLittleEndian,
+					"binary_big_endian" => PlyFormat::BinaryBigEndian,
+					_ => return Err(invalid_data(&format!("Unknown PLY format {}", name))),
+				};
+			},
+			["element", name, count, ..] => {
+				elements.push(PlyElement {
+					count: count.parse().map_err(|_| invalid_line())?,
+					name: name.to_string(),
+					properties: Vec::new(),
+				});
+			},
+			["property", "list", list_type, value_type, name, ..] => {
+				elements.last_mut().ok_or_else(invalid_line)?.properties.push(PlyProperty {
+					list_type: Some(PlyType::from_name(list_type)?),
+					name: name.to_string(),
+					value_type: PlyType::from_name(value_type)?,
+				});
+			},
+			["property", "list", ..] => return Err(invalid_line()),
+			["property", value_type, name, ..] => {
+				elements.last_mut().ok_or_else(invalid_line)?.properties.push(PlyProperty {
+					list_type: None,
+					name: name.to_string(),
+					value_type: PlyType::from_name(value_type)?,
+				});
+			},
+			["format", ..] | ["element", ..] | ["property", ..] => return Err(invalid_line()),
+			// ply, comment, obj_info
+			_ => {},
+		};
+	}
+
+	Ok((format, elements, body_start))
+}
+
+fn gather<T: Copy>(data: &[T], item_size: usize, indices: &[u32]) -> Vec<T> {
+	let mut gathered = Vec::with_capacity(indices.len() * item_size);
+	if data.is_empty() {
+		return gathered;
+	}
+	for index in indices.iter() {
+		let offset = *index as usize * item_size;
+		gathered.extend_from_slice(&data[offset..offset + item_size]);
+	}
+	gathered
+}
+
+pub struct PlyLoader {
+}
+
+impl PlyLoader {
+	// Loads ASCII or binary PLY. Vertex normals, colors and uvs are loaded
+	// if the file has them. Faces are triangulated as fan and normals are
+	// computed if missing. A file without faces is loaded as a point list
+	// geometry with normals facing +Z if missing, e.g. a point cloud.
+	// Truncated or malformed files and faces referring to missing
+	// vertices return InvalidData error.
+	pub fn load_ply<R: std::io::Read>(
+		pools: &mut ResourcePools,
+		mut reader: R,
+	) -> Result<ResourceId<Geometry>> {
+		let mut bytes = Vec::new();
+		reader.read_to_end(&mut bytes)?;
+
+		let (format, elements, body_start) = parse_header(&bytes)?;
+		let vertex_count = elements.iter()
+			.find(|element| element.name == "vertex")
+			.map(|element| element.count)
+			.unwrap_or(0);
+		let mut body = PlyBodyReader {
+			bytes: &bytes[body_start..],
+			format,
+			position: 0,
+		};
+
+		let mut positions = Vec::new();
+		let mut normals = Vec::new();
+		let mut uvs = Vec::new();
+		let mut colors = Vec::new();
+		let mut indices = Vec::<u32>::new();
+
+		let mut has_normals = false;
+		let mut has_uvs = false;
+		let mut color_type = None;
+		let mut color_size = 3;
+
+		for element in elements.iter() {
+			let find = |names: &[&str]| element.properties.iter().position(|property| {
+				names.contains(&property.name.as_str())
+			});
+
+			let (position_columns, normal_columns, uv_columns, color_columns, index_column) = match element.name.as_str() {
+				"vertex" => {
+					let normal_columns = [find(&["nx"]), find(&["ny"]), find(&["nz"])];
+					let uv_columns = [find(&["u", "s", "texture_u"]), find(&["v", "t", "texture_v"])];
+					let color_columns = [
+						find(&["red", "diffuse_red"]),
+						find(&["green", "diffuse_green"]),
+						find(&["blue", "diffuse_blue"]),
+						find(&["alpha"]),
+					];
+					has_normals = normal_columns.iter().all(|column| column.is_some());
+					has_uvs = uv_columns.iter().all(|column| column.is_some());
+					if color_columns[..3].iter().all(|column| column.is_some()) {
+						color_type = Some(element.properties[color_columns[0].unwrap()].value_type);
+						if color_columns[3].is_some() {
+							color_size = 4;
+						}
+					}
+					(
+						[find(&["x"]), find(&["y"]), find(&["z"])],
+						normal_columns,
+						uv_columns,
+						color_columns,
+						None,
+					)
+				},
+				"face" => ([None; 3], [None; 3], [None; 2], [None; 4], find(&["vertex_indices", "vertex_index"])),
+				// Other elements are read and ignored
+				_ => ([None; 3], [None; 3], [None; 2], [None; 4], None),
+			};
+
+			let mut values = vec![0.0; element.properties.len()];
+			let mut list = Vec::new();
+			for _ in 0..element.count {
+				for (column, property) in element.properties.iter().enumerate() {
+					match property.list_type {
+						Some(list_type) => {
+							let count = body.read(list_type)? as usize;
+							list.clear();
+							for _ in 0..count {
+								list.push(match index_column == Some(column) {
+									true => body.read_index(property.value_type, vertex_count)? as f64,
+									false => body.read(property.value_type)?,
+								});
+							}
+							if index_column == Some(column) {
+								for i in 1..list.len().max(2) - 1 {
+									indices.push(list[0] as u32);
+									indices.push(list[i] as u32);
+									indices.push(list[i + 1] as u32);
+								}
+							}
+						},
+						None => values[column] = body.read(property.value_type)?,
+					};
+				}
+
+				if element.name != "vertex" {
+					continue;
+				}
+
+				let value = |column: Option<usize>| column.map(|column| values[column]).unwrap_or(0.0);
+				for column in position_columns.iter() {
+					positions.push(value(*column) as f32);
+				}
+				if has_normals {
+					for column in normal_columns.iter() {
+						normals.push(value(*column) as f32);
+					}
+				}
+				if has_uvs {
+					uvs.push(value(uv_columns[0]) as f32);
+					// PLY v is upward, the renderer v is downward
+					uvs.push(1.0 - value(uv_columns[1]) as f32);
+				}
+				if let Some(color_type) = color_type {
+					for column in color_columns[..color_size].iter() {
+						colors.push(value(*column) / color_type.get_max_value());
+					}
+				}
+			}
+		}
+
+		let is_points = indices.is_empty();
+
+		if !has_normals && is_points {
+			normals = [0.0, 0.0, 1.0].repeat(vertex_count);
+		}
+		if !has_uvs {
+			uvs = vec![0.0; vertex_count * 2];
+		}
+
+		// Index is u16 so too many vertices are expanded to non-indexed
+		let indexed = !is_points && vertex_count <= u16::MAX as usize + 1;
+		if !is_points && !indexed {
+			positions = gather(&positions, 3, &indices);
+			normals = gather(&normals, 3, &indices);
+			uvs = gather(&uvs, 2, &indices);
+			colors = gather(&colors, color_size, &indices);
+		}
+
+		let mut geometry = Geometry::new();
+		geometry.set_attribute("position", pools.borrow_mut::<Attribute>().add(Attribute::new(positions, 3)));
+		geometry.set_attribute("uv", pools.borrow_mut::<Attribute>().add(Attribute::new(uvs, 2)));
+		if !normals.is_empty() {
+			geometry.set_attribute("normal", pools.borrow_mut::<Attribute>().add(Attribute::new(normals, 3)));
+		}
+
+		if let Some(color_type) = color_type {
+			let color = match color_type {
+				PlyType::Uint8 => Attribute::new_unorm8(
+					colors.iter().map(|value| (value * u8::MAX as f64).round() as u8).collect(),
+					color_size as u32,
+				),
+				PlyType::Uint16 => Attribute::new_unorm16(
+					colors.iter().map(|value| (value * u16::MAX as f64).round() as u16).collect(),
+					color_size as u32,
+				),
+				_ => Attribute::new(
+					colors.iter().map(|value| *value as f32).collect(),
+					color_size as u32,
+				),
+			};
+			geometry.set_attribute("color", pools.borrow_mut::<Attribute>().add(color));
+		}
+
+		if is_points {
+			geometry.set_topology(PrimitiveTopology::PointList);
+		}
+		if indexed {
+			geometry.set_index(pools.borrow_mut::<Index>().add(Index::new(
+				indices.iter().map(|index| *index as u16).collect(),
+			)));
+		}
+
+		let geometry = pools.borrow_mut::<Geometry>().add(geometry);
+
+		if !is_points && !has_normals {
+			GeometryProcessor::compute_vertex_normals(pools, &geometry);
+		}
+
+		Ok(geometry)
+	}
+
+	pub async fn load_ply_with_filepath(
+		pools: &mut ResourcePools,
+		file_path: &str,
+	) -> Result<ResourceId<Geometry>> {
+		Self::load_ply(pools, FileLoader::open(file_path).await)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::io::ErrorKind;
+	use crate::{
+		geometry::{
+			attribute::Attribute,
+			geometry::{
+				Geometry,
+				PrimitiveTopology,
+			},
+			index::Index,
+		},
+		resource::resource::{
+			ResourceId,
+			ResourcePools,
+		},
+	};
+	use super::PlyLoader;
+
+	fn borrow_attribute_data<'a>(
+		pools: &'a ResourcePools,
+		geometry_rid: &ResourceId<Geometry>,
+		name: &'static str,
+	) -> &'a Vec<f32> {
+		let geometry = pools.borrow::<Geometry>().borrow(geometry_rid).unwrap();
+		pools.borrow::<Attribute>().borrow(geometry.borrow_attribute(name).unwrap()).unwrap().borrow_data()
+	}
+
+	// A triangle with uchar vertex count and int vertex indices
+	fn create_binary_big_endian(indices: &[i32; 3]) -> Vec<u8> {
+		let mut bytes = b"ply
+format binary_big_endian 1.0
+element vertex 3
+property float x
+property float y
+property float z
+element face 1
+property list uchar int vertex_indices
+end_header
+".to_vec();
+		for value in [0.0_f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0].iter() {
+			bytes.extend_from_slice(&value.to_be_bytes());
+		}
+		bytes.push(3);
+		for index in indices.iter() {
+			bytes.extend_from_slice(&index.to_be_bytes());
+		}
+		bytes
+	}
+
+	#[test]
+	fn load_ascii_point_cloud() {
+		let mut pools = ResourcePools::new();
+		let text = "ply
+format ascii 1.0
+comment point cloud
+element vertex 3
+property float x
+property float y
+property float z
+end_header
+0 0 0
+1 0 0
+0 1 0.5
+";
+		let geometry = PlyLoader::load_ply(&mut pools, text.as_bytes()).unwrap();
+
+		{
+			let geometry = pools.borrow::<Geometry>().borrow(&geometry).unwrap();
+			assert_eq!(*geometry.borrow_topology(), PrimitiveTopology::PointList);
+			assert!(geometry.borrow_index().is_none());
+		}
+		assert_eq!(borrow_attribute_data(&pools, &geometry, "position"), &vec![
+			0.0, 0.0, 0.0,
+			1.0, 0.0, 0.0,
+			0.0, 1.0, 0.5,
+		]);
+		assert_eq!(borrow_attribute_data(&pools, &geometry, "normal"), &[0.0, 0.0, 1.0].repeat(3));
+	}
+
+	#[test]
+	fn load_binary_big_endian() {
+		let mut pools = ResourcePools::new();
+		let bytes = create_binary_big_endian(&[0, 1, 2]);
+		let geometry = PlyLoader::load_ply(&mut pools, bytes.as_slice()).unwrap();
+
+		assert_eq!(borrow_attribute_data(&pools, &geometry, "position"), &vec![
+			0.0, 0.0, 0.0,
+			1.0, 0.0, 0.0,
+			0.0, 1.0, 0.0,
+		]);
+		assert_eq!(borrow_attribute_data(&pools, &geometry, "normal"), &[0.0, 0.0, 1.0].repeat(3));
+
+		let geometry = pools.borrow::<Geometry>().borrow(&geometry).unwrap();
+		let index = pools.borrow::<Index>().borrow(geometry.borrow_index().unwrap()).unwrap();
+		assert_eq!(index.borrow_data(), &vec![0, 1, 2]);
+	}
+
+	#[test]
+	fn malformed_file_is_invalid_data() {
+		let mut pools = ResourcePools::new();
+
+		let bytes = create_binary_big_endian(&[0, 1, 3]);
+		let error = PlyLoader::load_ply(&mut pools, bytes.as_slice()).unwrap_err();
+		assert_eq!(error.kind(), ErrorKind::InvalidData);
+
+		let bytes = create_binary_big_endian(&[0, 1, 2]);
+		let error = PlyLoader::load_ply(&mut pools, &bytes[..bytes.len() - 1]).unwrap_err();
+		assert_eq!(error.kind(), ErrorKind::InvalidData);
+
+		let error = PlyLoader::load_ply(&mut pools, &b"obj\nend_header\n"[..]).unwrap_err();
+		assert_eq!(error.kind(), ErrorKind::InvalidData);
+	}
+}
@@ -0,0 +1,320 @@
+use std::collections::HashMap;
+
+use crate::{
+	geometry::{
+		attribute::{
+			Attribute,
+			AttributeData,
+		},
+		geometry::{
+			Geometry,
+			PrimitiveTopology,
+		},
+		index::Index,
+	},
+	material::material::{
+		Material,
+		Side,
+	},
+	math::{
+		matrix4::Matrix4,
+		ray::Ray,
+		sphere::Sphere,
+		vector3::Vector3,
+	},
+	resource::resource::{
+		ResourceId,
+		ResourcePools,
+	},
+	scene::{
+		camera::Camera,
+		mesh::Mesh,
+		node::Node,
+		scene::Scene,
+	},
+	utils::geometry_processor::GeometryProcessor,
+};
+
+pub struct Intersection {
+	// From the ray origin in world space
+	pub distance: f32,
+	// Triangle index in the geometry
+	pub face_index: usize,
+	// Index of the mesh material the face is drawn with
+	pub material_index: usize,
+	pub mesh: ResourceId<Mesh>,
+	pub node: ResourceId<Node>,
+	// In world space
+	pub point: [f32; 3],
+	// Barycentric coordinates of the second and third vertices of the face
+	pub uv: [f32; 2],
+}
+
+// Finds the mesh triangles a world space ray hits on CPU, for picking and
+// line of sight queries. Node world matrices need to be updated beforehand.
+// Faces are tested as the material Side culls them. Only triangle list
+// geometries are tested. Invisible nodes and nodes sharing no layer with
+// the raycaster are skipped.
+//
+// Bounding spheres are computed once per position attribute and cached.
+// Attributes are replaced rather than edited in place, so the cache
+// doesn't get stale.
+// @TODO: Support InstancedMesh
+pub struct Raycaster {
+	bounding_spheres: HashMap<ResourceId<Attribute>, [f32; 4]>,
+	far: f32,
+	layers: u32,
+	near: f32,
+	ray: [f32; 6],
+}
+
+impl Default for Raycaster {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl Raycaster {
+	pub fn new() -> Self {
+		Raycaster {
+			bounding_spheres: HashMap::new(),
+			far: f32::INFINITY,
+			layers: u32::MAX,
+			near: 0.0,
+			ray: Ray::create(),
+		}
+	}
+
+	pub fn borrow_ray(&self) -> &[f32; 6] {
+		&self.ray
+	}
+
+	pub fn set_ray(&mut self, ray: &[f32; 6]) -> &mut Self {
+		Ray::copy(&mut self.ray, ray);
+		self
+	}
+
+	// Pixel coordinates have the origin at the top left
+	pub fn set_from_camera(
+		&mut self,
+		camera: &dyn Camera,
+		camera_world_matrix: &[f32; 16],
+		x: f32,
+		y: f32,
+		width: f32,
+		height: f32,
+	) -> &mut Self {
+		camera.get_ray_from_pixel(&mut self.ray, x, y, width, height, camera_world_matrix);
+		self
+	}
+
+	// Hits out of the distance range are ignored
+	pub fn set_range(&mut self, near: f32, far: f32) -> &mut Self {
+		self.near = near;
+		self.far = far;
+		self
+	}
+
+	// Bitmask of the layers to test, all the layers by default
+	pub fn set_layers(&mut self, layers: u32) -> &mut Self {
+		self.layers = layers;
+		self
+	}
+
+	pub fn get_layers(&self) -> u32 {
+		self.layers
+	}
+
+	// Sorted by the distance, the nearest first
+	pub fn intersect_scene(
+		&mut self,
+		pools: &ResourcePools,
+		scene: &Scene,
+	) -> Vec<Intersection> {
+		let nodes = scene.collect_visible_nodes(pools);
+		self.intersect_nodes(pools, scene, &nodes)
+	}
+
+	// Sorted by the distance, the nearest first.
+	// Children of the nodes aren't traversed.
+	pub fn intersect_nodes(
+		&mut self,
+		pools: &ResourcePools,
+		scene: &Scene,
+		nodes: &[ResourceId<Node>],
+	) -> Vec<Intersection> {
+		let mut intersections = Vec::new();
+		for node in nodes.iter() {
+			match pools.borrow::<Node>().borrow(node) {
+				Some(node) if node.is_visible() && node.get_layers() & self.layers != 0 => {},
+				_ => continue,
+			};
+			for mesh in scene.borrow_all_assigned_to::<Mesh>(node).iter() {
+				self.intersect_mesh(pools, node, mesh, &mut intersections);
+			}
+		}
+		intersections.sort_by(|a, b| a.distance.partial_cmp(&b.distance).unwrap());
+		intersections
+	}
+
+	fn intersect_mesh(
+		&mut self,
+		pools: &ResourcePools,
+		node_rid: &ResourceId<Node>,
+		mesh_rid: &ResourceId<Mesh>,
+		intersections: &mut Vec<Intersection>,
+	) {
+		let mesh = match pools.borrow::<Mesh>().borrow(mesh_rid) {
+			Some(mesh) => mesh,
+			None => return,
+		};
+
+		let geometry_rid = mesh.borrow_geometry();
+		let geometry = match pools.borrow::<Geometry>().borrow(geometry_rid) {
+			Some(geometry) => geometry,
+			None => return,
+		};
+
+		let material_pool = pools.borrow::<Material>();
+
+		// @TODO: Support points and lines with a threshold
+		if *geometry.borrow_topology() != PrimitiveTopology::TriangleList {
+			return;
+		}
+
+		let position_rid = match geometry.borrow_attribute("position") {
+			Some(rid) => rid,
+			None => return,
+		};
+
+		let positions = match pools.borrow::<Attribute>().borrow(position_rid) {
+			Some(attribute) => match attribute.borrow_attribute_data() {
+				AttributeData::Float32(data) if attribute.get_item_size() == 3 => data,
+				_ => return,
+			},
+			None => return,
+		};
+
+		let world_matrix = match pools.borrow::<Node>().borrow(node_rid) {
+			Some(node) => node.borrow_world_matrix(),
+			None => return,
+		};
+
+		// Early rejection with the bounding sphere
+		if !self.bounding_spheres.contains_key(position_rid) {
+			let sphere = match GeometryProcessor::compute_bounding_sphere(pools, geometry_rid) {
+				Some(sphere) => sphere,
+				None => return,
+			};
+			self.bounding_spheres.insert(*position_rid, sphere);
+		}
+		let mut sphere = Sphere::create();
+		Sphere::copy(&mut sphere, self.bounding_spheres.get(position_rid).unwrap());
+		Sphere::apply_matrix4(&mut sphere, world_matrix);
+		match Ray::intersect_sphere(&self.ray, &sphere) {
+			Some(distance) if distance <= self.far => {},
+			_ => return,
+		};
+
+		// Tests in the geometry local space
+		let mut inverse_matrix = *world_matrix;
+		Matrix4::invert(&mut inverse_matrix);
+		let mut local_ray = self.ray;
+		Ray::apply_matrix4(&mut local_ray, &inverse_matrix);
+
+		// Mirroring transforms flip the winding seen from the ray
+		let flipped = Matrix4::determinant(world_matrix) < 0.0;
+
+		let indices = match geometry.borrow_index() {
+			Some(rid) => match pools.borrow::<Index>().borrow(rid) {
+				Some(index) => Some(index.borrow_data()),
+				None => return,
+			},
+			None => None,
+		};
+		let count = match indices {
+			Some(indices) => indices.len(),
+			None => positions.len() / 3,
+		};
+
+		// Faces of each group are tested as its material culls them
+		let ranges = match geometry.borrow_groups().is_empty() {
+			true => vec![(0, count, 0)],
+			false => geometry.borrow_groups().iter().map(|group| {
+				let start = (group.start as usize).min(count);
+				(start, (group.start as usize + group.count as usize).min(count), group.material_index)
+			}).collect::<Vec<(usize, usize, usize)>>(),
+		};
+
+		let get_position = |index: usize| [
+			positions[index * 3],
+			positions[index * 3 + 1],
+			positions[index * 3 + 2],
+		];
+
+		for (start, end, material_index) in ranges {
+			let side = match mesh.borrow_materials().get(material_index).and_then(|rid| material_pool.borrow(rid)) {
+				Some(material) => material.borrow_side(),
+				None => continue,
+			};
+
+			let (front, back) = match (side, flipped) {
+				(Side::FrontSide, false) | (Side::BackSide, true) => (true, false),
+				(Side::BackSide, false) | (Side::FrontSide, true) => (false, true),
+				(Side::DoubleSide, _) => (true, true),
+			};
+
+			for face_index in (start / 3)..(end / 3) {
+				let (a, b, c) = match indices {
+					Some(indices) => (
+						indices[face_index * 3] as usize,
+						indices[face_index * 3 + 1] as usize,
+						indices[face_index * 3 + 2] as usize,
+					),
+					None => (face_index * 3, face_index * 3 + 1, face_index * 3 + 2),
+				};
+
+				let a = get_position(a);
+				let b = get_position(b);
+				let c = get_position(c);
+
+				// Back faces are tested as front faces of the reversed triangle
+				let hit = match (front, back) {
+					(true, false) => Ray::intersect_triangle(&local_ray, &a, &b, &c, true)
+						.map(|hit| [hit[1], hit[2]]),
+					(false, true) => Ray::intersect_triangle(&local_ray, &a, &c, &b, true)
+						.map(|hit| [hit[2], hit[1]]),
+					_ => Ray::intersect_triangle(&local_ray, &a, &b, &c, false)
+						.map(|hit| [hit[1], hit[2]]),
+				};
+
+				let uv = match hit {
+					Some(uv) => uv,
+					None => continue,
+				};
+
+				// Distance in world space as the local space may be scaled
+				let mut point = Vector3::create();
+				for i in 0..3 {
+					point[i] = a[i] * (1.0 - uv[0] - uv[1]) + b[i] * uv[0] + c[i] * uv[1];
+				}
+				Vector3::apply_matrix4(&mut point, world_matrix);
+				let distance = Vector3::distance(&Ray::get_origin(&self.ray), &point);
+
+				if distance < self.near || distance > self.far {
+					continue;
+				}
+
+				intersections.push(Intersection {
+					distance,
+					face_index,
+					material_index,
+					mesh: *mesh_rid,
+					node: *node_rid,
+					point,
+					uv,
+				});
+			}
+		}
+	}
+}
@@ -0,0 +1,248 @@
+use std::io::{
+	Error,
+	ErrorKind,
+	Result,
+};
+
+use crate::{
+	geometry::{
+		attribute::Attribute,
+		geometry::Geometry,
+	},
+	math::vector3::Vector3,
+	resource::resource::{
+		ResourceId,
+		ResourcePools,
+	},
+	utils::file_loader::FileLoader,
+};
+
+const BINARY_HEADER_SIZE: usize = 84;
+const BINARY_FACET_SIZE: usize = 50;
+
+// Facet normal and vertices
+type Facet = ([f32; 3], [[f32; 3]; 3]);
+
+fn read_f32(bytes: &[u8], offset: usize) -> f32 {
+	f32::from_le_bytes([bytes[offset], bytes[offset + 1], bytes[offset + 2], bytes[offset + 3]])
+}
+
+fn read_vector3(bytes: &[u8], offset: usize) -> [f32; 3] {
+	[read_f32(bytes, offset), read_f32(bytes, offset + 4), read_f32(bytes, offset + 8)]
+}
+
+// Binary STL can also start with "solid" so the size is checked first
+fn is_binary(bytes: &[u8]) -> bool {
+	if bytes.len() >= BINARY_HEADER_SIZE {
+		let count = u32::from_le_bytes([bytes[80], bytes[81], bytes[82], bytes[83]]) as usize;
+		if BINARY_HEADER_SIZE + count * BINARY_FACET_SIZE == bytes.len() {
+			return true;
+		}
+	}
+	!bytes.trim_ascii_start().starts_with(b"solid")
+}
+
+fn invalid_data(message: &str) -> Error {
+	Error::new(ErrorKind::InvalidData, message)
+}
+
+fn parse_binary(bytes: &[u8]) -> Result<Vec<Facet>> {
+	if bytes.len() < BINARY_HEADER_SIZE {
+		return Err(invalid_data("STL file is truncated"));
+	}
+	let count = u32::from_le_bytes([bytes[80], bytes[81], bytes[82], bytes[83]]) as usize;
+	let mut facets = Vec::with_capacity(count);
+	for i in 0..count {
+		let offset = BINARY_HEADER_SIZE + i * BINARY_FACET_SIZE;
+		if offset + BINARY_FACET_SIZE > bytes.len() {
+			return Err(invalid_data("STL file is truncated"));
+		}
+		// The last two bytes are the attribute byte count, unused
+		facets.push((
+			read_vector3(bytes, offset),
+			[
+				read_vector3(bytes, offset + 12),
+				read_vector3(bytes, offset + 24),
+				read_vector3(bytes, offset + 36),
+			],
+		));
+	}
+	Ok(facets)
+}
+
+// Loops with more than three vertices, which some exporters write,
+// are triangulated as fan.
+fn parse_ascii(text: &str) -> Result<Vec<Facet>> {
+	let mut facets = Vec::new();
+	let mut normal = [0.0; 3];
+	let mut vertices = Vec::new();
+	let mut tokens = text.split_whitespace();
+
+	let read_vector3 = |tokens: &mut std::str::SplitWhitespace| -> Result<[f32; 3]> {
+		let mut v = [0.0; 3];
+		for value in v.iter_mut() {
+			*value = match tokens.next() {
+				Some(token) => token.parse::<f32>().map_err(|_| invalid_data("STL has an invalid number"))?,
+				None => return Err(invalid_data("STL file is truncated")),
+			};
+		}
+		Ok(v)
+	};
+
+	while let Some(token) = tokens.next() {
+		match token {
+			"normal" => normal = read_vector3(&mut tokens)?,
+			"vertex" => vertices.push(read_vector3(&mut tokens)?),
+			"endloop" => {
+				for i in 1..vertices.len().max(2) - 1 {
+					facets.push((normal, [vertices[0], vertices[i], vertices[i + 1]]));
+				}
+				vertices.clear();
+			},
+			_ => {},
+		};
+	}
+	Ok(facets)
+}
+
+pub struct StlLoader {
+}
+
+impl StlLoader {
+	// Loads binary or ASCII STL as a non-indexed geometry. Facet normals
+	// are computed from the vertex winding because many exporters write
+	// zero or inaccurate ones, and the stored ones are used only for
+	// degenerate facets. STL has no uvs so they are zero. Truncated or
+	// malformed files return InvalidData error.
+	pub fn load_stl<R: std::io::Read>(
+		pools: &mut ResourcePools,
+		mut reader: R,
+	) -> Result<ResourceId<Geometry>> {
+		let mut bytes = Vec::new();
+		reader.read_to_end(&mut bytes)?;
+
+		let facets = match is_binary(&bytes) {
+			true => parse_binary(&bytes)?,
+			false => parse_ascii(&String::from_utf8_lossy(&bytes))?,
+		};
+
+		let mut positions = Vec::with_capacity(facets.len() * 9);
+		let mut normals = Vec::with_capacity(facets.len() * 9);
+		let mut face_normal = Vector3::create();
+		let mut edge = Vector3::create();
+
+		for (normal, vertices) in facets.iter() {
+			Vector3::copy(&mut face_normal, &vertices[1]);
+			Vector3::sub(&mut face_normal, &vertices[0]);
+			Vector3::copy(&mut edge, &vertices[2]);
+			Vector3::sub(&mut edge, &vertices[0]);
+			Vector3::cross(&mut face_normal, &edge);
+			if Vector3::length(&face_normal) == 0.0 {
+				Vector3::copy(&mut face_normal, normal);
+			}
+			Vector3::normalize(&mut face_normal);
+
+			for vertex in vertices.iter() {
+				positions.extend_from_slice(vertex);
+				normals.extend_from_slice(&face_normal);
+			}
+		}
+
+		let uvs = vec![0.0; facets.len() * 6];
+
+		let mut geometry = Geometry::new();
+		geometry.set_attribute("position", pools.borrow_mut::<Attribute>().add(Attribute::new(positions, 3)));
+		geometry.set_attribute("normal", pools.borrow_mut::<Attribute>().add(Attribute::new(normals, 3)));
+		geometry.set_attribute("uv", pools.borrow_mut::<Attribute>().add(Attribute::new(uvs, 2)));
+		Ok(pools.borrow_mut::<Geometry>().add(geometry))
+	}
+
+	pub async fn load_stl_with_filepath(
+		pools: &mut ResourcePools,
+		file_path: &str,
+	) -> Result<ResourceId<Geometry>> {
+		Self::load_stl(pools, FileLoader::open(file_path).await)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::io::ErrorKind;
+	use crate::{
+		geometry::{
+			attribute::Attribute,
+			geometry::Geometry,
+		},
+		resource::resource::{
+			ResourceId,
+			ResourcePools,
+		},
+	};
+	use super::StlLoader;
+
+	fn create_binary(header: &[u8], count: u32, facets: &[[f32; 12]]) -> Vec<u8> {
+		let mut bytes = header.to_vec();
+		bytes.resize(80, 0);
+		bytes.extend_from_slice(&count.to_le_bytes());
+		for facet in facets.iter() {
+			for value in facet.iter() {
+				bytes.extend_from_slice(&value.to_le_bytes());
+			}
+			bytes.extend_from_slice(&[0, 0]);
+		}
+		bytes
+	}
+
+	fn borrow_positions<'a>(pools: &'a ResourcePools, geometry_rid: &ResourceId<Geometry>) -> &'a Vec<f32> {
+		let geometry = pools.borrow::<Geometry>().borrow(geometry_rid).unwrap();
+		pools.borrow::<Attribute>().borrow(geometry.borrow_attribute("position").unwrap()).unwrap().borrow_data()
+	}
+
+	const FACET: [f32; 12] = [
+		0.0, 0.0, 1.0,
+		0.0, 0.0, 0.0,
+		1.0, 0.0, 0.0,
+		0.0, 1.0, 0.0,
+	];
+
+	#[test]
+	fn load_binary_starting_with_solid() {
+		let mut pools = ResourcePools::new();
+		let bytes = create_binary(b"solid exported as binary", 1, &[FACET]);
+		let geometry = StlLoader::load_stl(&mut pools, bytes.as_slice()).unwrap();
+		assert_eq!(borrow_positions(&pools, &geometry), &FACET[3..].to_vec());
+	}
+
+	#[test]
+	fn load_ascii() {
+		let mut pools = ResourcePools::new();
+		let text = "solid triangle
+facet normal 0 0 1
+  outer loop
+    vertex 0 0 0
+    vertex 1 0 0
+    vertex 0 1 0
+  endloop
+endfacet
+endsolid triangle
+";
+		let geometry = StlLoader::load_stl(&mut pools, text.as_bytes()).unwrap();
+		assert_eq!(borrow_positions(&pools, &geometry), &FACET[3..].to_vec());
+	}
+
+	#[test]
+	fn truncated_file_is_invalid_data() {
+		let mut pools = ResourcePools::new();
+
+		let bytes = create_binary(b"binary", 2, &[FACET]);
+		let error = StlLoader::load_stl(&mut pools, bytes.as_slice()).unwrap_err();
+		assert_eq!(error.kind(), ErrorKind::InvalidData);
+
+		let error = StlLoader::load_stl(&mut pools, &b"binary"[..]).unwrap_err();
+		assert_eq!(error.kind(), ErrorKind::InvalidData);
+
+		let text = "solid triangle\nfacet normal 0 0 1\nouter loop\nvertex 0 0";
+		let error = StlLoader::load_stl(&mut pools, text.as_bytes()).unwrap_err();
+		assert_eq!(error.kind(), ErrorKind::InvalidData);
+	}
+}
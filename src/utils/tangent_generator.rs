@@ -0,0 +1,141 @@
+use crate::{
+	geometry::{
+		attribute::{
+			Attribute,
+			AttributeData,
+		},
+		geometry::Geometry,
+	},
+	resource::resource::{
+		ResourceId,
+		ResourcePools,
+	},
+	utils::geometry_processor::GeometryProcessor,
+};
+
+// Adapts the non-indexed geometry data to the MikkTSpace interface,
+// a vertex per face corner
+struct MikkTSpaceGeometry<'a> {
+	normals: &'a Vec<f32>,
+	positions: &'a Vec<f32>,
+	tangents: Vec<f32>,
+	uvs: &'a Vec<f32>,
+}
+
+impl<'a> MikkTSpaceGeometry<'a> {
+	fn get_index(&self, face: usize, vert: usize) -> usize {
+		face * 3 + vert
+	}
+}
+
+impl<'a> bevy_mikktspace::Geometry for MikkTSpaceGeometry<'a> {
+	fn num_faces(&self) -> usize {
+		self.positions.len() / 9
+	}
+
+	fn num_vertices_of_face(&self, _face: usize) -> usize {
+		3
+	}
+
+	fn position(&self, face: usize, vert: usize) -> [f32; 3] {
+		let index = self.get_index(face, vert) * 3;
+		[self.positions[index], self.positions[index + 1], self.positions[index + 2]]
+	}
+
+	fn normal(&self, face: usize, vert: usize) -> [f32; 3] {
+		let index = self.get_index(face, vert) * 3;
+		[self.normals[index], self.normals[index + 1], self.normals[index + 2]]
+	}
+
+	fn tex_coord(&self, face: usize, vert: usize) -> [f32; 2] {
+		let index = self.get_index(face, vert) * 2;
+		[self.uvs[index], self.uvs[index + 1]]
+	}
+
+	fn set_tangent_encoded(&mut self, tangent: [f32; 4], face: usize, vert: usize) {
+		let index = self.get_index(face, vert) * 4;
+		self.tangents[index..index + 4].copy_from_slice(&tangent);
+	}
+}
+
+pub struct TangentGenerator {
+}
+
+impl TangentGenerator {
+	// Generates MikkTSpace tangents from the position, normal and uv
+	// attributes and sets them as "tangent" attribute, xyz is the
+	// tangent and w is the bitangent sign as in glTF. Returns false
+	// if the geometry lacks attributes or the generation fails.
+	// An indexed geometry is de-indexed to generate the tangents per
+	// face corner and then the equal vertices are welded again, so
+	// the vertices on UV seams and mirrored UVs are split as MikkTSpace
+	// requires. It stays non-indexed if the split vertices don't fit
+	// in u16 index.
+	pub fn generate(
+		pools: &mut ResourcePools,
+		geometry_rid: &ResourceId<Geometry>,
+	) -> bool {
+		let indexed = match pools.borrow::<Geometry>().borrow(geometry_rid) {
+			Some(geometry) => geometry.borrow_index().is_some(),
+			None => return false,
+		};
+
+		if indexed && !GeometryProcessor::to_non_indexed(pools, geometry_rid) {
+			return false;
+		}
+
+		let generated = match generate_tangents(pools, geometry_rid) {
+			Some(tangents) => {
+				let tangent = pools.borrow_mut::<Attribute>().add(Attribute::new(tangents, 4));
+				pools.borrow_mut::<Geometry>().borrow_mut(geometry_rid).unwrap().set_attribute("tangent", tangent);
+				true
+			},
+			None => false,
+		};
+
+		// Welded also on failure to restore the index
+		if indexed {
+			GeometryProcessor::merge_vertices(pools, geometry_rid, 0.0);
+		}
+
+		generated
+	}
+}
+
+// From the non-indexed geometry. None if the geometry lacks attributes,
+// they aren't Float32 with matching counts or the generation fails.
+fn generate_tangents(
+	pools: &ResourcePools,
+	geometry_rid: &ResourceId<Geometry>,
+) -> Option<Vec<f32>> {
+	let geometry = pools.borrow::<Geometry>().borrow(geometry_rid)?;
+	let attribute_pool = pools.borrow::<Attribute>();
+	let borrow_data = |name, item_size| {
+		let attribute = attribute_pool.borrow(geometry.borrow_attribute(name)?)?;
+		match attribute.borrow_attribute_data() {
+			AttributeData::Float32(data) if attribute.get_item_size() == item_size => Some(data),
+			_ => None,
+		}
+	};
+
+	let positions = borrow_data("position", 3)?;
+	let normals = borrow_data("normal", 3)?;
+	let uvs = borrow_data("uv", 2)?;
+
+	if normals.len() != positions.len() || uvs.len() / 2 != positions.len() / 3 {
+		return None;
+	}
+
+	let mut mikktspace_geometry = MikkTSpaceGeometry {
+		normals,
+		positions,
+		tangents: vec![0.0; positions.len() / 3 * 4],
+		uvs,
+	};
+
+	if !bevy_mikktspace::generate_tangents(&mut mikktspace_geometry) {
+		return None;
+	}
+
+	Some(mikktspace_geometry.tangents)
+}
@@ -49,10 +49,6 @@ async fn create_scene(
 			.unwrap()
 			.add_node(node);
 		objects.push(*node);
-		pools.borrow_mut::<Node>()
-			.borrow_mut(node)
-			.unwrap()
-			.borrow_rotation_mut()[0] = 90.0_f32.to_radians();
 	}
 
 	let window_size = get_window_inner_size();
@@ -122,7 +118,8 @@ fn render(
 	scene: &ResourceId<Scene>,
 	camera: &ResourceId<PerspectiveCamera>,
 ) {
-	renderer.render(pools, scene, camera);
+	// @TODO: Proper error handling
+	renderer.render(pools, scene, camera).unwrap();
 }
 
 #[wasm_bindgen(start)]